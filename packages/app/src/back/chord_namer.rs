@@ -1,43 +1,107 @@
+use alloc::collections::btree_map::BTreeMap;
+use alloc::collections::btree_set::BTreeSet;
 use music::Notes;
 use spur::{Publish as _, React};
 
 use crate::broker::Broker;
-use crate::messages::{ActiveNotesChanged, HoldPedalPressed, HoldPedalReleased, NoteOff, NoteOn};
+use crate::messages::{
+    ActiveNotesChanged, HoldPedalPressed, HoldPedalReleased, NoteOff, NoteOn, SoftPedalPressed,
+    SoftPedalReleased, SostenutoPedalPressed, SostenutoPedalReleased, Source, SourceReset,
+};
+
+/// Hold pedal depths below this value only partially lift the dampers
+const FULL_PEDAL_DEPTH: u8 = 96;
 
 pub struct NoteGrouper {
-    held: Notes,
-    sustain: bool,
+    /// a note held on any source and channel counts as held
+    held: BTreeMap<(Source, /* channel */ u8), Notes>,
+    /// depth of the pressed hold pedals; any of them sustains the notes
+    hold_pedals: BTreeMap<(Source, /* channel */ u8), u8>,
+    /// sources whose una corda pedal is pressed
+    soft_pedals: BTreeSet<Source>,
+    /// notes that were held at the moment the sostenuto pedal was pressed
+    sostenuto: Notes,
+    /// sources whose sostenuto pedal is pressed
+    sostenuto_pedals: BTreeSet<Source>,
+    /// notes sustained by the hold pedal
     sustained: Notes,
+    velocities: [u8; 128],
 }
 
 impl NoteGrouper {
     pub const fn new() -> Self {
         Self {
-            held: Notes::empty(),
-            sustain: false,
+            held: BTreeMap::new(),
+            hold_pedals: BTreeMap::new(),
+            soft_pedals: BTreeSet::new(),
+            sostenuto: Notes::empty(),
+            sostenuto_pedals: BTreeSet::new(),
             sustained: Notes::empty(),
+            velocities: [0; 128],
         }
     }
 
+    fn held(&self) -> Notes {
+        self.held
+            .values()
+            .fold(Notes::empty(), |held, notes| held.union(notes))
+    }
+
+    fn is_half_pedal(&self) -> bool {
+        self.hold_pedals
+            .values()
+            .max()
+            .is_some_and(|depth| *depth < FULL_PEDAL_DEPTH)
+    }
+
     fn publish(&self) {
+        let held = self.held();
+        let sostenuto = self.sostenuto.difference(&held).difference(&self.sustained);
         Broker::publish(ActiveNotesChanged {
-            held: self.held.clone(),
-            sustained: self.sustained.difference(&self.held),
+            half_pedal: self.is_half_pedal(),
+            soft_pedal: !self.soft_pedals.is_empty(),
+            sustained: self.sustained.union(&sostenuto).difference(&held),
+            sostenuto,
+            held,
+            velocities: self.velocities,
         })
     }
 }
 
 impl React<NoteOff> for NoteGrouper {
-    fn react(&mut self, NoteOff(note, _): NoteOff) {
-        self.held.remove(note);
+    fn react(
+        &mut self,
+        NoteOff {
+            note,
+            channel,
+            source,
+            ..
+        }: NoteOff,
+    ) {
+        if let Some(notes) = self.held.get_mut(&(source, channel)) {
+            notes.remove(note);
+        }
         self.publish();
     }
 }
 
 impl React<NoteOn> for NoteGrouper {
-    fn react(&mut self, NoteOn(note, _): NoteOn) {
-        self.held.insert(note);
-        if self.sustain {
+    fn react(
+        &mut self,
+        NoteOn {
+            note,
+            channel,
+            source,
+            velocity,
+            ..
+        }: NoteOn,
+    ) {
+        self.velocities[usize::from(note.as_u8())] = velocity;
+        self.held
+            .entry((source, channel))
+            .or_insert_with(Notes::empty)
+            .insert(note);
+        if !self.hold_pedals.is_empty() {
             self.sustained.insert(note);
         }
         self.publish();
@@ -45,16 +109,97 @@ impl React<NoteOn> for NoteGrouper {
 }
 
 impl React<HoldPedalPressed> for NoteGrouper {
-    fn react(&mut self, _message: HoldPedalPressed) {
-        self.sustain = true;
-        self.sustained = self.held.clone();
+    fn react(
+        &mut self,
+        HoldPedalPressed {
+            channel,
+            source,
+            depth,
+            ..
+        }: HoldPedalPressed,
+    ) {
+        let was_pressed = !self.hold_pedals.is_empty();
+        let was_half_pedal = self.is_half_pedal();
+
+        self.hold_pedals.insert((source, channel), depth);
+        self.sustained = self.sustained.union(&self.held());
+
+        // pressing the pedal does not change the active notes but going to or from half pedal
+        // changes how the sustained ones are displayed
+        let is_half_pedal = self.is_half_pedal();
+        if was_pressed && was_half_pedal != is_half_pedal {
+            self.publish();
+        }
     }
 }
 
 impl React<HoldPedalReleased> for NoteGrouper {
-    fn react(&mut self, _: HoldPedalReleased) {
-        self.sustain = false;
-        self.sustained.clear();
+    fn react(
+        &mut self,
+        HoldPedalReleased {
+            channel, source, ..
+        }: HoldPedalReleased,
+    ) {
+        let was_half_pedal = self.is_half_pedal();
+
+        self.hold_pedals.remove(&(source, channel));
+        if self.hold_pedals.is_empty() {
+            self.sustained.clear();
+            self.publish();
+        } else if was_half_pedal != self.is_half_pedal() {
+            self.publish();
+        }
+    }
+}
+
+impl React<SostenutoPedalPressed> for NoteGrouper {
+    fn react(&mut self, SostenutoPedalPressed(source): SostenutoPedalPressed) {
+        // notes pressed while the pedal is down are not sustained by it
+        if self.sostenuto_pedals.insert(source) {
+            self.sostenuto = self.sostenuto.union(&self.held());
+        }
+    }
+}
+
+impl React<SostenutoPedalReleased> for NoteGrouper {
+    fn react(&mut self, SostenutoPedalReleased(source): SostenutoPedalReleased) {
+        self.sostenuto_pedals.remove(&source);
+        if self.sostenuto_pedals.is_empty() {
+            self.sostenuto.clear();
+            self.publish();
+        }
+    }
+}
+
+impl React<SoftPedalPressed> for NoteGrouper {
+    fn react(&mut self, SoftPedalPressed(source): SoftPedalPressed) {
+        if self.soft_pedals.insert(source) {
+            self.publish();
+        }
+    }
+}
+
+impl React<SoftPedalReleased> for NoteGrouper {
+    fn react(&mut self, SoftPedalReleased(source): SoftPedalReleased) {
+        if self.soft_pedals.remove(&source) {
+            self.publish();
+        }
+    }
+}
+
+impl React<SourceReset> for NoteGrouper {
+    fn react(&mut self, SourceReset(source): SourceReset) {
+        self.held.retain(|(other, _), _| *other != source);
+        self.hold_pedals.retain(|(other, _), _| *other != source);
+        self.soft_pedals.remove(&source);
+        self.sostenuto_pedals.remove(&source);
+
+        if self.hold_pedals.is_empty() {
+            self.sustained.clear();
+        }
+        if self.sostenuto_pedals.is_empty() {
+            self.sostenuto.clear();
+        }
         self.publish();
     }
 }
@@ -3,31 +3,81 @@ use crate::front::canvas::Canvas;
 use crate::front::cof::CircleOfFifths;
 use crate::front::console::Console;
 use crate::front::contour::Contour;
+use crate::front::settings::harmony::HarmonyWindowSelect;
 use crate::front::settings::midi_input::device::MidiInputDeviceSelect;
+use crate::front::settings::midi_input::file::MidiFilePlayer;
+use crate::front::settings::overtones::OvertonesSelect;
+use crate::front::settings::recorder::Recorder;
 use crate::front::settings::scale::ScaleTonicSelect;
+use crate::front::settings::tuning::TuningSelect;
 use crate::front::tonnetz::Tonnetz;
 
 #[spur::subscriptions]
 const _: Broker = {
     /* front */
     #[subscribed(to = crate::front::settings::midi_input::device::Initialize)]
+    #[subscribed(to = crate::front::settings::midi_input::device::PortStateChanged)]
     #[subscribed(to = crate::front::settings::midi_input::device::SelectChanged)]
+    #[subscribed(to = crate::front::settings::midi_input::device::ChannelChanged)]
+    #[subscribed(to = crate::front::settings::midi_input::device::BendRangeChanged)]
+    #[subscribed(to = crate::front::settings::midi_input::device::MidiMessageReceived)]
     const _: MidiInputDeviceSelect = MidiInputDeviceSelect::new();
 
+    #[subscribed(to = crate::front::settings::midi_input::file::Initialize)]
+    #[subscribed(to = crate::front::settings::midi_input::file::SourceSelected)]
+    #[subscribed(to = crate::front::settings::midi_input::file::FileLoaded)]
+    #[subscribed(to = crate::front::settings::midi_input::file::PlayClicked)]
+    #[subscribed(to = crate::front::settings::midi_input::file::SeekChanged)]
+    #[subscribed(to = crate::front::settings::midi_input::file::TempoChanged)]
+    #[subscribed(to = crate::front::settings::midi_input::file::Tick)]
+    const _: MidiFilePlayer = MidiFilePlayer::new();
+
     #[subscribed(to = crate::front::settings::scale::Initialize)]
     #[subscribed(to = crate::messages::NewScaleTypeSelected)]
+    #[subscribed(to = crate::front::settings::scale::CustomScaleSelected)]
+    #[subscribed(to = crate::front::settings::scale::CustomScaleRenamed)]
+    #[subscribed(to = crate::front::settings::scale::DegreeClicked)]
+    #[subscribed(to = crate::front::settings::scale::AutoToggled)]
+    #[subscribed(to = crate::messages::ActiveNotesChanged)]
     const _: ScaleTonicSelect = ScaleTonicSelect::new();
 
+    #[subscribed(to = crate::front::settings::harmony::Initialize)]
+    #[subscribed(to = crate::front::settings::harmony::Changed)]
+    const _: HarmonyWindowSelect = HarmonyWindowSelect::new();
+
+    #[subscribed(to = crate::front::settings::overtones::Initialize)]
+    #[subscribed(to = crate::front::settings::overtones::Changed)]
+    const _: OvertonesSelect = OvertonesSelect::new();
+
+    #[subscribed(to = crate::front::settings::tuning::Initialize)]
+    #[subscribed(to = crate::front::settings::tuning::Changed)]
+    const _: TuningSelect = TuningSelect::new();
+
+    #[subscribed(to = crate::front::settings::recorder::Initialize)]
+    #[subscribed(to = crate::front::settings::recorder::RecordClicked)]
+    #[subscribed(to = crate::front::settings::recorder::DownloadClicked)]
+    #[subscribed(to = crate::messages::NoteOn)]
+    #[subscribed(to = crate::messages::NoteOff)]
+    #[subscribed(to = crate::messages::HoldPedalPressed)]
+    #[subscribed(to = crate::messages::HoldPedalReleased)]
+    #[subscribed(to = crate::messages::SourceReset)]
+    const _: Recorder = Recorder::new();
+
     #[subscribed(to = crate::front::canvas::Initialize)]
     #[subscribed(to = crate::messages::NewScaleTonicSelected)]
     #[subscribed(to = crate::messages::NewScaleTypeSelected)]
     #[subscribed(to = crate::messages::ActiveNotesChanged)]
+    #[subscribed(to = crate::messages::VoicingSelected)]
+    #[subscribed(to = crate::messages::OvertonesChanged)]
     const _: Canvas = Canvas::new();
 
     #[subscribed(to = crate::front::console::Initialize)]
     #[subscribed(to = crate::messages::NewScaleTonicSelected)]
     #[subscribed(to = crate::messages::NewScaleTypeSelected)]
     #[subscribed(to = crate::messages::ActiveNotesChanged)]
+    #[subscribed(to = crate::messages::HarmonyWindowChanged)]
+    #[subscribed(to = crate::messages::TuningChanged)]
+    #[subscribed(to = crate::messages::OvertonesChanged)]
     const _: Console = Console::new();
 
     #[subscribed(to = crate::front::tonnetz::Initialize)]
@@ -40,6 +90,8 @@ const _: Broker = {
     #[subscribed(to = crate::messages::NewScaleTonicSelected)]
     #[subscribed(to = crate::messages::NoteOff)]
     #[subscribed(to = crate::messages::NoteOn)]
+    #[subscribed(to = crate::messages::PitchBendChanged)]
+    #[subscribed(to = crate::messages::ModulationChanged)]
     const _: Contour = Contour::new();
 
     #[subscribed(to = crate::front::cof::Initialize)]
@@ -53,5 +105,10 @@ const _: Broker = {
     #[subscribed(to = crate::messages::NoteOn)]
     #[subscribed(to = crate::messages::HoldPedalPressed)]
     #[subscribed(to = crate::messages::HoldPedalReleased)]
+    #[subscribed(to = crate::messages::SostenutoPedalPressed)]
+    #[subscribed(to = crate::messages::SostenutoPedalReleased)]
+    #[subscribed(to = crate::messages::SoftPedalPressed)]
+    #[subscribed(to = crate::messages::SoftPedalReleased)]
+    #[subscribed(to = crate::messages::SourceReset)]
     const _: NoteGrouper = NoteGrouper::new();
 };
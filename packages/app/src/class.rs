@@ -1,5 +1,7 @@
 #[derive(Clone, Copy)]
 pub enum Class {
+    Cents,
+    ChordAlternatives,
     CofLabel,
     CofPath,
     ColumnContainer,
@@ -7,8 +9,13 @@ pub enum Class {
     Contour,
     ContourGridMajor,
     ContourGridMinor,
+    ContourColumn,
     ContourLine,
+    ContourModulation,
+    ContourModulationLevel,
     Degree,
+    Ghost,
+    HalfPedal,
     Highlight,
     HvCenter,
     InScale,
@@ -22,11 +29,18 @@ pub enum Class {
     PianoColor,
     PianoWhite,
     Pressed,
+    ProgressionPattern,
+    RomanBorrowed,
     RowContainer,
+    ScaleEditor,
+    SoftPedal,
+    Sostenuto,
     Sustained,
     Tonnetz,
     TonnetzCircle,
     TonnetzLabel,
+    TonnetzStep,
+    TonnetzTrail,
 }
 
 // TODO compress `style.css` to not use these long names
@@ -35,15 +49,22 @@ impl Class {
         use Class::*;
 
         match self {
+            Cents => "cents",
+            ChordAlternatives => "chord-alternatives",
             CofPath => "cof-path",
             CofLabel => "cof-label",
             ColumnContainer => "column-container",
             Contour => "contour",
             ContourGridMajor => "contour-grid-major",
             ContourGridMinor => "contour-grid-minor",
+            ContourColumn => "contour-column",
             ContourLine => "contour-line",
+            ContourModulation => "contour-modulation",
+            ContourModulationLevel => "contour-modulation-level",
             Console => "console",
             Degree => "degree",
+            Ghost => "ghost",
+            HalfPedal => "half-pedal",
             Highlight => "highlight",
             HvCenter => "hv-center",
             InScale => "in-scale",
@@ -57,11 +78,18 @@ impl Class {
             PianoColor => "piano-color",
             PianoWhite => "piano-white",
             Pressed => "pressed",
+            ProgressionPattern => "progression-pattern",
+            RomanBorrowed => "roman-borrowed",
             RowContainer => "row-container",
+            ScaleEditor => "scale-editor",
+            SoftPedal => "soft-pedal",
+            Sostenuto => "sostenuto",
             Sustained => "sustained",
             Tonnetz => "tonnetz",
             TonnetzCircle => "tonnetz-circle",
             TonnetzLabel => "tonnetz-label",
+            TonnetzStep => "tonnetz-step",
+            TonnetzTrail => "tonnetz-trail",
         }
     }
 }
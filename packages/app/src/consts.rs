@@ -6,4 +6,6 @@ pub const INITIAL_SCALE_TYPE_INDEX: u8 = 0; // ScaleType::Major
 pub const MIN_KEY: Note = Note::A0;
 pub const MAX_KEY: Note = Note::C8;
 
-pub const MIDI_INPUT_CHANNEL: u8 = 0; // Ch1
+pub const INITIAL_MIDI_INPUT_CHANNEL_INDEX: u8 = 1; // Ch1 (0 is omni)
+pub const NUM_MIDI_CHANNELS: usize = 16;
+pub const INITIAL_PITCH_BEND_RANGE_INDEX: u8 = 1; // ±2 semitones
@@ -9,6 +9,9 @@ pub mod contour;
 pub mod settings;
 pub mod tonnetz;
 
+/// Opacity of a note struck with the lowest velocity
+const MIN_VELOCITY_OPACITY: f64 = 0.3;
+
 pub fn initialize(parent: &Node, midi_access: MIDIAccess) {
     settings::initialize(parent, midi_access);
     canvas::initialize(parent);
@@ -18,3 +21,9 @@ pub fn initialize(parent: &Node, midi_access: MIDIAccess) {
     cof::initialize(&row);
     console::initialize(parent);
 }
+
+/// The harder a note was struck, the more opaque it is drawn; soft notes are faint but still
+/// visible
+fn velocity2opacity(velocity: u8) -> f64 {
+    MIN_VELOCITY_OPACITY + (1. - MIN_VELOCITY_OPACITY) * f64::from(velocity) / 127.
+}
@@ -1,12 +1,15 @@
 use alloc::collections::btree_map::BTreeMap;
-use music::{NoteName, Notes, ScaleType};
+use music::{Chord, NoteName, Notes, OvertoneSeries, Scale, ScaleType, Tuning, Voicing};
 use piano::Piano;
 use spur::{Message, Publish as _, React};
 use web::Node;
 
 use crate::broker::Broker;
 use crate::class::Class;
-use crate::messages::{ActiveNotesChanged, NewScaleTonicSelected, NewScaleTypeSelected};
+use crate::messages::{
+    ActiveNotesChanged, NewScaleTonicSelected, NewScaleTypeSelected, OvertonesChanged,
+    VoicingSelected,
+};
 use crate::{consts, svg};
 
 mod piano;
@@ -33,58 +36,148 @@ pub struct Initialize {
 }
 
 struct State {
+    /// the notes of the suggested voicing
+    ghosts: Notes,
     last_held: Notes,
     last_overtone: Notes,
+    last_sostenuto: Notes,
     last_sustained: Notes,
+    /// the partials highlighted above each sounding note
+    overtones: OvertoneSeries,
     piano: Piano,
     scale_tonic: NoteName,
     scale_type: ScaleType,
+    voicing: Option<Voicing>,
+}
+
+impl State {
+    /// Shows the best name of the chord being played in the selected voicing as ghost keys
+    fn refresh_ghosts(&mut self) {
+        let sounding = self.last_held.union(&self.last_sustained);
+        let scale = Scale {
+            tonic: self.scale_tonic,
+            ty: self.scale_type,
+        };
+        let ghosts = self
+            .voicing
+            .and_then(|voicing| suggest(voicing, sounding, scale))
+            .unwrap_or(Notes::empty());
+
+        for note in ghosts.difference(&self.ghosts) {
+            self.piano.ghost_on(note);
+        }
+
+        for note in self.ghosts.difference(&ghosts) {
+            self.piano.ghost_off(note);
+        }
+
+        self.ghosts = ghosts;
+    }
+
+    /// Highlights the keys closest to the overtones of the sounding notes; overtones shared by
+    /// several notes add up
+    fn refresh_overtones(&mut self) {
+        let sounding = self.last_held.union(&self.last_sustained);
+
+        // only the closest keys are shown, and they do not depend on the tuning
+        let equal = Tuning::default();
+        let mut overtone_power = BTreeMap::new();
+        for note in sounding.iter() {
+            for partial in self.overtones.partials(note, &equal) {
+                if sounding.contains(partial.key) {
+                    continue;
+                }
+
+                *overtone_power.entry(partial.key).or_default() += partial.power;
+            }
+        }
+
+        for (note, power) in &overtone_power {
+            self.piano.overtone_on(*note, *power);
+        }
+
+        for note in self.last_overtone.clone() {
+            if !overtone_power.contains_key(&note) {
+                self.piano.overtone_off(note);
+            }
+        }
+
+        self.last_overtone = overtone_power.keys().copied().collect();
+    }
+}
+
+/// Voices the chord from an octave below the notes being played on
+fn suggest(voicing: Voicing, sounding: Notes, scale: Scale) -> Option<Notes> {
+    let chord = Chord::try_from(sounding).ok()?;
+    let best = chord.identify_ranked(Some(scale)).best()?;
+    let low = chord
+        .lowest()
+        .step(-12)
+        .map_or(consts::MIN_KEY, |note| note.max(consts::MIN_KEY));
+
+    voicing.voice(best.tonic, &best.id, low..=consts::MAX_KEY)
 }
 
 impl React<Initialize> for Canvas {
     fn react(&mut self, Initialize { piano }: Initialize) {
         self.state = Some(State {
+            ghosts: Notes::empty(),
             piano,
             last_held: Notes::empty(),
             last_sustained: Notes::empty(),
+            last_sostenuto: Notes::empty(),
             last_overtone: Notes::empty(),
+            overtones: OvertoneSeries::default(),
             scale_tonic: NoteName::CIRCLE_OF_FIFTHS[consts::INITIAL_SCALE_TONIC_INDEX as usize],
             scale_type: ScaleType::ALL[consts::INITIAL_SCALE_TYPE_INDEX as usize],
+            voicing: None,
         });
     }
 }
 
+impl React<VoicingSelected> for Canvas {
+    fn react(&mut self, VoicingSelected(voicing): VoicingSelected) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        state.voicing = voicing;
+        state.refresh_ghosts();
+    }
+}
+
+impl React<OvertonesChanged> for Canvas {
+    fn react(&mut self, OvertonesChanged(overtones): OvertonesChanged) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        state.overtones = overtones;
+        state.refresh_overtones();
+    }
+}
+
 impl React<NewScaleTonicSelected> for Canvas {
     fn react(&mut self, NewScaleTonicSelected(index): NewScaleTonicSelected) {
-        let Some(State {
-            piano,
-            scale_tonic,
-            scale_type,
-            ..
-        }) = &mut self.state
-        else {
+        let Some(state) = &mut self.state else {
             return;
         };
 
-        *scale_tonic = NoteName::CIRCLE_OF_FIFTHS[index];
-        piano.set_scale(*scale_tonic, *scale_type);
+        state.scale_tonic = NoteName::CIRCLE_OF_FIFTHS[index];
+        state.piano.set_scale(state.scale_tonic, state.scale_type);
+        state.refresh_ghosts();
     }
 }
 
 impl React<NewScaleTypeSelected> for Canvas {
-    fn react(&mut self, NewScaleTypeSelected(index): NewScaleTypeSelected) {
-        let Some(State {
-            piano,
-            scale_type,
-            scale_tonic,
-            ..
-        }) = &mut self.state
-        else {
+    fn react(&mut self, NewScaleTypeSelected(ty): NewScaleTypeSelected) {
+        let Some(state) = &mut self.state else {
             return;
         };
 
-        *scale_type = ScaleType::ALL[index];
-        piano.set_scale(*scale_tonic, *scale_type);
+        state.scale_type = ty;
+        state.piano.set_scale(state.scale_tonic, state.scale_type);
+        state.refresh_ghosts();
     }
 }
 
@@ -94,22 +187,25 @@ impl React<ActiveNotesChanged> for Canvas {
         ActiveNotesChanged {
             held: current_held,
             sustained: current_sustained,
-            ..
+            sostenuto: current_sostenuto,
+            half_pedal,
+            soft_pedal,
+            velocities,
         }: ActiveNotesChanged,
     ) {
-        let Some(State {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+        let State {
             last_held,
+            last_sostenuto,
             last_sustained,
-            last_overtone,
             piano,
             ..
-        }) = &mut self.state
-        else {
-            return;
-        };
+        } = state;
 
         for note in current_held.difference(last_held) {
-            piano.pressed(note);
+            piano.pressed(note, velocities[usize::from(note.as_u8())]);
         }
 
         for note in last_held.difference(&current_held) {
@@ -124,43 +220,22 @@ impl React<ActiveNotesChanged> for Canvas {
             piano.sustain_off(note);
         }
 
-        let mut overtone_power = BTreeMap::new();
-        for note in current_held.union(&current_sustained) {
-            for (half_steps, den) in OVERTONES.iter().copied().zip(2..) {
-                if let Ok(note) = note.step(half_steps) {
-                    if current_held.contains(note) || current_sustained.contains(note) {
-                        continue;
-                    }
-
-                    *overtone_power.entry(note).or_default() += 1. / den as f64;
-                }
-            }
+        for note in current_sostenuto.difference(last_sostenuto) {
+            piano.sostenuto_on(note);
         }
 
-        for (note, power) in &overtone_power {
-            piano.overtone_on(*note, *power);
+        for note in last_sostenuto.difference(&current_sostenuto) {
+            piano.sostenuto_off(note);
         }
 
-        for note in last_overtone.clone() {
-            if !overtone_power.contains_key(&note) {
-                piano.overtone_off(note);
-            }
-        }
+        piano.set_half_pedal(half_pedal);
+        piano.set_soft_pedal(soft_pedal);
 
-        *last_overtone = overtone_power.keys().copied().collect();
         *last_held = current_held;
         *last_sustained = current_sustained;
+        *last_sostenuto = current_sostenuto;
+
+        state.refresh_overtones();
+        state.refresh_ghosts();
     }
 }
-
-const OVERTONES: &[i8] = &[
-    12, // P8
-    19, // P8 + P5
-    24, // 2*P8
-    28, // 2*P8+M3
-    31, // 2*P8+P5
-    34, // 2*P8+m7
-    36, // 3*P8
-    38, // 3*P8+M2
-    40, // 3*P8+M3
-];
@@ -9,7 +9,7 @@ use web::{DominantBaseline, SVGRectElement, SVGSVGElement, SVGTextElement};
 use crate::class::Class;
 use crate::consts::MIN_KEY;
 use crate::css::Percentage;
-use crate::{consts, svg};
+use crate::{consts, front, svg};
 
 mod scale_factor;
 
@@ -20,6 +20,7 @@ const BLACK_KEY_TEXT_HEIGHT: &str = "30%";
 
 pub struct Piano {
     labeled_keys: Vec<Key>,
+    root: SVGSVGElement,
 }
 
 #[derive(Clone)]
@@ -130,6 +131,7 @@ impl Piano {
 
         let piano = Self {
             labeled_keys: keys.into_iter().flatten().collect(),
+            root: parent.clone(),
         };
         piano.set_scale(
             NoteName::CIRCLE_OF_FIFTHS[consts::INITIAL_SCALE_TONIC_INDEX as usize],
@@ -143,7 +145,8 @@ impl Piano {
 
         for (note, key) in notes().zip(&self.labeled_keys) {
             let degree = scale.name2degree(note.name());
-            key.label.set_text_content(&degree.as_str().into());
+            key.label
+                .set_text_content(&scale_ty.degree_str(degree).into());
 
             for other in Degree::ALL {
                 if degree == other {
@@ -162,11 +165,14 @@ impl Piano {
         }
     }
 
-    pub fn pressed(&self, note: Note) {
+    /// The harder the key was struck, the more intense its color
+    pub fn pressed(&self, note: Note, velocity: u8) {
         if let Some(key) = self.get(note) {
             let class = js::String::from(Class::Pressed.as_str());
             key.label.add_class(&class);
             key.front.add_class(&class);
+            let fill_opacity = front::velocity2opacity(velocity);
+            key.front.set_fill_opacity(&js::Float::from(fill_opacity));
         }
     }
 
@@ -175,6 +181,8 @@ impl Piano {
             let class = js::String::from(Class::Pressed.as_str());
             key.label.rm_class(&class);
             key.front.rm_class(&class);
+            // sustained and overtone colors do not depend on the velocity
+            key.front.set_fill_opacity(&js::Integer::from(1));
         }
     }
 
@@ -196,6 +204,39 @@ impl Piano {
         }
     }
 
+    pub fn sostenuto_on(&self, note: Note) {
+        if let Some(key) = self.get(note) {
+            key.front.add_class(&Class::Sostenuto.as_str().into());
+        }
+    }
+
+    pub fn sostenuto_off(&self, note: Note) {
+        if let Some(key) = self.get(note) {
+            key.front.rm_class(&Class::Sostenuto.as_str().into());
+        }
+    }
+
+    /// Sustained notes are drawn fainter while the hold pedal is only partially pressed
+    pub fn set_half_pedal(&self, half_pedal: bool) {
+        let class = js::String::from(Class::HalfPedal.as_str());
+        if half_pedal {
+            self.root.add_class(&class);
+        } else {
+            self.root.rm_class(&class);
+        }
+    }
+
+    /// Pressed keys are drawn duller while the una corda pedal is pressed; their velocity is
+    /// left as struck
+    pub fn set_soft_pedal(&self, soft_pedal: bool) {
+        let class = js::String::from(Class::SoftPedal.as_str());
+        if soft_pedal {
+            self.root.add_class(&class);
+        } else {
+            self.root.rm_class(&class);
+        }
+    }
+
     pub fn overtone_on(&self, note: Note, power: f64) {
         if let Some(key) = self.get(note) {
             let class = js::String::from(Class::Overtone.as_str());
@@ -212,6 +253,19 @@ impl Piano {
         }
     }
 
+    /// Outlines a key the user is not playing, e.g. a suggested voicing
+    pub fn ghost_on(&self, note: Note) {
+        if let Some(key) = self.get(note) {
+            key.front.add_class(&Class::Ghost.as_str().into());
+        }
+    }
+
+    pub fn ghost_off(&self, note: Note) {
+        if let Some(key) = self.get(note) {
+            key.front.rm_class(&Class::Ghost.as_str().into());
+        }
+    }
+
     fn get(&self, note: Note) -> Option<&Key> {
         self.labeled_keys.get(note2index(note))
     }
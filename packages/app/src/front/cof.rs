@@ -58,11 +58,9 @@ impl React<NewScaleTonicSelected> for CircleOfFifths {
 }
 
 impl React<NewScaleTypeSelected> for CircleOfFifths {
-    fn react(&mut self, NewScaleTypeSelected(index): NewScaleTypeSelected) {
+    fn react(&mut self, NewScaleTypeSelected(new_scale_type): NewScaleTypeSelected) {
         let Some(state) = &mut self.state else { return };
 
-        let new_scale_type = ScaleType::ALL[index];
-
         state.canvas.reset_in_key();
         state.canvas.set_scale_type(new_scale_type);
     }
@@ -152,16 +150,20 @@ impl Canvas {
     fn set_scale_type(&self, scale_type: ScaleType) {
         let class = Class::InScale.as_str().into();
         for degree in scale_type.degrees() {
-            let items = &self.items[degree];
-
-            let item = if scale_type.contains(*degree, false) {
-                &items.major
-            } else {
-                &items.minor
-            };
-
-            item.path.add_class(&class);
-            item.label.add_class(&class);
+            let items = &self.items[&degree];
+
+            let is_major = scale_type.contains(degree, false);
+            let is_minor = scale_type.contains(degree, true);
+            // the diminished scales have both chords on each degree; some degrees of the
+            // pentatonic scales have neither but they are still shown
+            let show_major = is_major || !is_minor;
+
+            for (show, item) in [(show_major, &items.major), (is_minor, &items.minor)] {
+                if show {
+                    item.path.add_class(&class);
+                    item.label.add_class(&class);
+                }
+            }
         }
     }
 
@@ -1,32 +1,50 @@
 use alloc::collections::btree_map::BTreeMap;
 use alloc::format;
-use alloc::string::ToString;
-use music::{Chord, Interval, MajorScale, Note, NoteName, NoteNames, Notes, Scale, ScaleType};
+use alloc::string::{String, ToString};
+use music::chord_id::Candidate;
+use music::spelled_pitch::ChordSpelling;
+use music::tuning::{self, Temperament};
+use music::{
+    Chord, HarmonyWindow, Interval, MajorScale, Note, NoteName, NoteNames, Notes, OvertoneSeries,
+    Progression, RomanNumeral, Scale, SpelledInterval, SpelledPitch, Tuning,
+};
 use spur::{Message, Publish as _, React};
-use web::{HtmlDivElement, Node};
+use web::{HtmlDivElement, Node, Performance};
 
 use crate::broker::Broker;
 use crate::class::Class;
 use crate::messages::{
-    ActiveHarmonyChanged, ActiveNotesChanged, NewScaleTonicSelected, NewScaleTypeSelected,
+    ActiveHarmonyChanged, ActiveNotesChanged, HarmonyWindowChanged, NewScaleTonicSelected,
+    NewScaleTypeSelected, OvertonesChanged, TuningChanged,
 };
 use crate::{consts, html};
 
 pub(super) fn initialize(parent: &Node) {
     let container = &html::div(parent, Class::Console);
     let chord_id = html::div(container, Class::Padded);
+    let roman = html::div(container, Class::Padded);
+    let progression = html::div(container, Class::Padded);
     let notes = html::div(container, Class::Padded);
     let intervals = html::div(container, Class::Padded);
+    let set_class = html::div(container, Class::Padded);
+    let overtones = html::div(container, Class::Padded);
 
+    let tonic = NoteName::CIRCLE_OF_FIFTHS[consts::INITIAL_SCALE_TONIC_INDEX as usize];
     Broker::publish(Initialize {
         state: State {
             held_and_sustained: Notes::empty(),
             intervals,
+            set_class,
+            overtones,
+            series: OvertoneSeries::default(),
             notes,
             chord_id,
-            scale: Scale::major(
-                NoteName::CIRCLE_OF_FIFTHS[consts::INITIAL_SCALE_TONIC_INDEX as usize],
-            ),
+            roman,
+            progression,
+            chords: Progression::new(),
+            window: None,
+            scale: Scale::major(tonic),
+            tuning: Tuning::new(tuning::STANDARD_A4, Temperament::Equal, tonic),
         },
     });
 }
@@ -63,7 +81,61 @@ impl React<ActiveNotesChanged> for Console {
             return;
         };
 
-        state.held_and_sustained = held.union(&sustained);
+        let active = held.union(&sustained);
+        // releasing keys does not start a new chord
+        let is_new = !active.difference(&state.held_and_sustained).is_empty();
+        let now = Performance.now();
+        // the chord played last sounded until now
+        if !state.held_and_sustained.is_empty() {
+            state.chords.sound(now);
+        }
+        if let Some(window) = &mut state.window {
+            window.update(&active, now);
+        }
+        state.held_and_sustained = active;
+
+        let numeral = state.refresh();
+        if let (true, Some(numeral)) = (is_new, numeral) {
+            state.chords.push(numeral, now);
+        }
+        state.display_progression();
+    }
+}
+
+impl React<HarmonyWindowChanged> for Console {
+    fn react(&mut self, HarmonyWindowChanged(length): HarmonyWindowChanged) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        match (&mut state.window, length) {
+            (Some(window), Some(length)) => window.set_length(length),
+            (window, length) => *window = length.map(HarmonyWindow::new),
+        }
+
+        state.refresh();
+    }
+}
+
+impl React<TuningChanged> for Console {
+    fn react(&mut self, TuningChanged { a4, temperament }: TuningChanged) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        state.tuning.a4 = a4;
+        state.tuning.temperament = temperament;
+        state.refresh();
+    }
+}
+
+impl React<OvertonesChanged> for Console {
+    fn react(&mut self, OvertonesChanged(series): OvertonesChanged) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        state.series = series;
         state.refresh();
     }
 }
@@ -73,22 +145,33 @@ impl React<NewScaleTonicSelected> for Console {
         if let Some(state) = &mut self.state {
             let tonic = NoteName::CIRCLE_OF_FIFTHS[index];
             state.scale.tonic = tonic;
+            state.tuning.tonic = tonic;
+            state.chords.clear();
             state.refresh();
+            state.display_progression();
         }
     }
 }
 
 impl React<NewScaleTypeSelected> for Console {
-    fn react(&mut self, NewScaleTypeSelected(index): NewScaleTypeSelected) {
+    fn react(&mut self, NewScaleTypeSelected(ty): NewScaleTypeSelected) {
         if let Some(state) = &mut self.state {
-            let ty = ScaleType::ALL[index];
             state.scale.ty = ty;
+            state.chords.clear();
             state.refresh();
+            state.display_progression();
         }
     }
 }
 
-fn display_notes(notes: &HtmlDivElement, scale: Scale, all: &Notes) {
+/// Chord tones are spelled from the root of `chord`, the other notes like in `scale`
+fn spell(note: Note, scale: Scale, chord: Option<ChordSpelling>) -> SpelledPitch {
+    chord
+        .and_then(|chord| chord.get(note.name()))
+        .unwrap_or_else(|| note.name().spell(scale))
+}
+
+fn display_notes(notes: &HtmlDivElement, scale: Scale, chord: Option<ChordSpelling>, all: &Notes) {
     let mut is_first = true;
     let major_scale = MajorScale::new(scale.tonic);
     for note in all.iter() {
@@ -96,7 +179,8 @@ fn display_notes(notes: &HtmlDivElement, scale: Scale, all: &Notes) {
             html::span(notes, " ");
         }
 
-        let span = html::span(notes, &note.display(scale).to_string());
+        let spelling = spell(note, scale, chord);
+        let span = html::span(notes, &note.display_as(spelling).to_string());
         let degree = major_scale.name2degree(note.name());
         span.set_class_name(&degree.as_str().into());
 
@@ -104,7 +188,14 @@ fn display_notes(notes: &HtmlDivElement, scale: Scale, all: &Notes) {
     }
 }
 
-fn display_intervals(intervals: &HtmlDivElement, all: &Notes) {
+/// Each interval is followed by how far it is from its just ratio in `tuning`
+fn display_intervals(
+    intervals: &HtmlDivElement,
+    scale: Scale,
+    tuning: &Tuning,
+    chord: Option<ChordSpelling>,
+    all: &Notes,
+) {
     let mut notes = all.iter();
     let mut last = notes.next().unwrap();
     let mut is_first = true;
@@ -112,87 +203,136 @@ fn display_intervals(intervals: &HtmlDivElement, all: &Notes) {
     for note in notes {
         let half_steps = last.distance_to(note);
         let interval = Interval::from_u8_lossy(half_steps.unsigned_abs());
+        // e.g. A5 rather than m6 in C+
+        let spelled = SpelledInterval::between(
+            last,
+            spell(last, scale, chord),
+            note,
+            spell(note, scale, chord),
+        );
 
         if !is_first {
             html::span(intervals, " ");
         }
 
-        let span = html::span(intervals, interval.as_str());
-        if half_steps < 24 && below_low_interval_limit(last, interval) {
+        let span = html::span(intervals, &spelled.to_string());
+        if half_steps < 24 && interval.is_below_low_limit(last) {
             span.add_class(&lil_warning);
         }
+        let impurity = html::span(intervals, &display_cents(tuning.impurity(last, note)));
+        impurity.set_class_name(&Class::Cents.as_str().into());
 
         last = note;
         is_first = false;
     }
 }
 
-fn below_low_interval_limit(lower: Note, interval: Interval) -> bool {
-    use Interval::*;
-
-    match interval {
-        // unlimited
-        P0 | P8 | P15 => false,
-
-        m2 => lower < Note::E3,
-        M2 => lower < Note::Eb3,
-        m3 => lower < Note::C3,
-        M3 => lower < Note::Bb2,
-        P4 => lower < Note::Bb2,
-        TT => lower < Note::Bb2,
-        P5 => lower < Note::Bb1,
-        m6 => lower < Note::G2,
-        M6 => lower < Note::F2,
-        m7 => lower < Note::F2,
-        M7 => lower < Note::F2,
-        m9 => lower < Note::E2,
-        M9 => lower < Note::Eb2,
-        m10 => lower < Note::C2,
-        M10 => lower < Note::Bb1,
+/// Frequency of `fundamental` then the keys closest to the partials of `series` that sound,
+/// each followed by how far the partial is from the key as tuned
+fn display_overtones(
+    overtones: &HtmlDivElement,
+    scale: Scale,
+    tuning: &Tuning,
+    series: &OvertoneSeries,
+    fundamental: Note,
+) {
+    html::span(
+        overtones,
+        &format!(
+            "{} {:.1} Hz",
+            fundamental.display(scale),
+            tuning.frequency(fundamental)
+        ),
+    );
+
+    for partial in series.partials(fundamental, tuning) {
+        html::span(overtones, &format!(" {}", partial.key.display(scale)));
+        let deviation = html::span(overtones, &display_cents(partial.cents));
+        deviation.set_class_name(&Class::Cents.as_str().into());
+    }
+}
 
-        // no data
-        P11 | A11 | P12 | m13 | M13 | m14 | M14 => false,
+/// Rounded to the cent, e.g. +14¢
+fn display_cents(cents: f64) -> String {
+    if cents.abs() < 0.5 {
+        "±0¢".to_string()
+    } else {
+        format!("{cents:+.0}¢")
     }
 }
 
-fn display_chord_id(chord_id: &HtmlDivElement, all: &Notes, scale: Scale) {
-    let Ok(chord) = Chord::try_from(all.clone()) else {
+/// Post-tonal analysis: prime form and Forte name of the pitch classes
+fn display_set_class(set_class: &HtmlDivElement, all: &Notes) {
+    let names = all.iter().map(|note| note.name()).collect::<NoteNames>();
+    let Some(forte) = names.forte() else {
         return;
     };
 
-    let mut note_names = NoteNames::empty();
-    for note in chord.notes() {
-        note_names.insert(note.name());
-    }
-
-    let mut is_first = true;
-    let mut tonics = BTreeMap::new();
-    for tonic in note_names {
-        if let Some(id) = chord.identify_with_tonic(tonic) {
-            tonics.insert(tonic, id.kind.is_minor());
+    html::span(
+        set_class,
+        &format!("{forte} {}", names.prime_form().display_integers()),
+    );
+}
 
-            if !is_first {
-                html::span(chord_id, " or ");
-            }
+/// Returns the most plausible name
+fn display_chord_id(chord_id: &HtmlDivElement, all: &Notes, scale: Scale) -> Option<Candidate> {
+    let Ok(chord) = Chord::try_from(all.clone()) else {
+        return None;
+    };
 
-            let span = html::span(chord_id, &format!("{}{}", tonic.as_str(scale), id.normal()));
-            html::sup(&span, &id.sup().to_string());
-            html::sub(&span, &id.sub(scale).to_string());
+    let candidates = chord.identify_ranked(Some(scale));
 
-            is_first = false;
-        }
+    let mut tonics = BTreeMap::new();
+    for candidate in candidates.iter() {
+        tonics.insert(candidate.tonic, candidate.id.kind.is_minor());
     }
 
     Broker::publish(ActiveHarmonyChanged { tonics });
 
-    let found_no_id = is_first;
-    if found_no_id {
+    let mut candidates = candidates.iter();
+    let Some(best) = candidates.next() else {
         let intentionally_blank = "　";
 
         let span = html::span(chord_id, intentionally_blank);
         html::sup(&span, intentionally_blank);
         html::sub(&span, intentionally_blank);
+
+        return None;
+    };
+
+    display_candidate(chord_id, best, scale);
+
+    // the less plausible names, in smaller print
+    let alternatives = html::span(chord_id, "");
+    alternatives.set_class_name(&Class::ChordAlternatives.as_str().into());
+    for candidate in candidates {
+        html::span(&alternatives, " ");
+        display_candidate(&alternatives, candidate, scale);
+    }
+
+    Some(best)
+}
+
+fn display_roman(
+    roman: &HtmlDivElement,
+    Candidate { id, tonic, .. }: Candidate,
+    scale: Scale,
+) -> Option<RomanNumeral> {
+    let numeral = RomanNumeral::analyze(tonic, &id, scale)?;
+
+    html::span(roman, &numeral.to_string());
+    if numeral.is_borrowed {
+        let span = html::span(roman, " borrowed");
+        span.set_class_name(&Class::RomanBorrowed.as_str().into());
     }
+
+    Some(numeral)
+}
+
+fn display_candidate(parent: &Node, Candidate { id, tonic, .. }: Candidate, scale: Scale) {
+    let span = html::span(parent, &format!("{}{}", tonic.as_str(scale), id.normal()));
+    html::sup(&span, &id.sup().to_string());
+    html::sub(&span, &id.sub(tonic, scale).to_string());
 }
 
 struct State {
@@ -200,33 +340,98 @@ struct State {
     scale: Scale,
     notes: HtmlDivElement,
     intervals: HtmlDivElement,
+    set_class: HtmlDivElement,
+    overtones: HtmlDivElement,
+    /// the partials listed after the lowest note
+    series: OvertoneSeries,
     chord_id: HtmlDivElement,
+    roman: HtmlDivElement,
+    progression: HtmlDivElement,
+    /// the roman numerals of the last chords played
+    chords: Progression,
+    /// `None` names the chord from the notes that sound together only
+    window: Option<HarmonyWindow>,
+    /// follows the scale tonic
+    tuning: Tuning,
 }
 
 impl State {
-    fn refresh(&self) {
+    /// Returns the roman numeral of the chord being played
+    fn refresh(&self) -> Option<RomanNumeral> {
         let Self {
             scale,
             notes,
             intervals,
+            set_class,
+            overtones,
+            series,
             chord_id,
+            roman,
             held_and_sustained,
+            window,
+            tuning,
+            ..
         } = self;
 
         notes.replace_children0();
         intervals.replace_children0();
+        set_class.replace_children0();
+        overtones.replace_children0();
         chord_id.replace_children0();
+        roman.replace_children0();
+
+        // broken chords are named from the notes played recently
+        let harmony = match window {
+            Some(window) => window.notes(),
+            None => held_and_sustained.clone(),
+        };
 
-        if held_and_sustained.len() < 2 {
+        let best = if harmony.len() < 2 {
             Broker::publish(ActiveHarmonyChanged {
                 tonics: BTreeMap::new(),
             });
 
-            return;
+            None
+        } else {
+            display_chord_id(chord_id, &harmony, *scale)
+        };
+
+        if held_and_sustained.len() >= 2 {
+            let spelling = best.map(|Candidate { id, tonic, .. }| id.spell(tonic, *scale));
+            display_notes(notes, *scale, spelling, held_and_sustained);
+            display_intervals(intervals, *scale, tuning, spelling, held_and_sustained);
+            display_set_class(set_class, held_and_sustained);
         }
 
-        display_notes(notes, *scale, held_and_sustained);
-        display_intervals(intervals, held_and_sustained);
-        display_chord_id(chord_id, held_and_sustained, *scale);
+        if let Some(lowest) = held_and_sustained.iter().next() {
+            display_overtones(overtones, *scale, tuning, series, lowest);
+        }
+
+        best.and_then(|best| display_roman(roman, best, *scale))
+    }
+
+    fn display_progression(&self) {
+        let Self {
+            progression,
+            chords,
+            ..
+        } = self;
+
+        progression.replace_children0();
+
+        let mut is_first = true;
+        for numeral in chords.numerals() {
+            if !is_first {
+                html::span(progression, " → ");
+            }
+
+            html::span(progression, &numeral.to_string());
+            is_first = false;
+        }
+
+        if let Some(pattern) = chords.pattern() {
+            let span = html::span(progression, &format!(" {}", pattern.as_str()));
+            span.set_class_name(&Class::ProgressionPattern.as_str().into());
+        }
     }
 }
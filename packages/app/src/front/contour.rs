@@ -7,13 +7,18 @@ use web::{Node, Performance, SVGAnimateElement, SVGRectElement, SVGSVGElement};
 
 use crate::broker::Broker;
 use crate::class::Class;
-use crate::messages::{NewScaleTonicSelected, NoteOff, NoteOn};
-use crate::{consts, svg};
+use crate::front::velocity2opacity;
+use crate::messages::{
+    ModulationChanged, NewScaleTonicSelected, NoteOff, NoteOn, PitchBendChanged, Source,
+};
+use crate::{consts, html, svg};
 
 pub(super) fn initialize(parent: &Node) {
-    let canvas = Canvas::new(&svg::svg(parent, Class::Contour, false));
+    let column = html::div(parent, Class::ContourColumn);
+    let canvas = Canvas::new(&svg::svg(&column, Class::Contour, false));
+    let modulation = Lane::new(&svg::svg(&column, Class::ContourModulation, true));
 
-    Broker::publish(Initialize { canvas });
+    Broker::publish(Initialize { canvas, modulation });
 }
 
 pub struct Contour {
@@ -29,26 +34,32 @@ impl Contour {
 #[derive(Message)]
 pub struct Initialize {
     canvas: Canvas,
+    modulation: Lane,
 }
 
 impl React<Initialize> for Contour {
-    fn react(&mut self, Initialize { canvas }: Initialize) {
-        self.state = Some(State::new(canvas));
+    fn react(&mut self, Initialize { canvas, modulation }: Initialize) {
+        self.state = Some(State::new(canvas, modulation));
     }
 }
 
 struct State {
     canvas: Canvas,
     begin_zero: f64,
+    /// current pitch bend of each channel, in semitones
+    bends: BTreeMap<(Source, /* channel */ u8), f64>,
     held: Notes,
+    modulation: Lane,
 }
 
 impl State {
-    fn new(canvas: Canvas) -> Self {
+    fn new(canvas: Canvas, modulation: Lane) -> Self {
         Self {
             canvas,
             begin_zero: Performance.now(),
+            bends: BTreeMap::new(),
             held: Notes::empty(),
+            modulation,
         }
     }
 }
@@ -70,7 +81,16 @@ impl React<NewScaleTonicSelected> for Contour {
 }
 
 impl React<NoteOn> for Contour {
-    fn react(&mut self, NoteOn(note, timestamp): NoteOn) {
+    fn react(
+        &mut self,
+        NoteOn {
+            note,
+            timestamp,
+            channel,
+            source,
+            velocity,
+        }: NoteOn,
+    ) {
         let Some(state) = &mut self.state else {
             return;
         };
@@ -78,7 +98,9 @@ impl React<NoteOn> for Contour {
         let begin_timestamp = timestamp - state.begin_zero;
         state.canvas.gc(begin_timestamp);
 
-        state.canvas.on(note, begin_timestamp);
+        let key = (source, channel);
+        let bend = state.bends.get(&key).copied().unwrap_or(0.);
+        state.canvas.on(note, velocity, key, bend, begin_timestamp);
 
         state.held.insert(note);
 
@@ -87,7 +109,12 @@ impl React<NoteOn> for Contour {
 }
 
 impl React<NoteOff> for Contour {
-    fn react(&mut self, NoteOff(note, timestamp): NoteOff) {
+    fn react(
+        &mut self,
+        NoteOff {
+            note, timestamp, ..
+        }: NoteOff,
+    ) {
         let Some(state) = &mut self.state else {
             return;
         };
@@ -101,8 +128,43 @@ impl React<NoteOff> for Contour {
     }
 }
 
+impl React<PitchBendChanged> for Contour {
+    fn react(
+        &mut self,
+        PitchBendChanged {
+            timestamp,
+            channel,
+            source,
+            semitones,
+        }: PitchBendChanged,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        let begin_timestamp = timestamp - state.begin_zero;
+        state.canvas.gc(begin_timestamp);
+
+        let key = (source, channel);
+        state.bends.insert(key, semitones);
+        state.canvas.bend(key, semitones, begin_timestamp);
+    }
+}
+
+impl React<ModulationChanged> for Contour {
+    fn react(&mut self, ModulationChanged { timestamp, value }: ModulationChanged) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        let begin_timestamp = timestamp - state.begin_zero;
+        state.modulation.gc(begin_timestamp);
+        state.modulation.set(value, begin_timestamp);
+    }
+}
+
 struct Canvas {
-    active: BTreeMap<Note, AnimatedLine>,
+    active: BTreeMap<Note, ActiveNote>,
     current_y: i32,
     grid: Vec<SVGRectElement>,
     lines: BTreeMap<i64, Vec<(Note, SVGRectElement)>>,
@@ -112,12 +174,123 @@ struct Canvas {
     view_box_animate: Option<SVGAnimateElement>,
 }
 
+struct ActiveNote {
+    /// pitch bend, in semitones, the line was drawn with
+    bend: f64,
+    key: (Source, /* channel */ u8),
+    line: AnimatedLine,
+    velocity: u8,
+}
+
+/// A line that enters from the right edge and grows until it's stopped
 struct AnimatedLine {
     translate: SVGAnimateElement,
     line: SVGRectElement,
     start: f64,
 }
 
+impl AnimatedLine {
+    fn start(
+        root: &SVGSVGElement,
+        class: Class,
+        y: &js::Value,
+        height: &js::Value,
+        now_ms: f64,
+    ) -> Self {
+        let line = svg::rect(
+            root,
+            class,
+            &js::String::from("100%"),
+            y,
+            &js::String::from("0"),
+            height,
+        );
+
+        let now = now_ms / 1000.;
+        let now_s = format!("{}s", now).as_str().into();
+        let translate = svg::animate(
+            &line,
+            &"x".into(),
+            &now_s,
+            &"8s".into(),
+            &"100%".into(),
+            &"0%".into(),
+        );
+        translate.set_fill(&"freeze".into());
+
+        let grow = svg::animate(
+            &line,
+            &"width".into(),
+            &now_s,
+            &"8s".into(),
+            &"0".into(),
+            &"100%".into(),
+        );
+        grow.set_fill(&"freeze".into());
+
+        Self {
+            line,
+            start: now,
+            translate,
+        }
+    }
+
+    /// Returns the line and the time, in ms, after which it's no longer visible
+    fn stop(self, now_ms: f64) -> (i64, SVGRectElement) {
+        let Self {
+            translate,
+            line,
+            start,
+        } = self;
+
+        let now = now_ms / 1000.;
+        let now_s = format!("{}s", now).as_str().into();
+
+        let freeze_s = "freeze".into();
+        line.replace_children0();
+        if now > start + DUR {
+            // shrink now
+            svg::animate(
+                &line,
+                &"width".into(),
+                &now_s,
+                &"8s".into(),
+                &"100%".into(),
+                &"0%".into(),
+            )
+            .set_fill(&freeze_s);
+
+            let deadline = (1000. * (now + DUR)) as i64 + 1;
+            (deadline, line)
+        } else {
+            let pct = 100. * (now - start) / DUR;
+
+            let dur = DUR * pct / 100.;
+            let dur_s = format!("{}s", dur).as_str().into();
+            let then = start + DUR;
+            let then_s = format!("{}s", then).as_str().into();
+            let pct_s: js::String = format!("{pct}%").as_str().into();
+            line.set_width(&pct_s);
+
+            // shrink then
+            svg::animate(
+                &line,
+                &"width".into(),
+                &then_s,
+                &dur_s,
+                &pct_s,
+                &"0%".into(),
+            )
+            .set_fill(&freeze_s);
+
+            line.append_child(&translate);
+
+            let deadline = (1000. * (then + dur)) as i64 + 1;
+            (deadline, line)
+        }
+    }
+}
+
 const DUR: f64 = 8.;
 const SEMITONE_GAP: i32 = 10;
 const MIN_PIANO_NOTE: Note = Note::A0;
@@ -126,6 +299,9 @@ const HEIGHT: i32 = 300;
 const PAN_COOLDOWN: f64 = 1000.; // ms
 const WIDTH: u32 = 800;
 const PAN_DUR: &str = "1s";
+/// Smallest change of pitch bend, in semitones, that starts a new segment of a line
+const MIN_BEND_STEP: f64 = 0.1;
+const MODULATION_HEIGHT: i32 = 40;
 
 impl Canvas {
     fn new(parent: &SVGSVGElement) -> Self {
@@ -270,109 +446,121 @@ impl Canvas {
         }
     }
 
-    fn on(&mut self, note: Note, now_ms: f64) {
-        let y = note.distance_to(Note::MAX) as i32 * SEMITONE_GAP + SEMITONE_GAP / 2;
-
-        let line = svg::rect(
+    fn on(&mut self, note: Note, velocity: u8, key: (Source, u8), bend: f64, now_ms: f64) {
+        let line = AnimatedLine::start(
             &self.root,
             Class::ContourLine,
-            &js::String::from("100%"),
-            &js::Integer::from(y),
-            &js::String::from("0"),
+            &js::Float::from(line_y(note, bend)),
             &js::Integer::from(1),
+            now_ms,
         );
-        line.set_rx(&js::Integer::from(3));
-        line.set_ry(&js::Integer::from(3));
+        line.line.set_rx(&js::Integer::from(3));
+        line.line.set_ry(&js::Integer::from(3));
+        line.line
+            .set_opacity(&js::Float::from(velocity2opacity(velocity)));
         let degree = self.scale.name2degree(note.name());
-        line.add_class(&js::String::from(degree.as_str()));
-
-        let now = now_ms / 1000.;
-        let now_s = format!("{}s", now).as_str().into();
-        let translate = svg::animate(
-            &line,
-            &"x".into(),
-            &now_s,
-            &"8s".into(),
-            &"100%".into(),
-            &"0%".into(),
-        );
-        translate.set_fill(&"freeze".into());
-
-        let grow = svg::animate(
-            &line,
-            &"width".into(),
-            &now_s,
-            &"8s".into(),
-            &"0".into(),
-            &"100%".into(),
-        );
-        grow.set_fill(&"freeze".into());
+        line.line.add_class(&js::String::from(degree.as_str()));
 
         self.active.insert(
             note,
-            AnimatedLine {
+            ActiveNote {
+                bend,
+                key,
                 line,
-                start: now,
-                translate,
+                velocity,
             },
         );
     }
 
     fn off(&mut self, note: Note, now_ms: f64) {
-        let Some(AnimatedLine {
-            translate,
-            line,
-            start,
-        }) = self.active.remove(&note)
-        else {
+        let Some(ActiveNote { line, .. }) = self.active.remove(&note) else {
             return;
         };
 
-        let now = now_ms / 1000.;
-        let now_s = format!("{}s", now).as_str().into();
+        let (deadline, line) = line.stop(now_ms);
+        self.lines.entry(deadline).or_default().push((note, line));
+    }
 
-        let freeze_s = "freeze".into();
-        line.replace_children0();
-        if now > start + DUR {
-            // shrink now
-            svg::animate(
-                &line,
-                &"width".into(),
-                &now_s,
-                &"8s".into(),
-                &"100%".into(),
-                &"0%".into(),
-            )
-            .set_fill(&freeze_s);
+    /// Continues the lines of the notes held on `key` at their bent pitch
+    fn bend(&mut self, key: (Source, u8), semitones: f64, now_ms: f64) {
+        let bent = self
+            .active
+            .iter()
+            .filter(|(_, active)| {
+                active.key == key
+                    && ((active.bend - semitones).abs() >= MIN_BEND_STEP
+                        // always draw the return to the unbent pitch
+                        || (semitones == 0. && active.bend != 0.))
+            })
+            .map(|(note, active)| (*note, active.velocity))
+            .collect::<Vec<_>>();
+
+        for (note, velocity) in bent {
+            self.off(note, now_ms);
+            self.on(note, velocity, key, semitones, now_ms);
+        }
+    }
 
-            let deadline = (1000. * (now + DUR)) as i64 + 1;
-            self.lines.entry(deadline).or_default().push((note, line));
-        } else {
-            let pct = 100. * (now - start) / DUR;
+    fn gc(&mut self, now: f64) {
+        let now = now as i64;
+        self.lines.retain(|deadline, lines| {
+            if *deadline > now {
+                true
+            } else {
+                for (_note, line) in lines {
+                    self.root.remove_child(line);
+                }
+                false
+            }
+        });
+    }
+}
 
-            let dur = DUR * pct / 100.;
-            let dur_s = format!("{}s", dur).as_str().into();
-            let then = start + DUR;
-            let then_s = format!("{}s", then).as_str().into();
-            let pct_s: js::String = format!("{pct}%").as_str().into();
-            line.set_width(&pct_s);
+/// `bend` is in semitones
+fn line_y(note: Note, bend: f64) -> f64 {
+    f64::from(note.distance_to(Note::MAX) as i32 * SEMITONE_GAP + SEMITONE_GAP / 2)
+        - bend * f64::from(SEMITONE_GAP)
+}
 
-            // shrink then
-            svg::animate(
-                &line,
-                &"width".into(),
-                &then_s,
-                &dur_s,
-                &pct_s,
-                &"0%".into(),
-            )
-            .set_fill(&freeze_s);
+/// A value drawn over time below the contour, e.g. the modulation wheel
+struct Lane {
+    current: Option<AnimatedLine>,
+    lines: BTreeMap<i64, Vec<SVGRectElement>>,
+    root: SVGSVGElement,
+}
 
-            line.append_child(&translate);
+impl Lane {
+    fn new(parent: &SVGSVGElement) -> Self {
+        parent.set_height(&js::String::from(format!("{MODULATION_HEIGHT}px").as_str()));
+        parent.set_view_box(&js::String::from(
+            format!("0 0 {WIDTH} {MODULATION_HEIGHT}").as_str(),
+        ));
 
-            let deadline = (1000. * (then + dur)) as i64 + 1;
-            self.lines.entry(deadline).or_default().push((note, line));
-        };
+        Self {
+            current: None,
+            lines: BTreeMap::new(),
+            root: parent.clone(),
+        }
+    }
+
+    fn set(&mut self, value: u8, now_ms: f64) {
+        if let Some(current) = self.current.take() {
+            let (deadline, line) = current.stop(now_ms);
+            self.lines.entry(deadline).or_default().push(line);
+        }
+
+        if value == 0 {
+            return;
+        }
+
+        let height = f64::from(MODULATION_HEIGHT) * f64::from(value) / 127.;
+        self.current = Some(AnimatedLine::start(
+            &self.root,
+            Class::ContourModulationLevel,
+            &js::Float::from(f64::from(MODULATION_HEIGHT) - height),
+            &js::Float::from(height),
+            now_ms,
+        ));
     }
 
     fn gc(&mut self, now: f64) {
@@ -381,7 +569,7 @@ impl Canvas {
             if *deadline > now {
                 true
             } else {
-                for (_note, line) in lines {
+                for line in lines {
                     self.root.remove_child(line);
                 }
                 false
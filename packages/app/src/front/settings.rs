@@ -3,11 +3,21 @@ use web::{MIDIAccess, Node};
 use crate::class::Class;
 use crate::html;
 
+pub mod harmony;
 pub mod midi_input;
+pub mod overtones;
+pub mod recorder;
 pub mod scale;
+pub mod tuning;
+pub mod voicing;
 
 pub(super) fn initialize(parent: &Node, midi_access: MIDIAccess) {
     let form = html::form(parent, Class::ColumnContainer);
     midi_input::initialize(&form, midi_access);
     scale::initialize(&form);
+    voicing::initialize(&form);
+    harmony::initialize(&form);
+    overtones::initialize(&form);
+    tuning::initialize(&form);
+    recorder::initialize(&form);
 }
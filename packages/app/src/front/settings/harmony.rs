@@ -0,0 +1,112 @@
+use music::harmony_window::Length;
+use spur::{Message, Publish as _, React};
+use web::{HtmlInputElement, HtmlSelectElement};
+
+use crate::broker::Broker;
+use crate::html;
+use crate::html::Form;
+use crate::messages::HarmonyWindowChanged;
+
+/// Lengths of the window; the first one only looks at the notes that sound together. The tempo
+/// of the lengths in beats comes from the BPM input
+const LENGTHS: [(Option<Length>, &str); 7] = [
+    (None, "off"),
+    (Some(Length::Millis(500.)), "½ s"),
+    (Some(Length::Millis(1_000.)), "1 s"),
+    (Some(Length::Millis(2_000.)), "2 s"),
+    (Some(Length::Beats { count: 1., bpm: 0. }), "1 beat"),
+    (Some(Length::Beats { count: 2., bpm: 0. }), "2 beats"),
+    (Some(Length::Beats { count: 4., bpm: 0. }), "4 beats"),
+];
+const INITIAL_BPM: f64 = 100.;
+const MIN_BPM: f64 = 20.;
+const MAX_BPM: f64 = 300.;
+
+pub(super) fn initialize(form: &Form) {
+    let fieldset = form.fieldset(&"Harmony window".into());
+
+    let select = html::select(&fieldset, &"harmony-window".into());
+    select.set_required(true);
+    for (_, text) in LENGTHS {
+        html::option(&select, None, &text.into());
+    }
+    select.set_selected_index(0);
+    select.set_onchange(|_| Broker::publish(Changed));
+
+    let bpm = html::input(&fieldset, &"number".into());
+    bpm.set_min(MIN_BPM);
+    bpm.set_max(MAX_BPM);
+    bpm.set_value_as_number(INITIAL_BPM);
+    bpm.set_disabled(true);
+    bpm.set_onchange(|_| Broker::publish(Changed));
+    html::span(&fieldset, "BPM");
+
+    Broker::publish(Initialize(State { bpm, select }));
+}
+
+pub struct HarmonyWindowSelect {
+    state: Option<State>,
+}
+
+impl HarmonyWindowSelect {
+    pub const fn new() -> Self {
+        Self { state: None }
+    }
+}
+
+#[derive(Message)]
+pub struct Initialize(State);
+
+/// Either the length or the tempo changed
+#[derive(Message)]
+pub struct Changed;
+
+impl React<Initialize> for HarmonyWindowSelect {
+    fn react(&mut self, Initialize(state): Initialize) {
+        self.state = Some(state);
+    }
+}
+
+impl React<Changed> for HarmonyWindowSelect {
+    fn react(&mut self, _: Changed) {
+        let Some(State { bpm, select }) = &self.state else {
+            return;
+        };
+
+        let Some((length, _)) = usize::try_from(select.selected_index())
+            .ok()
+            .and_then(|index| LENGTHS.get(index))
+        else {
+            return;
+        };
+
+        let length = match length {
+            Some(Length::Beats { count, .. }) => {
+                bpm.set_disabled(false);
+
+                // empty or out of range inputs fall back to the initial tempo
+                let mut tempo = bpm.value_as_number();
+                if !(MIN_BPM..=MAX_BPM).contains(&tempo) {
+                    tempo = INITIAL_BPM;
+                }
+
+                Some(Length::Beats {
+                    count: *count,
+                    bpm: tempo,
+                })
+            }
+
+            _ => {
+                bpm.set_disabled(true);
+                *length
+            }
+        };
+
+        Broker::publish(HarmonyWindowChanged(length));
+    }
+}
+
+struct State {
+    bpm: HtmlInputElement,
+    select: HtmlSelectElement,
+}
@@ -1,30 +1,95 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
 use js::{Downcast as _, Upcast as _};
-use music::Note;
+use music::Notes;
+use music::midi::{
+    self, CONTROL_HOLD_PEDAL, CONTROL_MODULATION_WHEEL, CONTROL_SOFT_PEDAL,
+    CONTROL_SOSTENUTO_PEDAL, MidiDecoder, MidiMessage, PitchBendSensitivity,
+};
 use spur::{Message, Publish as _, React};
-use web::{HtmlSelectElement, MIDIAccess, MIDIMessageEvent, Node};
+use web::{
+    Document, HtmlOptionElement, HtmlSelectElement, MIDIAccess, MIDIInput, MIDIMessageEvent,
+    MIDIPort, MIDIPortDeviceState, Node, Performance,
+};
 
+use super::file;
 use crate::broker::Broker;
-use crate::messages::{HoldPedalPressed, HoldPedalReleased, NoteOff, NoteOn};
+use crate::messages::{
+    HoldPedalPressed, HoldPedalReleased, ModulationChanged, NoteOff, NoteOn, PitchBendChanged,
+    SoftPedalPressed, SoftPedalReleased, SostenutoPedalPressed, SostenutoPedalReleased, Source,
+    SourceReset,
+};
 use crate::{consts, html};
 
+/// The "File" `<option>` comes first; the options of the devices follow
+const FILE_OPTION_INDEX: usize = 0;
+
+/// Continuous hold pedals report small depths when at rest; below this the dampers are down
+const HOLD_PEDAL_MIN_DEPTH: u8 = 16;
+
+/// For on/off pedals
+const PEDAL_THRESHOLD: u8 = 64;
+
+/// Pitch bend ranges, in semitones, offered for devices that do not report theirs
+const PITCH_BEND_RANGES: [u8; 8] = [1, 2, 3, 4, 5, 7, 12, 24];
+
+/// Source of the messages published by the file player
+pub const FILE_SOURCE: Source = Source(FILE_OPTION_INDEX as u8);
+
 pub(super) fn initialize(parent: &Node, midi_access: MIDIAccess) {
     let select = html::select(parent, &"midi-input-device".into());
-    select.set_required(true);
+    // several devices can be played at the same time
+    select.set_multiple(true);
+
+    let file_option = html::option(&select, None, &"File".into());
+
+    select.set_onchange(|_| Broker::publish(SelectChanged));
 
-    html::option(&select, None, &"(disconnected)".into());
+    // the device `<option>`s are added when handling `Initialize`
+    midi_access.set_onstatechange(Some(|event| {
+        Broker::publish(PortStateChanged(event.port()))
+    }));
 
-    for (key, midi_input) in midi_access.inputs().entries() {
-        let name = midi_input.name();
-        html::option(&select, Some(&key), &name);
+    let channel_select = html::select(parent, &"midi-input-channel".into());
+    channel_select.set_required(true);
+
+    html::option(&channel_select, None, &"omni".into());
+    for channel in 1..=consts::NUM_MIDI_CHANNELS {
+        html::option(
+            &channel_select,
+            None,
+            &format!("Ch{channel}").as_str().into(),
+        );
+    }
+
+    channel_select.set_selected_index(consts::INITIAL_MIDI_INPUT_CHANNEL_INDEX.into());
+    channel_select.set_onchange(|event| {
+        let select: HtmlSelectElement = event.target().upcast().upcast().downcast();
+        Broker::publish(ChannelChanged(select.selected_index()))
+    });
+
+    let bend_range_select = html::select(parent, &"midi-input-bend-range".into());
+    bend_range_select.set_required(true);
+    for semitones in PITCH_BEND_RANGES {
+        html::option(
+            &bend_range_select,
+            None,
+            &format!("bend ±{semitones}").as_str().into(),
+        );
     }
 
-    select.set_onchange(|event| {
-        Broker::publish(SelectChanged {
-            select: event.target().upcast().upcast().downcast(),
-        })
+    bend_range_select.set_selected_index(consts::INITIAL_PITCH_BEND_RANGE_INDEX.into());
+    bend_range_select.set_onchange(|event| {
+        let select: HtmlSelectElement = event.target().upcast().upcast().downcast();
+        Broker::publish(BendRangeChanged(select.selected_index()))
     });
 
-    Broker::publish(Initialize { midi_access });
+    Broker::publish(Initialize {
+        file_option,
+        midi_access,
+        select,
+    });
 }
 
 pub struct MidiInputDeviceSelect {
@@ -32,34 +97,170 @@ pub struct MidiInputDeviceSelect {
 }
 
 impl React<Initialize> for MidiInputDeviceSelect {
-    fn react(&mut self, Initialize { midi_access }: Initialize) {
-        self.state = Some(State {
+    fn react(
+        &mut self,
+        Initialize {
+            file_option,
             midi_access,
-            last_selected: None,
-        })
+            select,
+        }: Initialize,
+    ) {
+        let state = self.state.insert(State {
+            bend_range: PITCH_BEND_RANGES[usize::from(consts::INITIAL_PITCH_BEND_RANGE_INDEX)],
+            channel: channel_from_index(consts::INITIAL_MIDI_INPUT_CHANNEL_INDEX.into()),
+            file_option,
+            inputs: Vec::new(),
+            midi_access,
+            select,
+        });
+        state.refresh();
     }
 }
 
-impl React<SelectChanged> for MidiInputDeviceSelect {
-    fn react(&mut self, message: SelectChanged) {
+impl React<PortStateChanged> for MidiInputDeviceSelect {
+    fn react(&mut self, PortStateChanged(port): PortStateChanged) {
+        let Some(state) = &mut self.state else { return };
+
+        // this also fires when a port is opened or closed; only (un)plugging changes the list
+        let plugged = port.state() == MIDIPortDeviceState::Connected;
+        let id = port.id();
+        let listed = state
+            .inputs
+            .iter()
+            .any(|input| input.id == id && input.option.is_some());
+        if plugged != listed {
+            state.refresh();
+        }
+    }
+}
+
+impl React<ChannelChanged> for MidiInputDeviceSelect {
+    fn react(&mut self, ChannelChanged(index): ChannelChanged) {
+        let Some(state) = &mut self.state else { return };
+
+        let channel = channel_from_index(index);
+
+        // the note-offs and pedal releases of the dropped channels would be filtered out
+        let now = Performance.now();
+        for (index, input) in state.inputs.iter_mut().enumerate() {
+            let is_kept = |other| channel.is_none_or(|selected| selected == other);
+            input.release(source(index), is_kept, now);
+        }
+
+        state.channel = channel;
+    }
+}
+
+impl React<BendRangeChanged> for MidiInputDeviceSelect {
+    fn react(&mut self, BendRangeChanged(index): BendRangeChanged) {
         let Some(state) = &mut self.state else { return };
 
-        let input_id = message.select.value();
-        let inputs = state.midi_access.inputs();
+        if let Some(semitones) = usize::try_from(index)
+            .ok()
+            .and_then(|index| PITCH_BEND_RANGES.get(index))
+        {
+            state.bend_range = *semitones;
+        }
+    }
+}
 
-        let Some(new_input) = inputs.get(&input_id) else {
+impl React<MidiMessageReceived> for MidiInputDeviceSelect {
+    fn react(
+        &mut self,
+        MidiMessageReceived {
+            bytes,
+            input_id,
+            timestamp,
+        }: MidiMessageReceived,
+    ) {
+        let Some(state) = &mut self.state else { return };
+
+        let Some(index) = state.inputs.iter().position(|input| input.id == input_id) else {
             return;
         };
+        let source = source(index);
+
+        let selected_channel = state.channel;
+        let Input {
+            bend_sensitivities,
+            decoder,
+            held,
+            pedals,
+            ..
+        } = &mut state.inputs[index];
+        for message in decoder.decode(&bytes) {
+            let Some(channel) = message.channel() else {
+                continue;
+            };
 
-        if let Some(old_input_id) = &state.last_selected {
-            if let Some(old_input) = inputs.get(old_input_id) {
-                old_input.set_onmidimessage(None);
+            if selected_channel.is_some_and(|selected| selected != channel) {
+                continue;
+            }
+
+            match message {
+                MidiMessage::NoteOn { note, velocity, .. } => {
+                    held[usize::from(channel)].insert(note);
+                    Broker::publish(NoteOn {
+                        note,
+                        timestamp,
+                        channel,
+                        source,
+                        velocity,
+                    })
+                }
+                MidiMessage::NoteOff { note, .. } => {
+                    held[usize::from(channel)].remove(note);
+                    Broker::publish(NoteOff {
+                        note,
+                        timestamp,
+                        channel,
+                        source,
+                    })
+                }
+                MidiMessage::PitchBend { value, .. } => {
+                    let range = bend_sensitivities[usize::from(channel)]
+                        .semitones()
+                        .unwrap_or(f64::from(state.bend_range));
+                    let semitones = midi::pitch_bend_semitones(value, range);
+                    Broker::publish(PitchBendChanged {
+                        timestamp,
+                        channel,
+                        source,
+                        semitones,
+                    })
+                }
+                MidiMessage::ControlChange {
+                    controller, value, ..
+                } => {
+                    bend_sensitivities[usize::from(channel)].control_change(controller, value);
+                    let pedals = &mut pedals[usize::from(channel)];
+                    control_change(controller, value, timestamp, channel, source, pedals)
+                }
+                _ => {}
             }
         }
+    }
+}
+
+impl React<SelectChanged> for MidiInputDeviceSelect {
+    fn react(&mut self, _: SelectChanged) {
+        let Some(state) = &mut self.state else { return };
+
+        Broker::publish(file::SourceSelected(state.file_option.selected()));
+
+        for (index, input) in state.inputs.iter_mut().enumerate() {
+            let Some(option) = &input.option else {
+                continue;
+            };
 
-        new_input.set_onmidimessage(Some(onmidimessage));
+            let was_chosen = input.chosen;
+            input.chosen = option.selected();
+            input.listen();
 
-        state.last_selected = Some(input_id);
+            if was_chosen && !input.chosen {
+                input.reset(source(index));
+            }
+        }
     }
 }
 
@@ -71,91 +272,253 @@ impl MidiInputDeviceSelect {
 
 #[derive(Message)]
 pub struct Initialize {
+    file_option: HtmlOptionElement,
     midi_access: MIDIAccess,
+    select: HtmlSelectElement,
 }
 
+/// A MIDI port was plugged in, unplugged, opened or closed
 #[derive(Message)]
-pub struct SelectChanged {
-    select: HtmlSelectElement,
+pub struct PortStateChanged(MIDIPort);
+
+#[derive(Message)]
+pub struct SelectChanged;
+
+#[derive(Message)]
+pub struct ChannelChanged(i32);
+
+#[derive(Message)]
+pub struct BendRangeChanged(i32);
+
+/// Raw bytes from one of the selected devices; a MIDI message may span several of these
+#[derive(Message)]
+pub struct MidiMessageReceived {
+    bytes: Vec<u8>,
+    input_id: js::String,
+    timestamp: f64,
 }
 
 struct State {
+    /// in semitones; used until a device reports its own range
+    bend_range: u8,
+    /// `None` means omni mode: messages from all channels are accepted
+    channel: Option<u8>,
+    file_option: HtmlOptionElement,
+    /// every input seen so far, including the unplugged ones, so that each keeps its `Source`
+    inputs: Vec<Input>,
     midi_access: MIDIAccess,
-    last_selected: Option<js::String>,
+    select: HtmlSelectElement,
 }
 
-fn onmidimessage(event: MIDIMessageEvent) {
-    let data = event.data();
-    // only interested in NoteOn, NoteOff and ControlChange messages which are 3-byte long
-    if data.length() != 3 {
-        return;
-    }
+impl State {
+    /// Rebuilds the device `<option>`s from the inputs that are currently plugged in
+    fn refresh(&mut self) {
+        let mut were_plugged_in = Vec::with_capacity(self.inputs.len());
+        for input in &mut self.inputs {
+            were_plugged_in.push(input.option.is_some());
+            if let Some(option) = input.option.take() {
+                self.select.remove_child(&option);
+            }
+        }
 
-    let mut buf = [0; 3];
-    data.copy_to_slice(&mut buf);
+        for (id, port) in self.midi_access.inputs().entries() {
+            if port.state() != MIDIPortDeviceState::Connected {
+                continue;
+            }
 
-    let Some(message) = parse(buf) else { return };
+            let option = Document.create_element::<HtmlOptionElement>();
+            option.set_value(&id);
+            option.set_text_content(&port.name());
+            option.set_attribute(&"title".into(), &port.manufacturer());
 
-    let timestamp = event.timestamp();
-    match message {
-        MidiMessage::NoteOn(note) => Broker::publish(NoteOn(note, timestamp)),
-        MidiMessage::NoteOff(note) => Broker::publish(NoteOff(note, timestamp)),
-        MidiMessage::SustainPedal(on) => {
-            if on {
-                Broker::publish(HoldPedalPressed)
+            let input = if let Some(input) = self.inputs.iter_mut().find(|input| input.id == id) {
+                input
             } else {
-                Broker::publish(HoldPedalReleased)
+                self.inputs.push(Input {
+                    bend_sensitivities: [PitchBendSensitivity::new(); consts::NUM_MIDI_CHANNELS],
+                    chosen: false,
+                    decoder: MidiDecoder::new(),
+                    held: [const { Notes::empty() }; consts::NUM_MIDI_CHANNELS],
+                    id,
+                    pedals: [Pedals::default(); consts::NUM_MIDI_CHANNELS],
+                    option: None,
+                    port: port.clone(),
+                });
+                self.inputs.last_mut().unwrap()
+            };
+
+            // reconnect to the devices chosen before they were unplugged
+            option.set_selected(input.chosen);
+            self.select.add(&option);
+            input.option = Some(option);
+            input.port = port;
+            input.listen();
+        }
+
+        // unplugged devices never send the releases, and a message cut off by the unplugging must
+        // not run into the ones sent once plugged back in
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            if were_plugged_in.get(index) == Some(&true) && input.option.is_none() {
+                input.decoder = MidiDecoder::new();
+                input.reset(source(index));
             }
         }
     }
 }
 
-fn parse(buf: [u8; 3]) -> Option<MidiMessage> {
-    const CMD_CONTROL_CHANGE: u8 = 0b1011;
-    const CMD_NOTE_OFF: u8 = 0b1000;
-    const CMD_NOTE_ON: u8 = 0b1001;
-    const CONTROL_HOLD_PEDAL: u8 = 64;
-    const HOLD_PEDAL_THRESHOLD: u8 = 64;
-    const MASK_CHANNEL: u8 = 0b0000_1111;
-    const MASK_COMMAND: u8 = 0b1111_0000;
+struct Input {
+    /// reported by the device through RPN messages, per channel
+    bend_sensitivities: [PitchBendSensitivity; consts::NUM_MIDI_CHANNELS],
+    /// selected by the user; kept while the device is unplugged
+    chosen: bool,
+    /// keeps the running status and partial messages between packets
+    decoder: MidiDecoder,
+    /// notes held on each channel, released when the channel stops being listened to
+    held: [Notes; consts::NUM_MIDI_CHANNELS],
+    id: js::String,
+    /// `Some` while the device is plugged in
+    option: Option<HtmlOptionElement>,
+    /// pressed on each channel
+    pedals: [Pedals; consts::NUM_MIDI_CHANNELS],
+    port: MIDIInput,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Pedals {
+    hold: bool,
+    sostenuto: bool,
+    soft: bool,
+}
 
-    let status = buf[0];
-    let command = (status & MASK_COMMAND) >> 4;
-    let channel = status & MASK_CHANNEL;
+impl Pedals {
+    fn merge(&mut self, other: Self) {
+        self.hold |= other.hold;
+        self.sostenuto |= other.sostenuto;
+        self.soft |= other.soft;
+    }
+}
 
-    if channel != consts::MIDI_INPUT_CHANNEL {
-        return None;
+impl Input {
+    fn listen(&self) {
+        let callback: fn(MIDIMessageEvent) = onmidimessage;
+        self.port
+            .set_onmidimessage((self.chosen && self.option.is_some()).then_some(callback));
     }
 
-    let note = buf[1];
-    let velocity = buf[2];
-    if note >= 0x80 || velocity >= 0x80 {
-        return None;
+    /// Forgets the held notes and the pressed pedals, letting them go all at once
+    fn reset(&mut self, source: Source) {
+        self.held.iter_mut().for_each(Notes::clear);
+        self.pedals = [Pedals::default(); consts::NUM_MIDI_CHANNELS];
+        Broker::publish(SourceReset(source));
     }
 
-    let message = if command == CMD_NOTE_ON {
-        let note = Note::from_u8_lossy(note);
-        MidiMessage::NoteOn(note)
-    } else if command == CMD_NOTE_OFF {
-        let note = Note::from_u8_lossy(note);
-        MidiMessage::NoteOff(note)
-    } else if command == CMD_CONTROL_CHANGE {
-        let ctrl_number = note;
-        let ctrl_value = velocity;
-        if ctrl_number == CONTROL_HOLD_PEDAL {
-            MidiMessage::SustainPedal(ctrl_value >= HOLD_PEDAL_THRESHOLD)
-        } else {
-            return None;
+    /// Releases the notes and the pedals of the channels that are not kept. The sostenuto and
+    /// soft pedals are only released when no kept channel of the device presses them
+    fn release(&mut self, source: Source, is_kept: impl Fn(u8) -> bool, timestamp: f64) {
+        let mut released = Pedals::default();
+        let mut kept = Pedals::default();
+        for (channel, (held, pedals)) in (0..).zip(self.held.iter_mut().zip(&mut self.pedals)) {
+            if is_kept(channel) {
+                kept.merge(*pedals);
+                continue;
+            }
+
+            for note in held.iter() {
+                Broker::publish(NoteOff {
+                    note,
+                    timestamp,
+                    channel,
+                    source,
+                });
+            }
+            held.clear();
+
+            if pedals.hold {
+                Broker::publish(HoldPedalReleased {
+                    timestamp,
+                    channel,
+                    source,
+                });
+            }
+            released.merge(core::mem::take(pedals));
+        }
+
+        if released.sostenuto && !kept.sostenuto {
+            Broker::publish(SostenutoPedalReleased(source));
         }
-    } else {
-        return None;
-    };
+        if released.soft && !kept.soft {
+            Broker::publish(SoftPedalReleased(source));
+        }
+    }
+}
+
+/// Devices come after the file player, in the order they were first seen
+fn source(index: usize) -> Source {
+    Source((FILE_OPTION_INDEX + 1 + index) as u8)
+}
+
+fn onmidimessage(event: MIDIMessageEvent) {
+    let data = event.data();
+    let mut bytes = vec![0; data.length() as usize];
+    data.copy_to_slice(&mut bytes);
+
+    let input: MIDIInput = event.target().upcast().upcast().downcast();
+    Broker::publish(MidiMessageReceived {
+        bytes,
+        input_id: input.id(),
+        timestamp: event.timestamp(),
+    });
+}
 
-    Some(message)
+fn control_change(
+    controller: u8,
+    value: u8,
+    timestamp: f64,
+    channel: u8,
+    source: Source,
+    pedals: &mut Pedals,
+) {
+    match controller {
+        CONTROL_MODULATION_WHEEL => Broker::publish(ModulationChanged { timestamp, value }),
+        // the hold pedal supports half pedaling so keep its value
+        CONTROL_HOLD_PEDAL => {
+            pedals.hold = value >= HOLD_PEDAL_MIN_DEPTH;
+            if pedals.hold {
+                Broker::publish(HoldPedalPressed {
+                    timestamp,
+                    channel,
+                    source,
+                    depth: value,
+                })
+            } else {
+                Broker::publish(HoldPedalReleased {
+                    timestamp,
+                    channel,
+                    source,
+                })
+            }
+        }
+        CONTROL_SOSTENUTO_PEDAL => {
+            pedals.sostenuto = value >= PEDAL_THRESHOLD;
+            if pedals.sostenuto {
+                Broker::publish(SostenutoPedalPressed(source))
+            } else {
+                Broker::publish(SostenutoPedalReleased(source))
+            }
+        }
+        CONTROL_SOFT_PEDAL => {
+            pedals.soft = value >= PEDAL_THRESHOLD;
+            if pedals.soft {
+                Broker::publish(SoftPedalPressed(source))
+            } else {
+                Broker::publish(SoftPedalReleased(source))
+            }
+        }
+        _ => {}
+    }
 }
 
-enum MidiMessage {
-    NoteOn(Note),
-    NoteOff(Note),
-    SustainPedal(bool),
+/// The first option is omni mode
+fn channel_from_index(index: i32) -> Option<u8> {
+    u8::try_from(index).ok()?.checked_sub(1)
 }
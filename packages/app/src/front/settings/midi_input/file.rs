@@ -0,0 +1,468 @@
+use js::{Downcast as _, Upcast as _};
+use music::Notes;
+use music::smf::{Event, Position, Smf, TimedEvent};
+use nosync::{Owned, Shared};
+use spur::{Message, Publish as _, React};
+use web::{
+    HtmlButtonElement, HtmlInputElement, HtmlSelectElement, HtmlSpanElement, Node, Performance,
+};
+
+use super::device;
+use crate::broker::Broker;
+use crate::messages::{HoldPedalPressed, HoldPedalReleased, NoteOff, NoteOn};
+use crate::{consts, html};
+
+/// Size of the static buffer the file is loaded into. The heap cannot hold it: its allocations
+/// are limited to 62 KiB, smaller than many multi-track files
+const MAX_FILE_LEN: usize = 1024 * 1024;
+/// Channel 10; its notes are drum sounds, not pitches
+const PERCUSSION_CHANNEL: u8 = 9;
+const TEMPO_SCALES: [(f64, &str); 6] = [
+    (0.5, "50%"),
+    (0.75, "75%"),
+    (1., "100%"),
+    (1.25, "125%"),
+    (1.5, "150%"),
+    (2., "200%"),
+];
+const INITIAL_TEMPO_SCALE_INDEX: u32 = 2; // 100%
+
+const PLAY: &str = "play";
+const PAUSE: &str = "pause";
+
+pub(super) fn initialize(parent: &Node) {
+    let input = html::input(parent, &"file".into());
+    input.set_accept(&".mid,.midi".into());
+    input.set_disabled(true);
+    input.set_onchange(|event| {
+        let input: HtmlInputElement = event.target().upcast().upcast().downcast();
+        let Some(file) = input.files().item(0) else {
+            return;
+        };
+
+        if file.size() as usize > MAX_FILE_LEN {
+            Broker::publish(FileLoaded(None));
+            return;
+        }
+
+        file.array_buffer().then2(
+            |buffer| Broker::publish(FileLoaded(Some(buffer))),
+            || Broker::publish(FileLoaded(None)),
+        );
+    });
+
+    let status = html::span(parent, "");
+
+    let play = html::button(parent, &PLAY.into());
+    play.set_disabled(true);
+    play.set_onclick(|_| Broker::publish(PlayClicked));
+
+    let seek = html::input(parent, &"range".into());
+    seek.set_disabled(true);
+    seek.set_max(0.);
+    seek.set_onchange(|event| {
+        let seek: HtmlInputElement = event.target().upcast().upcast().downcast();
+        Broker::publish(SeekChanged(seek.value_as_number()))
+    });
+
+    let tempo = html::select(parent, &"midi-file-tempo".into());
+    tempo.set_required(true);
+    tempo.set_disabled(true);
+    for (_, text) in TEMPO_SCALES {
+        html::option(&tempo, None, &text.into());
+    }
+    tempo.set_selected_index(INITIAL_TEMPO_SCALE_INDEX);
+    tempo.set_onchange(|event| {
+        let tempo: HtmlSelectElement = event.target().upcast().upcast().downcast();
+        Broker::publish(TempoChanged(tempo.selected_index()))
+    });
+
+    Broker::publish(Initialize(State {
+        input,
+        ms: 0.,
+        pedals: [false; consts::NUM_MIDI_CHANNELS],
+        play,
+        playing: None,
+        seek,
+        song: None,
+        sounding: [const { Notes::empty() }; consts::NUM_MIDI_CHANNELS],
+        status,
+        tempo,
+        tempo_scale: TEMPO_SCALES[INITIAL_TEMPO_SCALE_INDEX as usize].0,
+    }));
+}
+
+/// Plays back a Standard MIDI File as if it was a MIDI input device
+pub struct MidiFilePlayer {
+    state: Option<State>,
+}
+
+impl MidiFilePlayer {
+    pub const fn new() -> Self {
+        Self { state: None }
+    }
+}
+
+#[derive(Message)]
+pub struct Initialize(State);
+
+/// Whether the "File" source was selected in the device `<select>`
+#[derive(Message)]
+pub struct SourceSelected(pub bool);
+
+#[derive(Message)]
+pub struct FileLoaded(Option<js::ArrayBuffer>);
+
+#[derive(Message)]
+pub struct PlayClicked;
+
+#[derive(Message)]
+pub struct SeekChanged(f64);
+
+#[derive(Message)]
+pub struct TempoChanged(i32);
+
+#[derive(Message)]
+pub struct Tick;
+
+impl React<Initialize> for MidiFilePlayer {
+    fn react(&mut self, Initialize(state): Initialize) {
+        self.state = Some(state);
+    }
+}
+
+impl React<SourceSelected> for MidiFilePlayer {
+    fn react(&mut self, SourceSelected(selected): SourceSelected) {
+        let Some(state) = &mut self.state else { return };
+
+        if !selected {
+            state.pause(Performance.now());
+        }
+
+        state.input.set_disabled(!selected);
+        state.tempo.set_disabled(!selected);
+        let loaded = state.song.is_some();
+        state.play.set_disabled(!selected || !loaded);
+        state.seek.set_disabled(!selected || !loaded);
+    }
+}
+
+impl React<FileLoaded> for MidiFilePlayer {
+    fn react(&mut self, FileLoaded(buffer): FileLoaded) {
+        let Some(state) = &mut self.state else { return };
+
+        state.pause(Performance.now());
+        state.song = None;
+        state.ms = 0.;
+
+        // the previous song gave the buffer back when it was dropped
+        let smf = buffer.and_then(|buffer| {
+            let array = js::Uint8Array::from(&buffer);
+            let len = array.length() as usize;
+            if len > MAX_FILE_LEN {
+                return None;
+            }
+
+            let buf = SPARE_BUFFER.borrow_mut().take().or_else(|| BUFFER.take())?;
+            array.copy_to_slice(&mut buf[..len]);
+            Smf::parse(FileBytes {
+                buf: Some(buf),
+                len,
+            })
+            .ok()
+        });
+
+        if let Some(smf) = smf {
+            let duration = smf.events().last().map_or(0., |event| event.ms);
+            let mut events = smf.events();
+            let next = events.next();
+            let position = events.position();
+
+            state.song = Some(Song {
+                duration,
+                next,
+                position,
+                smf,
+            });
+            state.status.set_text_content(&"".into());
+            state.seek.set_max(duration);
+        } else {
+            state.status.set_text_content(&"unsupported file".into());
+            state.seek.set_max(0.);
+        }
+
+        state.seek.set_value_as_number(0.);
+        let loaded = state.song.is_some();
+        state.play.set_disabled(!loaded);
+        state.seek.set_disabled(!loaded);
+    }
+}
+
+impl React<PlayClicked> for MidiFilePlayer {
+    fn react(&mut self, _: PlayClicked) {
+        let Some(state) = &mut self.state else { return };
+
+        let now = Performance.now();
+        if state.playing.is_some() {
+            state.pause(now);
+        } else {
+            state.play(now);
+        }
+    }
+}
+
+impl React<SeekChanged> for MidiFilePlayer {
+    fn react(&mut self, SeekChanged(ms): SeekChanged) {
+        let Some(state) = &mut self.state else { return };
+
+        let now = Performance.now();
+        let was_playing = state.playing.is_some();
+        state.pause(now);
+        state.seek_to(ms);
+        if was_playing {
+            state.play(now);
+        }
+    }
+}
+
+impl React<TempoChanged> for MidiFilePlayer {
+    fn react(&mut self, TempoChanged(index): TempoChanged) {
+        let Some(state) = &mut self.state else { return };
+        let Some(&(scale, _)) = usize::try_from(index)
+            .ok()
+            .and_then(|index| TEMPO_SCALES.get(index))
+        else {
+            return;
+        };
+
+        let now = Performance.now();
+        if let Some(playing) = &mut state.playing {
+            // keep the current position in the file
+            let ms = (now - playing.origin) * state.tempo_scale;
+            playing.origin = now - ms / scale;
+            web::clear_timeout(playing.timeout);
+            state.tempo_scale = scale;
+            state.tick(now);
+        } else {
+            state.tempo_scale = scale;
+        }
+    }
+}
+
+impl React<Tick> for MidiFilePlayer {
+    fn react(&mut self, _: Tick) {
+        let Some(state) = &mut self.state else { return };
+
+        if state.playing.is_some() {
+            state.tick(Performance.now());
+        }
+    }
+}
+
+struct State {
+    input: HtmlInputElement,
+    /// position in the file, in milliseconds, while paused
+    ms: f64,
+    /// whether the hold pedal is down, per channel
+    pedals: [bool; consts::NUM_MIDI_CHANNELS],
+    play: HtmlButtonElement,
+    playing: Option<Playing>,
+    seek: HtmlInputElement,
+    song: Option<Song>,
+    /// notes that have been turned on but not off, per channel
+    sounding: [Notes; consts::NUM_MIDI_CHANNELS],
+    status: HtmlSpanElement,
+    tempo: HtmlSelectElement,
+    tempo_scale: f64,
+}
+
+struct Playing {
+    /// `Performance` timestamp that corresponds to the start of the file
+    origin: f64,
+    timeout: u32,
+}
+
+struct Song {
+    duration: f64,
+    /// the event that will be played next
+    next: Option<TimedEvent>,
+    /// position right after `next`
+    position: Position,
+    smf: Smf<FileBytes>,
+}
+
+static BUFFER: Owned<[u8; MAX_FILE_LEN]> = Owned::new([0; MAX_FILE_LEN]);
+/// `BUFFER` while no file uses it
+static SPARE_BUFFER: Shared<Option<&'static mut [u8; MAX_FILE_LEN]>> = Shared::new(None);
+
+/// The bytes of a file loaded into `BUFFER`
+struct FileBytes {
+    /// `None` once given back
+    buf: Option<&'static mut [u8; MAX_FILE_LEN]>,
+    len: usize,
+}
+
+impl AsRef<[u8]> for FileBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.buf.as_ref().map_or(&[], |buf| &buf[..self.len])
+    }
+}
+
+impl Drop for FileBytes {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            *SPARE_BUFFER.borrow_mut() = Some(buf);
+        }
+    }
+}
+
+impl State {
+    fn play(&mut self, now: f64) {
+        let Some(song) = &self.song else { return };
+
+        // restart from the beginning when the end was reached
+        if song.next.is_none() {
+            self.seek_to(0.);
+        }
+
+        self.playing = Some(Playing {
+            origin: now - self.ms / self.tempo_scale,
+            timeout: 0,
+        });
+        self.play.set_text_content(&PAUSE.into());
+        self.tick(now);
+    }
+
+    fn pause(&mut self, now: f64) {
+        let Some(playing) = self.playing.take() else {
+            return;
+        };
+
+        web::clear_timeout(playing.timeout);
+        self.ms = (now - playing.origin) * self.tempo_scale;
+        self.release(now);
+        self.play.set_text_content(&PLAY.into());
+    }
+
+    /// Moves to the first event at or after `ms`; must be called while paused
+    fn seek_to(&mut self, ms: f64) {
+        let Some(song) = &mut self.song else { return };
+
+        let mut events = song.smf.events();
+        song.next = events.by_ref().find(|event| event.ms >= ms);
+        song.position = events.position();
+        self.ms = ms;
+        self.seek.set_value_as_number(ms);
+    }
+
+    /// Publishes the events that are due and schedules the next tick
+    fn tick(&mut self, now: f64) {
+        let (Some(song), Some(playing)) = (&mut self.song, &mut self.playing) else {
+            return;
+        };
+
+        let ms = (now - playing.origin) * self.tempo_scale;
+        let mut events = song.smf.events_at(song.position);
+        while let Some(next) = song.next.filter(|next| next.ms <= ms) {
+            let timestamp = playing.origin + next.ms / self.tempo_scale;
+            publish(next.event, timestamp, &mut self.sounding, &mut self.pedals);
+            song.next = events.next();
+        }
+        song.position = events.position();
+        self.seek.set_value_as_number(ms.min(song.duration));
+
+        if let Some(next) = song.next {
+            let delay = (next.ms - ms) / self.tempo_scale;
+            playing.timeout = web::set_timeout(|| Broker::publish(Tick), delay);
+        } else {
+            // reached the end of the file
+            let duration = song.duration;
+            self.pause(now);
+            self.ms = duration;
+        }
+    }
+
+    /// Turns off the sounding notes and the pedal so they do not hang
+    fn release(&mut self, now: f64) {
+        for (channel, notes) in (0..).zip(&mut self.sounding) {
+            for note in notes.iter() {
+                Broker::publish(NoteOff {
+                    note,
+                    timestamp: now,
+                    channel,
+                    source: device::FILE_SOURCE,
+                });
+            }
+            notes.clear();
+        }
+
+        for (channel, pedal) in (0..).zip(&mut self.pedals) {
+            if *pedal {
+                Broker::publish(HoldPedalReleased {
+                    timestamp: now,
+                    channel,
+                    source: device::FILE_SOURCE,
+                });
+                *pedal = false;
+            }
+        }
+    }
+}
+
+fn publish(
+    event: Event,
+    timestamp: f64,
+    sounding: &mut [Notes; consts::NUM_MIDI_CHANNELS],
+    pedals: &mut [bool; consts::NUM_MIDI_CHANNELS],
+) {
+    match event {
+        Event::NoteOn {
+            channel,
+            note,
+            velocity,
+        } if channel != PERCUSSION_CHANNEL => {
+            sounding[usize::from(channel)].insert(note);
+            Broker::publish(NoteOn {
+                note,
+                timestamp,
+                channel,
+                source: device::FILE_SOURCE,
+                velocity,
+            });
+        }
+
+        Event::NoteOff { channel, note } if channel != PERCUSSION_CHANNEL => {
+            let sounding = &mut sounding[usize::from(channel)];
+            if sounding.contains(note) {
+                sounding.remove(note);
+                Broker::publish(NoteOff {
+                    note,
+                    timestamp,
+                    channel,
+                    source: device::FILE_SOURCE,
+                });
+            }
+        }
+
+        Event::SustainPedal { channel, on } => {
+            pedals[usize::from(channel)] = on;
+            if on {
+                // Standard MIDI Files are read as on/off hold pedal events
+                Broker::publish(HoldPedalPressed {
+                    timestamp,
+                    channel,
+                    source: device::FILE_SOURCE,
+                    depth: 0x7f,
+                });
+            } else {
+                Broker::publish(HoldPedalReleased {
+                    timestamp,
+                    channel,
+                    source: device::FILE_SOURCE,
+                });
+            }
+        }
+
+        _ => {}
+    }
+}
@@ -0,0 +1,137 @@
+use music::OvertoneSeries;
+use music::overtones::{self, Envelope};
+use spur::{Message, Publish as _, React};
+use web::{HtmlInputElement, HtmlSelectElement};
+
+use crate::broker::Broker;
+use crate::html;
+use crate::html::Form;
+use crate::messages::OvertonesChanged;
+
+/// Instrument profiles; the powers of the custom envelopes start at the second partial
+const PROFILES: [(Envelope, &str); 5] = [
+    (Envelope::Sawtooth, "strings"),
+    (Envelope::Square, "clarinet"),
+    (
+        Envelope::from_powers(&[0.6, 0.35, 0.25, 0.15, 0.1, 0.05, 0.05, 0.03]),
+        "piano",
+    ),
+    (Envelope::from_powers(&[0.3, 0.1, 0.05, 0.03]), "flute"),
+    (Envelope::from_powers(&[]), "sine"),
+];
+const MAX_COUNT: u8 = 32;
+/// Shape given to the profile edited by the user, from the second partial on
+const CUSTOM_POWERS: &str = "0.5 0.25 0.125";
+/// Room for `CUSTOM_LEN` powers written with a few decimals
+const MAX_POWERS_LEN: usize = 8 * overtones::CUSTOM_LEN;
+
+pub(super) fn initialize(form: &Form) {
+    let fieldset = form.fieldset(&"Overtones".into());
+
+    let select = html::select(&fieldset, &"overtones".into());
+    select.set_required(true);
+    for (_, text) in PROFILES {
+        html::option(&select, None, &text.into());
+    }
+    html::option(&select, None, &"custom".into());
+    select.set_selected_index(0);
+    select.set_onchange(|_| Broker::publish(Changed));
+
+    let powers = html::input(&fieldset, &"text".into());
+    powers.set_value(&CUSTOM_POWERS.into());
+    powers.set_onchange(|_| Broker::publish(Changed));
+
+    let count = html::input(&fieldset, &"number".into());
+    count.set_min(0.);
+    count.set_max(MAX_COUNT.into());
+    count.set_value_as_number(OvertoneSeries::default().count.into());
+    count.set_onchange(|_| Broker::publish(Changed));
+    html::span(&fieldset, "partials");
+
+    Broker::publish(Initialize(State {
+        count,
+        powers,
+        select,
+    }));
+}
+
+pub struct OvertonesSelect {
+    state: Option<State>,
+}
+
+impl OvertonesSelect {
+    pub const fn new() -> Self {
+        Self { state: None }
+    }
+}
+
+#[derive(Message)]
+pub struct Initialize(State);
+
+/// The profile, the powers of the custom one or the number of partials changed
+#[derive(Message)]
+pub struct Changed;
+
+impl React<Initialize> for OvertonesSelect {
+    fn react(&mut self, Initialize(state): Initialize) {
+        self.state = Some(state);
+    }
+}
+
+impl React<Changed> for OvertonesSelect {
+    fn react(&mut self, _: Changed) {
+        let Some(State {
+            count,
+            powers,
+            select,
+        }) = &self.state
+        else {
+            return;
+        };
+
+        let Ok(index) = usize::try_from(select.selected_index()) else {
+            return;
+        };
+        let envelope = match PROFILES.get(index) {
+            Some((envelope, _)) => *envelope,
+            None => {
+                let mut buf = [0; MAX_POWERS_LEN];
+                parse_powers(powers.value().encode_into(&mut buf))
+            }
+        };
+
+        // empty or out of range inputs fall back to the default count
+        let value = count.value_as_number();
+        let count = if (0.0..=f64::from(MAX_COUNT)).contains(&value) {
+            value as u8
+        } else {
+            OvertoneSeries::default().count
+        };
+
+        Broker::publish(OvertonesChanged(OvertoneSeries::new(envelope, count)));
+    }
+}
+
+struct State {
+    count: HtmlInputElement,
+    /// of the custom profile, separated by spaces or commas
+    powers: HtmlInputElement,
+    select: HtmlSelectElement,
+}
+
+/// Powers that are not numbers, or are negative, silence their partial
+fn parse_powers(text: &str) -> Envelope {
+    let mut powers = [0.; overtones::CUSTOM_LEN];
+    let words = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty());
+    for (power, word) in powers.iter_mut().zip(words) {
+        *power = word
+            .parse()
+            .ok()
+            .filter(|power: &f64| power.is_finite() && *power >= 0.)
+            .unwrap_or(0.);
+    }
+
+    Envelope::Custom(powers)
+}
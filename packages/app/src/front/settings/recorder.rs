@@ -0,0 +1,348 @@
+use alloc::collections::btree_set::BTreeSet;
+use alloc::vec::Vec;
+use music::Notes;
+use music::smf::{self, Event, TimedEvent};
+use spur::{Message, Publish as _, React};
+use web::{Blob, Document, HtmlAnchorElement, HtmlButtonElement, Performance, Url};
+
+use crate::broker::Broker;
+use crate::html::Form;
+use crate::messages::{HoldPedalPressed, HoldPedalReleased, NoteOff, NoteOn, Source, SourceReset};
+use crate::{consts, html};
+
+/// Events per chunk; keeps each allocation below the allocator's size limit
+const CHUNK_LEN: usize = 64;
+/// Upper bound on the size of a take; recording stops when it's reached
+const MAX_CHUNKS: usize = 128;
+/// Size of the `Uint8Array`s the `.mid` file is split into
+const PART_LEN: usize = 1024;
+
+const FILENAME: &str = "take.mid";
+const RECORD: &str = "record";
+const STOP: &str = "stop";
+
+pub(super) fn initialize(form: &Form) {
+    let fieldset = form.fieldset(&"Recorder".into());
+
+    let record = html::button(&fieldset, &RECORD.into());
+    record.set_onclick(|_| Broker::publish(RecordClicked));
+
+    let download = html::button(&fieldset, &"download".into());
+    download.set_disabled(true);
+    download.set_onclick(|_| Broker::publish(DownloadClicked));
+
+    Broker::publish(Initialize(State {
+        download,
+        held: [const { Notes::empty() }; consts::NUM_MIDI_CHANNELS],
+        hold_pedals: BTreeSet::new(),
+        last_url: None,
+        record,
+        start: None,
+        take: Vec::new(),
+    }));
+}
+
+pub struct Recorder {
+    state: Option<State>,
+}
+
+impl Recorder {
+    pub const fn new() -> Self {
+        Self { state: None }
+    }
+}
+
+#[derive(Message)]
+pub struct Initialize(State);
+
+#[derive(Message)]
+pub struct RecordClicked;
+
+#[derive(Message)]
+pub struct DownloadClicked;
+
+impl React<Initialize> for Recorder {
+    fn react(&mut self, Initialize(state): Initialize) {
+        self.state = Some(state);
+    }
+}
+
+impl React<RecordClicked> for Recorder {
+    fn react(&mut self, _: RecordClicked) {
+        let Some(state) = &mut self.state else { return };
+
+        if state.start.is_some() {
+            state.stop(Performance.now());
+        } else {
+            state.take.clear();
+            state.held.iter_mut().for_each(Notes::clear);
+            state.start = Some(Performance.now());
+            // so that the take does not play back dry
+            for channel in state.pedaled_channels() {
+                state.push(0., Event::SustainPedal { channel, on: true });
+            }
+            state.record.set_text_content(&STOP.into());
+            state.download.set_disabled(true);
+        }
+    }
+}
+
+impl React<DownloadClicked> for Recorder {
+    fn react(&mut self, _: DownloadClicked) {
+        let Some(state) = &mut self.state else { return };
+
+        if state.take.is_empty() {
+            return;
+        }
+
+        let mut parts = Parts {
+            array: js::Array::new(),
+            buf: Vec::with_capacity(PART_LEN),
+        };
+        let events =
+            state
+                .take
+                .iter()
+                .flatten()
+                .map(|&(ms, event)| TimedEvent { tick: 0, ms, event });
+        smf::write(events, &mut parts);
+        parts.flush();
+
+        let url = Url.create_object_url(&Blob::new(&parts.array));
+        let anchor = Document.create_element::<HtmlAnchorElement>();
+        anchor.set_href(&url);
+        anchor.set_download(&FILENAME.into());
+        anchor.click();
+
+        // the previous download has surely started by now
+        if let Some(old_url) = state.last_url.replace(url) {
+            Url.revoke_object_url(&old_url);
+        }
+    }
+}
+
+impl React<NoteOn> for Recorder {
+    fn react(
+        &mut self,
+        NoteOn {
+            note,
+            timestamp,
+            channel,
+            velocity,
+            ..
+        }: NoteOn,
+    ) {
+        let Some(state) = &mut self.state else { return };
+
+        if state.record(
+            timestamp,
+            Event::NoteOn {
+                channel,
+                note,
+                velocity,
+            },
+        ) {
+            state.held[usize::from(channel)].insert(note);
+        }
+    }
+}
+
+impl React<NoteOff> for Recorder {
+    fn react(
+        &mut self,
+        NoteOff {
+            note,
+            timestamp,
+            channel,
+            ..
+        }: NoteOff,
+    ) {
+        let Some(state) = &mut self.state else { return };
+
+        // ignore notes that were pressed before the recording started
+        if !state.held[usize::from(channel)].contains(note) {
+            return;
+        }
+
+        if state.record(timestamp, Event::NoteOff { channel, note }) {
+            state.held[usize::from(channel)].remove(note);
+        }
+    }
+}
+
+impl React<HoldPedalPressed> for Recorder {
+    fn react(
+        &mut self,
+        HoldPedalPressed {
+            timestamp,
+            channel,
+            source,
+            ..
+        }: HoldPedalPressed,
+    ) {
+        let Some(state) = &mut self.state else { return };
+
+        // continuous pedals report every change of depth, and the channel may already be
+        // pedaled from another source
+        let is_pedaled = state.is_pedaled(channel);
+        if !state.hold_pedals.insert((source, channel)) || is_pedaled {
+            return;
+        }
+
+        state.record(timestamp, Event::SustainPedal { channel, on: true });
+    }
+}
+
+impl React<HoldPedalReleased> for Recorder {
+    fn react(
+        &mut self,
+        HoldPedalReleased {
+            timestamp,
+            channel,
+            source,
+        }: HoldPedalReleased,
+    ) {
+        let Some(state) = &mut self.state else { return };
+
+        // the channel stays pedaled while another source holds its pedal down
+        if !state.hold_pedals.remove(&(source, channel)) || state.is_pedaled(channel) {
+            return;
+        }
+
+        state.record(timestamp, Event::SustainPedal { channel, on: false });
+    }
+}
+
+impl React<SourceReset> for Recorder {
+    fn react(&mut self, SourceReset(source): SourceReset) {
+        let Some(state) = &mut self.state else { return };
+
+        let channels = state
+            .hold_pedals
+            .iter()
+            .filter(|(other, _)| *other == source)
+            .map(|(_, channel)| *channel)
+            .collect::<Vec<_>>();
+        state.hold_pedals.retain(|(other, _)| *other != source);
+
+        let timestamp = Performance.now();
+        for channel in channels {
+            if !state.is_pedaled(channel) {
+                state.record(timestamp, Event::SustainPedal { channel, on: false });
+            }
+        }
+    }
+}
+
+struct State {
+    download: HtmlButtonElement,
+    /// notes pressed during the take that have not been released yet, per channel
+    held: [Notes; consts::NUM_MIDI_CHANNELS],
+    /// the pressed hold pedals, per source and channel
+    hold_pedals: BTreeSet<(Source, /* channel */ u8)>,
+    last_url: Option<js::String>,
+    record: HtmlButtonElement,
+    /// `Some` while recording
+    start: Option<f64>,
+    /// event timestamps are relative to `start`
+    take: Vec<Vec<(f64, Event)>>,
+}
+
+impl State {
+    fn is_pedaled(&self, channel: u8) -> bool {
+        self.hold_pedals.iter().any(|(_, other)| *other == channel)
+    }
+
+    fn pedaled_channels(&self) -> BTreeSet<u8> {
+        self.hold_pedals
+            .iter()
+            .map(|(_, channel)| *channel)
+            .collect()
+    }
+
+    /// Returns `false` if the event was not recorded
+    fn record(&mut self, timestamp: f64, event: Event) -> bool {
+        let Some(start) = self.start else {
+            return false;
+        };
+
+        // leave room to release all the held notes and pedals
+        let num_releases =
+            self.held.iter().map(Notes::len).sum::<usize>() + self.pedaled_channels().len();
+        if self.take.len() + num_releases.div_ceil(CHUNK_LEN) + 1 >= MAX_CHUNKS {
+            self.stop(timestamp);
+            return false;
+        }
+
+        self.push(timestamp - start, event);
+
+        true
+    }
+
+    fn push(&mut self, ms: f64, event: Event) {
+        match self.take.last_mut() {
+            Some(chunk) if chunk.len() < CHUNK_LEN => chunk.push((ms, event)),
+
+            _ => {
+                let mut chunk = Vec::with_capacity(CHUNK_LEN);
+                chunk.push((ms, event));
+                self.take.push(chunk);
+            }
+        }
+    }
+
+    fn stop(&mut self, timestamp: f64) {
+        let Some(start) = self.start.take() else {
+            return;
+        };
+
+        // release the held notes and pedals so they do not hang when the take is played back
+        for channel in 0..consts::NUM_MIDI_CHANNELS {
+            for note in self.held[channel].clone() {
+                self.push(
+                    timestamp - start,
+                    Event::NoteOff {
+                        channel: channel as u8,
+                        note,
+                    },
+                );
+            }
+            self.held[channel].clear();
+        }
+        for channel in self.pedaled_channels() {
+            self.push(
+                timestamp - start,
+                Event::SustainPedal { channel, on: false },
+            );
+        }
+
+        self.record.set_text_content(&RECORD.into());
+        self.download.set_disabled(self.take.is_empty());
+    }
+}
+
+/// Splits the `.mid` file into `Uint8Array`s so it does not need to be in a single allocation
+struct Parts {
+    array: js::Array,
+    buf: Vec<u8>,
+}
+
+impl Parts {
+    fn flush(&mut self) {
+        if !self.buf.is_empty() {
+            self.array.push(&js::Uint8Array::from(&self.buf[..]));
+            self.buf.clear();
+        }
+    }
+}
+
+impl Extend<u8> for Parts {
+    fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
+        for byte in iter {
+            self.buf.push(byte);
+            if self.buf.len() == PART_LEN {
+                self.flush();
+            }
+        }
+    }
+}
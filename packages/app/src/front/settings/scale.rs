@@ -1,14 +1,21 @@
 use alloc::vec::Vec;
 use js::{Downcast as _, Upcast as _};
-use music::{NoteName, Scale, ScaleType};
+use music::custom_scale::{self, CustomScale};
+use music::{Degree, KeyFinder, NoteName, Scale, ScaleType};
 use spur::{Message, Publish as _, React};
-use web::{HtmlOptionElement, HtmlSelectElement};
+use web::{
+    HtmlButtonElement, HtmlDivElement, HtmlInputElement, HtmlOptionElement, HtmlSelectElement,
+    Performance,
+};
 
 use crate::broker::Broker;
+use crate::class::Class;
 use crate::html::Form;
-use crate::messages::{NewScaleTonicSelected, NewScaleTypeSelected};
+use crate::messages::{ActiveNotesChanged, NewScaleTonicSelected, NewScaleTypeSelected};
 use crate::{consts, html};
 
+const CUSTOM_SCALE_NAME: &str = "Custom";
+
 pub(super) fn initialize(form: &Form) {
     let fieldset = form.fieldset(&"Scale".into());
 
@@ -30,11 +37,56 @@ pub(super) fn initialize(form: &Form) {
     for ty in ScaleType::ALL {
         html::option(&type_select, None, &ty.as_str().into());
     }
+    // comes after `ScaleType::ALL`
+    let custom_option = html::option(&type_select, None, &CUSTOM_SCALE_NAME.into());
 
     type_select.set_selected_index(consts::INITIAL_SCALE_TYPE_INDEX.into());
     type_select.set_onchange(|event| onchange_type(event.target().upcast().upcast().downcast()));
 
-    Broker::publish(Initialize(State { options }));
+    let auto = html::input(&fieldset, &"checkbox".into());
+    auto.set_onchange(|event| {
+        let auto: HtmlInputElement = event.target().upcast().upcast().downcast();
+        Broker::publish(AutoToggled(auto.checked()))
+    });
+    html::span(&fieldset, "auto");
+
+    let editor = html::div(&fieldset, Class::ScaleEditor);
+    editor.set_hidden(true);
+
+    let name = html::input(&editor, &"text".into());
+    name.set_value(&CUSTOM_SCALE_NAME.into());
+    name.set_onchange(|_| Broker::publish(CustomScaleRenamed));
+
+    let degree_buttons = Degree::ALL.map(|degree| {
+        let button = html::button(&editor, &degree.as_str().into());
+        // identifies the degree even if it's respelled
+        button.set_value(&degree.as_str().into());
+        button.set_class_name(&degree.as_str().into());
+        button.set_onclick(|event| {
+            let button: HtmlButtonElement = event.target().upcast().upcast().downcast();
+            Broker::publish(DegreeClicked(button.value()))
+        });
+        button
+    });
+    // the tonic cannot be removed
+    degree_buttons[0].set_disabled(true);
+
+    let state = State {
+        custom: CustomScale::new(CUSTOM_SCALE_NAME, ScaleType::Major.degrees()),
+        custom_option,
+        degree_buttons,
+        editor,
+        finder: KeyFinder::new(),
+        is_auto: false,
+        is_custom_selected: false,
+        name,
+        options,
+        tonic_select,
+        type_select,
+    };
+    state.redraw_editor();
+
+    Broker::publish(Initialize(state));
 }
 
 pub struct ScaleTonicSelect {
@@ -50,6 +102,20 @@ impl ScaleTonicSelect {
 #[derive(Message)]
 pub struct Initialize(State);
 
+/// Whether the scale follows the key detected in what is being played
+#[derive(Message)]
+pub struct AutoToggled(bool);
+
+#[derive(Message)]
+pub struct CustomScaleSelected;
+
+#[derive(Message)]
+pub struct CustomScaleRenamed;
+
+/// Carries the `value` of the button, i.e. the usual spelling of the degree
+#[derive(Message)]
+pub struct DegreeClicked(js::String);
+
 impl React<Initialize> for ScaleTonicSelect {
     fn react(&mut self, Initialize(state): Initialize) {
         self.state = Some(state);
@@ -57,21 +123,167 @@ impl React<Initialize> for ScaleTonicSelect {
 }
 
 impl React<NewScaleTypeSelected> for ScaleTonicSelect {
-    fn react(&mut self, NewScaleTypeSelected(index): NewScaleTypeSelected) {
-        let Some(State { options }) = &mut self.state else {
+    fn react(&mut self, NewScaleTypeSelected(ty): NewScaleTypeSelected) {
+        let Some(state) = &mut self.state else {
             return;
         };
 
-        let ty = ScaleType::ALL[index];
-
-        for (option, note) in options.iter_mut().zip(NoteName::CIRCLE_OF_FIFTHS) {
+        for (option, note) in state.options.iter_mut().zip(NoteName::CIRCLE_OF_FIFTHS) {
             option.set_text_content(&js::String::from(note.as_str(Scale { tonic: note, ty })));
         }
+
+        state.is_custom_selected = matches!(ty, ScaleType::Custom(_));
+        state.editor.set_hidden(!state.is_custom_selected);
+    }
+}
+
+impl React<AutoToggled> for ScaleTonicSelect {
+    fn react(&mut self, AutoToggled(is_auto): AutoToggled) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        state.is_auto = is_auto;
+        state.tonic_select.set_disabled(is_auto);
+        state.type_select.set_disabled(is_auto);
+
+        if let Some(key) = state.finder.current().filter(|_| is_auto) {
+            state.select(key);
+        }
+    }
+}
+
+impl React<ActiveNotesChanged> for ScaleTonicSelect {
+    fn react(
+        &mut self,
+        ActiveNotesChanged {
+            held, sustained, ..
+        }: ActiveNotesChanged,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        // the estimate is kept up to date even while the scale is picked by hand
+        let key = state
+            .finder
+            .update(&held.union(&sustained), Performance.now());
+        if let Some(key) = key.filter(|_| state.is_auto) {
+            state.select(key);
+        }
+    }
+}
+
+impl React<CustomScaleSelected> for ScaleTonicSelect {
+    fn react(&mut self, _: CustomScaleSelected) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        Broker::publish(NewScaleTypeSelected(ScaleType::Custom(state.custom)));
+    }
+}
+
+impl React<CustomScaleRenamed> for ScaleTonicSelect {
+    fn react(&mut self, _: CustomScaleRenamed) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        let mut buf = [0; custom_scale::MAX_NAME_LEN];
+        let name = state.name.value().encode_into(&mut buf);
+        state.custom.set_name(if name.is_empty() {
+            CUSTOM_SCALE_NAME
+        } else {
+            name
+        });
+        state
+            .custom_option
+            .set_text_content(&state.custom.name().into());
+
+        state.publish_if_selected();
+    }
+}
+
+impl React<DegreeClicked> for ScaleTonicSelect {
+    fn react(&mut self, DegreeClicked(value): DegreeClicked) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        let Some(degree) = Degree::ALL
+            .into_iter()
+            .find(|degree| js::String::from(degree.as_str()) == value)
+        else {
+            return;
+        };
+
+        // cycles through: out of the scale, in the scale, in the scale with the other spelling
+        let custom = &mut state.custom;
+        if !custom.degrees().contains(degree) {
+            custom.toggle(degree);
+        } else if degree.as_alt_str().is_some() && !custom.is_respelled(degree) {
+            custom.set_respelled(degree, true);
+        } else {
+            custom.set_respelled(degree, false);
+            custom.toggle(degree);
+        }
+
+        state.redraw_editor();
+        state.publish_if_selected();
     }
 }
 
 struct State {
+    custom: CustomScale,
+    custom_option: HtmlOptionElement,
+    /// indexed like `Degree::ALL`
+    degree_buttons: [HtmlButtonElement; 12],
+    editor: HtmlDivElement,
+    finder: KeyFinder,
+    is_auto: bool,
+    is_custom_selected: bool,
+    name: HtmlInputElement,
     options: Vec<HtmlOptionElement>,
+    tonic_select: HtmlSelectElement,
+    type_select: HtmlSelectElement,
+}
+
+impl State {
+    fn redraw_editor(&self) {
+        let class = js::String::from(Class::InScale.as_str());
+        for (button, degree) in self.degree_buttons.iter().zip(Degree::ALL) {
+            button.set_text_content(&self.custom.degree_str(degree).into());
+            if self.custom.degrees().contains(degree) {
+                button.add_class(&class);
+            } else {
+                button.rm_class(&class);
+            }
+        }
+    }
+
+    /// Selects a detected key as if the user had picked it
+    fn select(&self, key: Scale) {
+        let tonic = NoteName::CIRCLE_OF_FIFTHS
+            .iter()
+            .position(|tonic| *tonic == key.tonic);
+        let ty = ScaleType::ALL.iter().position(|ty| *ty == key.ty);
+        let (Some(tonic), Some(ty)) = (tonic, ty) else {
+            return;
+        };
+
+        self.tonic_select.set_selected_index(tonic as u32);
+        self.type_select.set_selected_index(ty as u32);
+
+        Broker::publish(NewScaleTonicSelected(tonic));
+        Broker::publish(NewScaleTypeSelected(key.ty));
+    }
+
+    fn publish_if_selected(&self) {
+        if self.is_custom_selected {
+            Broker::publish(NewScaleTypeSelected(ScaleType::Custom(self.custom)));
+        }
+    }
 }
 
 fn onchange_tonic(select: HtmlSelectElement) {
@@ -85,9 +297,13 @@ fn onchange_tonic(select: HtmlSelectElement) {
 
 fn onchange_type(select: HtmlSelectElement) {
     let selected = select.selected_index();
-    let Ok(index) = selected.try_into() else {
+    let Ok(index) = usize::try_from(selected) else {
         return;
     };
 
-    Broker::publish(NewScaleTypeSelected(index));
+    if let Some(ty) = ScaleType::ALL.get(index) {
+        Broker::publish(NewScaleTypeSelected(*ty));
+    } else {
+        Broker::publish(CustomScaleSelected);
+    }
 }
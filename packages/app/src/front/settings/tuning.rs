@@ -0,0 +1,88 @@
+use music::tuning::{self, Temperament};
+use spur::{Message, Publish as _, React};
+use web::{HtmlInputElement, HtmlSelectElement};
+
+use crate::broker::Broker;
+use crate::html;
+use crate::html::Form;
+use crate::messages::TuningChanged;
+
+/// From low baroque pitch to the sharpest orchestras
+const MIN_A4: f64 = 392.;
+const MAX_A4: f64 = 466.;
+
+pub(super) fn initialize(form: &Form) {
+    let fieldset = form.fieldset(&"Tuning".into());
+
+    let select = html::select(&fieldset, &"temperament".into());
+    select.set_required(true);
+    for temperament in Temperament::ALL {
+        html::option(&select, None, &temperament.as_str().into());
+    }
+    select.set_selected_index(0);
+    select.set_onchange(|_| Broker::publish(Changed));
+
+    let a4 = html::input(&fieldset, &"number".into());
+    a4.set_min(MIN_A4);
+    a4.set_max(MAX_A4);
+    a4.set_step(0.1);
+    a4.set_value_as_number(tuning::STANDARD_A4);
+    a4.set_onchange(|_| Broker::publish(Changed));
+    html::span(&fieldset, "Hz for A4");
+
+    Broker::publish(Initialize(State { a4, select }));
+}
+
+pub struct TuningSelect {
+    state: Option<State>,
+}
+
+impl TuningSelect {
+    pub const fn new() -> Self {
+        Self { state: None }
+    }
+}
+
+#[derive(Message)]
+pub struct Initialize(State);
+
+/// Either the temperament or the reference pitch changed
+#[derive(Message)]
+pub struct Changed;
+
+impl React<Initialize> for TuningSelect {
+    fn react(&mut self, Initialize(state): Initialize) {
+        self.state = Some(state);
+    }
+}
+
+impl React<Changed> for TuningSelect {
+    fn react(&mut self, _: Changed) {
+        let Some(State { a4, select }) = &self.state else {
+            return;
+        };
+
+        let Some(temperament) = usize::try_from(select.selected_index())
+            .ok()
+            .and_then(|index| Temperament::ALL.get(index))
+        else {
+            return;
+        };
+
+        // empty or out of range inputs fall back to the standard pitch
+        let mut reference = a4.value_as_number();
+        if !(MIN_A4..=MAX_A4).contains(&reference) {
+            reference = tuning::STANDARD_A4;
+        }
+
+        Broker::publish(TuningChanged {
+            a4: reference,
+            temperament: *temperament,
+        });
+    }
+}
+
+struct State {
+    a4: HtmlInputElement,
+    select: HtmlSelectElement,
+}
@@ -0,0 +1,38 @@
+use js::{Downcast as _, Upcast as _};
+use music::Voicing;
+use spur::Publish as _;
+use web::HtmlSelectElement;
+
+use crate::broker::Broker;
+use crate::html;
+use crate::html::Form;
+use crate::messages::VoicingSelected;
+
+pub(super) fn initialize(form: &Form) {
+    let fieldset = form.fieldset(&"Voicing".into());
+
+    let select = html::select(&fieldset, &"voicing".into());
+    select.set_required(true);
+
+    html::option(&select, None, &"off".into());
+    // come after "off"
+    for voicing in Voicing::ALL {
+        html::option(&select, None, &voicing.as_str().into());
+    }
+
+    select.set_selected_index(0);
+    select.set_onchange(|event| onchange(event.target().upcast().upcast().downcast()));
+}
+
+fn onchange(select: HtmlSelectElement) {
+    let selected = select.selected_index();
+    let Ok(index) = usize::try_from(selected) else {
+        return;
+    };
+
+    let voicing = index
+        .checked_sub(1)
+        .and_then(|index| Voicing::ALL.get(index))
+        .copied();
+    Broker::publish(VoicingSelected(voicing));
+}
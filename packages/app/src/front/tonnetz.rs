@@ -1,10 +1,13 @@
 use alloc::collections::btree_map::BTreeMap;
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt::Write as _;
 
 use js::Float;
+use music::neo_riemannian::{Path, Transformation, Triad};
 use music::{Degree, Degrees, MajorScale, NoteName, NoteNames, ScaleType};
 use spur::{Message, Publish as _, React};
-use web::{Node, SVGEllipseElement, SVGSVGElement, SVGTextElement};
+use web::{Node, SVGEllipseElement, SVGPathElement, SVGSVGElement, SVGTextElement};
 
 use crate::broker::Broker;
 use crate::class::Class;
@@ -39,6 +42,7 @@ impl React<Initialize> for Tonnetz {
         canvas.set_scale_type(scale_type);
         self.state = Some(State {
             canvas,
+            last: None,
             live: NoteNames::empty(),
             scale: MajorScale::new(tonic),
         })
@@ -61,16 +65,18 @@ impl React<NewScaleTonicSelected> for Tonnetz {
             }
 
             state.canvas.highlight(degrees);
+
+            // the triangles moved along with the tonic
+            state.canvas.clear_trail();
+            state.last = None;
         }
     }
 }
 
 impl React<NewScaleTypeSelected> for Tonnetz {
-    fn react(&mut self, NewScaleTypeSelected(index): NewScaleTypeSelected) {
+    fn react(&mut self, NewScaleTypeSelected(new_scale_type): NewScaleTypeSelected) {
         let Some(state) = &mut self.state else { return };
 
-        let new_scale_type = ScaleType::ALL[index];
-
         state.canvas.reset_in_key();
         state.canvas.set_scale_type(new_scale_type);
     }
@@ -96,7 +102,22 @@ impl React<ActiveNotesChanged> for Tonnetz {
             degrees.insert(degree);
         }
 
-        state.canvas.highlight(degrees);
+        let centers = state.canvas.highlight(degrees);
+
+        // the trail goes from the triangle of a triad to the next one; other chords and
+        // releasing the keys leave it as is
+        if let Some(triad) = Triad::from_names(&notes) {
+            let center = centroid(&centers);
+            if let Some((last, last_center)) = state.last {
+                if last != triad {
+                    let path = last.path(triad);
+                    state
+                        .canvas
+                        .draw_path(path, (last, last_center), center, &state.scale);
+                }
+            }
+            state.last = Some((triad, center));
+        }
 
         state.live = notes;
     }
@@ -104,12 +125,33 @@ impl React<ActiveNotesChanged> for Tonnetz {
 
 struct State {
     canvas: Canvas,
+    /// Last triad played and the center of its triangle
+    last: Option<(Triad, (f64, f64))>,
     live: NoteNames,
     scale: MajorScale,
 }
 
+/// Longest trail, in transformations
+const MAX_STEPS: usize = 8;
+
 struct Canvas {
+    parent: SVGSVGElement,
     items: BTreeMap<Degree, Vec<Item>>,
+    /// canvas center and radius of the circles, in pixels
+    center_x: f64,
+    center_y: f64,
+    r_px: f64,
+    trail: SVGPathElement,
+    /// oldest first
+    steps: Vec<Step>,
+}
+
+/// A transformation from the triangle of a triad to the next one
+struct Step {
+    // coordinates relative to canvas center
+    from: (f64, f64),
+    to: (f64, f64),
+    label: SVGTextElement,
 }
 
 #[derive(Clone)]
@@ -148,6 +190,9 @@ impl Canvas {
         let center_x = width_px / 2.;
         let center_y = HEIGHT_PX / 2.;
 
+        // below the circles and their labels
+        let trail = svg::path(parent, Class::TonnetzTrail, "");
+
         for curr_row in -3isize..=3 {
             let num_cols = 7 - curr_row.abs();
             let mut degree = if curr_row % 2 == 0 {
@@ -186,7 +231,15 @@ impl Canvas {
             }
         }
 
-        Self { items }
+        Self {
+            parent: parent.clone(),
+            items,
+            center_x,
+            center_y,
+            r_px,
+            trail,
+            steps: Vec::with_capacity(MAX_STEPS + 1),
+        }
     }
 
     fn reset_highlights(&self) {
@@ -199,14 +252,15 @@ impl Canvas {
         }
     }
 
-    fn highlight(&self, mut degrees: Degrees) {
+    /// Returns the centers of the highlighted circles
+    fn highlight(&self, mut degrees: Degrees) -> Vec<(f64, f64)> {
         self.reset_highlights();
 
         let class = Class::Highlight.as_str().into();
 
         let mut centers = Vec::with_capacity(degrees.len());
         while !degrees.is_empty() {
-            let (degree, item) = self.closest(degrees.clone(), &centers);
+            let (degree, item) = self.closest(degrees, &centers);
 
             item.circle.add_class(&class);
             item.label.add_class(&class);
@@ -214,11 +268,88 @@ impl Canvas {
 
             degrees.remove(degree);
         }
+
+        centers
+    }
+
+    /// Center of the triangle of `triad` that is the closest to `near`
+    fn locate(&self, triad: Triad, scale: &MajorScale, near: (f64, f64)) -> (f64, f64) {
+        let mut degrees = triad
+            .names()
+            .into_iter()
+            .map(|name| scale.name2degree(name))
+            .collect::<Degrees>();
+
+        let mut centers = Vec::with_capacity(degrees.len() + 1);
+        centers.push(near);
+        while !degrees.is_empty() {
+            let (degree, item) = self.closest(degrees, &centers);
+            centers.push((item.cx, item.cy));
+            degrees.remove(degree);
+        }
+
+        centroid(&centers[1..])
+    }
+
+    /// Extends the trail from `from` to the triangle centered on `to`, through the triangles
+    /// of the triads in between
+    fn draw_path(
+        &mut self,
+        path: Path,
+        (mut triad, mut point): (Triad, (f64, f64)),
+        to: (f64, f64),
+        scale: &MajorScale,
+    ) {
+        for (index, transformation) in path.steps().enumerate() {
+            triad = triad.apply(transformation);
+            let next = if index + 1 == path.len() {
+                to
+            } else {
+                self.locate(triad, scale, point)
+            };
+
+            self.push_step(point, next, transformation);
+            point = next;
+        }
+
+        while self.steps.len() > MAX_STEPS {
+            let step = self.steps.remove(0);
+            self.parent.remove_child(&step.label);
+        }
+
+        let mut d = String::new();
+        for Step { from, to, .. } in &self.steps {
+            let (x1, y1) = self.to_px(*from);
+            let (x2, y2) = self.to_px(*to);
+            let _ = write!(d, "M {x1} {y1} L {x2} {y2} ");
+        }
+        self.trail.set_d(&d.as_str().into());
+    }
+
+    fn push_step(&mut self, from: (f64, f64), to: (f64, f64), transformation: Transformation) {
+        // halfway, which falls between the two circles of the shared edge
+        let (x, y) = self.to_px(((from.0 + to.0) / 2., (from.1 + to.1) / 2.));
+        let label = svg::text(&self.parent, &Float::from(x), &Float::from(y));
+        label.set_class_name(&Class::TonnetzStep.as_str().into());
+        label.set_text_content(&transformation.as_str().into());
+
+        self.steps.push(Step { from, to, label });
+    }
+
+    fn clear_trail(&mut self) {
+        for step in self.steps.drain(..) {
+            self.parent.remove_child(&step.label);
+        }
+        self.trail.set_d(&"".into());
+    }
+
+    fn to_px(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (self.center_x + x * self.r_px, self.center_y + y * self.r_px)
     }
 
     fn closest(&self, degrees: Degrees, centers: &[(f64, f64)]) -> (Degree, Item) {
         let mut closest: Option<(f64, f64, _, _)> = None;
-        for degree in degrees.clone() {
+        for degree in degrees {
             for item in self.items[&degree].iter() {
                 let distances = if centers.is_empty() {
                     item.cx * item.cx + item.cy * item.cy
@@ -257,7 +388,7 @@ impl Canvas {
     fn set_scale_type(&self, scale_type: ScaleType) {
         let class = Class::InScale.as_str().into();
         for degree in scale_type.degrees() {
-            for item in &self.items[degree] {
+            for item in &self.items[&degree] {
                 item.circle.add_class(&class);
             }
         }
@@ -272,3 +403,12 @@ impl Canvas {
         }
     }
 }
+
+fn centroid(centers: &[(f64, f64)]) -> (f64, f64) {
+    let len = centers.len().max(1) as f64;
+    let (x, y) = centers
+        .iter()
+        .fold((0., 0.), |(x, y), (cx, cy)| (x + cx, y + cy));
+
+    (x / len, y / len)
+}
@@ -7,7 +7,9 @@ use tlsf::Tlsf;
 
 use nosync::{Owned, Shared};
 
-const SIZE: usize = 16 * 1024 / mem::size_of::<u32>();
+// large enough to hold a few minutes of `Recorder` takes; loaded MIDI files have their own buffer
+// NOTE TLSF can manage at most 256 KiB
+const SIZE: usize = 256 * 1024 / mem::size_of::<u32>();
 
 pub fn initialize() {
     static MEMORY: Owned<[MaybeUninit<u32>; SIZE]> = Owned::new([MaybeUninit::uninit(); SIZE]);
@@ -21,8 +23,8 @@ pub fn initialize() {
 static HEAP: Heap = Heap::new();
 
 struct Heap {
-    // FL=6 => MAX_ALLOC_SIZE = 1,984B
-    inner: Shared<Tlsf<'static, 6>>,
+    // FL=11 => MAX_ALLOC_SIZE = 63,488B
+    inner: Shared<Tlsf<'static, 11>>,
 }
 
 impl Heap {
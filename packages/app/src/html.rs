@@ -1,6 +1,7 @@
 use web::{
-    Document, HtmlDivElement, HtmlFieldsetElement, HtmlFormElement, HtmlLegendElement,
-    HtmlOptionElement, HtmlSelectElement, HtmlSpanElement, HtmlSubElement, HtmlSupElement, Node,
+    Document, HtmlButtonElement, HtmlDivElement, HtmlFieldsetElement, HtmlFormElement,
+    HtmlInputElement, HtmlLegendElement, HtmlOptionElement, HtmlSelectElement, HtmlSpanElement,
+    HtmlSubElement, HtmlSupElement, Node,
 };
 
 use crate::class::Class;
@@ -19,6 +20,15 @@ pub fn sup(parent: &Node, text: &str) -> HtmlSupElement {
     sup
 }
 
+pub fn button(parent: &Node, text: &js::String) -> HtmlButtonElement {
+    let button = Document.create_element::<HtmlButtonElement>();
+    // do not submit the parent form
+    button.set_type(&"button".into());
+    button.set_text_content(text);
+    parent.append_child(&button);
+    button
+}
+
 pub fn div(parent: &Node, class: Class) -> HtmlDivElement {
     let div = Document.create_element::<HtmlDivElement>();
     div.set_class_name(&class.as_str().into());
@@ -40,6 +50,13 @@ pub fn form(parent: &Node, class: Class) -> Form {
     Form { inner: form }
 }
 
+pub fn input(parent: &Node, ty: &js::String) -> HtmlInputElement {
+    let input = Document.create_element::<HtmlInputElement>();
+    input.set_type(ty);
+    parent.append_child(&input);
+    input
+}
+
 pub fn option(
     select: &HtmlSelectElement,
     value: Option<&js::String>,
@@ -1,11 +1,22 @@
 use alloc::collections::btree_map::BTreeMap;
-use music::{Note, NoteName, Notes};
+use music::harmony_window::Length;
+use music::tuning::Temperament;
+use music::{Note, NoteName, Notes, OvertoneSeries, ScaleType, Voicing};
 use spur::Message;
 
 #[derive(Clone, Message)]
 pub struct ActiveNotesChanged {
     pub held: Notes,
+    /// notes that are not held but still sound because of the hold or sostenuto pedals
     pub sustained: Notes,
+    /// subset of `sustained` that only the sostenuto pedal keeps sounding
+    pub sostenuto: Notes,
+    /// the hold pedal is only partially pressed
+    pub half_pedal: bool,
+    /// the una corda pedal is pressed on any source
+    pub soft_pedal: bool,
+    /// velocity of the last note-on of each note, indexed by MIDI note number
+    pub velocities: [u8; 128],
 }
 
 #[derive(Clone, Message)]
@@ -17,16 +28,90 @@ pub struct ActiveHarmonyChanged {
 pub struct NewScaleTonicSelected(pub usize);
 
 #[derive(Clone, Message)]
-pub struct NewScaleTypeSelected(pub usize);
+pub struct NewScaleTypeSelected(pub ScaleType);
 
+/// `None` turns the voicing suggestions off
 #[derive(Clone, Message)]
-pub struct NoteOn(pub Note, pub f64);
+pub struct VoicingSelected(pub Option<Voicing>);
 
+/// `None` names the chord from the notes that sound at the same time only
 #[derive(Clone, Message)]
-pub struct NoteOff(pub Note, pub f64);
+pub struct HarmonyWindowChanged(pub Option<Length>);
 
 #[derive(Clone, Message)]
-pub struct HoldPedalPressed;
+pub struct OvertonesChanged(pub OvertoneSeries);
 
+/// `a4` is in Hz
 #[derive(Clone, Message)]
-pub struct HoldPedalReleased;
+pub struct TuningChanged {
+    pub a4: f64,
+    pub temperament: Temperament,
+}
+
+#[derive(Clone, Message)]
+pub struct NoteOn {
+    pub note: Note,
+    pub timestamp: f64,
+    pub channel: u8,
+    pub source: Source,
+    pub velocity: u8,
+}
+
+#[derive(Clone, Message)]
+pub struct NoteOff {
+    pub note: Note,
+    pub timestamp: f64,
+    pub channel: u8,
+    pub source: Source,
+}
+
+#[derive(Clone, Message)]
+pub struct PitchBendChanged {
+    pub timestamp: f64,
+    pub channel: u8,
+    pub source: Source,
+    /// already scaled by the pitch bend range of the channel
+    pub semitones: f64,
+}
+
+#[derive(Clone, Message)]
+pub struct ModulationChanged {
+    pub timestamp: f64,
+    pub value: u8,
+}
+
+#[derive(Clone, Message)]
+pub struct HoldPedalPressed {
+    pub timestamp: f64,
+    pub channel: u8,
+    pub source: Source,
+    pub depth: u8,
+}
+
+#[derive(Clone, Message)]
+pub struct HoldPedalReleased {
+    pub timestamp: f64,
+    pub channel: u8,
+    pub source: Source,
+}
+
+#[derive(Clone, Message)]
+pub struct SostenutoPedalPressed(pub Source);
+
+#[derive(Clone, Message)]
+pub struct SostenutoPedalReleased(pub Source);
+
+#[derive(Clone, Message)]
+pub struct SoftPedalPressed(pub Source);
+
+#[derive(Clone, Message)]
+pub struct SoftPedalReleased(pub Source);
+
+/// The source stopped being listened to: its held notes and pressed pedals are let go without
+/// waiting for the matching release messages
+#[derive(Clone, Message)]
+pub struct SourceReset(pub Source);
+
+/// Where a note or pedal message came from, e.g. one of several MIDI input devices
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Source(pub u8);
@@ -1,7 +1,21 @@
-use crate::{IsFunction, IsValue, Value};
+use core::num::NonZeroU32;
+
+use crate::{Array, IsFunction, IsValue, Object, Value};
 
 inheritance!(Function: Value);
 
+impl Function {
+    /// Calls the function as a constructor, i.e. `new function(...args)`
+    pub fn construct(&self, args: &Array) -> Object {
+        unsafe extern "C" {
+            #[link_name = "$Function$construct"]
+            fn ff(function: NonZeroU32, args: NonZeroU32) -> Object;
+        }
+
+        unsafe { ff(self.index(), args.index()) }
+    }
+}
+
 impl<T> From<T> for Function
 where
     T: IsFunction,
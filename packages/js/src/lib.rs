@@ -27,7 +27,7 @@ pub use null::Null;
 pub use object::Object;
 pub use promise::Promise;
 pub use string::String;
-pub use typed_array::Uint8Array;
+pub use typed_array::{ArrayBuffer, Uint8Array};
 pub use value::Value;
 
 pub trait Downcast<Subtype: IsValue> {
@@ -1,3 +1,5 @@
+use core::num::NonZeroU32;
+
 use super::Object;
 
 inheritance!(String: Object);
@@ -13,3 +15,29 @@ impl From<&'_ str> for String {
         unsafe { ff(value.as_ptr() as u32, value.len() as u32) }
     }
 }
+
+impl String {
+    /// Writes the string into `buf` as UTF-8, truncating it at a character boundary if it does not
+    /// fit, and returns the part that was written
+    pub fn encode_into<'a>(&self, buf: &'a mut [u8]) -> &'a str {
+        unsafe extern "C" {
+            #[link_name = "$String$encode_into"]
+            fn ff(string: NonZeroU32, dst_ptr: u32, dst_len: u32) -> u32;
+        }
+
+        let len = unsafe { ff(self.index(), buf.as_mut_ptr() as u32, buf.len() as u32) };
+        core::str::from_utf8(&buf[..len as usize]).unwrap_or_default()
+    }
+}
+
+/// Strict (`===`) equality
+impl PartialEq for String {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe extern "C" {
+            #[link_name = "$String$eq"]
+            fn ff(left: NonZeroU32, right: NonZeroU32) -> bool;
+        }
+
+        unsafe { ff(self.index(), other.index()) }
+    }
+}
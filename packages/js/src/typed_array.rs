@@ -2,8 +2,32 @@ use core::num::NonZeroU32;
 
 use crate::Object;
 
+inheritance!(ArrayBuffer: Object);
+
 inheritance!(Uint8Array: Object);
 
+impl From<&'_ ArrayBuffer> for Uint8Array {
+    fn from(buffer: &'_ ArrayBuffer) -> Self {
+        unsafe extern "C" {
+            #[link_name = "$Uint8Array$from_buffer"]
+            fn ff(buffer: NonZeroU32) -> Uint8Array;
+        }
+
+        unsafe { ff(buffer.index()) }
+    }
+}
+
+impl From<&'_ [u8]> for Uint8Array {
+    fn from(value: &'_ [u8]) -> Self {
+        unsafe extern "C" {
+            #[link_name = "$Uint8Array$from_slice"]
+            fn ff(src_ptr: u32, src_len: u32) -> Uint8Array;
+        }
+
+        unsafe { ff(value.as_ptr() as u32, value.len() as u32) }
+    }
+}
+
 impl Uint8Array {
     pub fn length(&self) -> u32 {
         unsafe { self.get(&"length".into()).unwrap_unchecked().to_u32() }
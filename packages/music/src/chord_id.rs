@@ -2,7 +2,8 @@ use core::fmt::{self, Write as _};
 #[cfg(test)]
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{Chord, Note, NoteName, Notes, note_names::NoteNames, scale::Scale};
+use crate::spelled_pitch::{ChordSpelling, SpelledPitch};
+use crate::{Chord, MajorScale, Note, NoteName, Notes, note_names::NoteNames, scale::Scale};
 
 impl Chord {
     pub fn identify_with_tonic(&self, tonic: NoteName) -> Option<ChordId> {
@@ -441,6 +442,70 @@ impl Chord {
         None
     }
 
+    /// Every plausible name of the chord, the most plausible first. With a `scale`, the chords
+    /// that fit in it rank higher
+    pub fn identify_ranked(&self, scale: Option<Scale>) -> Candidates {
+        let mut names = NoteNames::empty();
+        for note in self.notes() {
+            names.insert(note.name());
+        }
+
+        let lowest = self.lowest().name();
+        let mut candidates = Candidates::empty();
+        for tonic in names.clone() {
+            let Some(id) = self.identify_with_tonic(tonic) else {
+                continue;
+            };
+
+            let intervals = id.kind.intervals();
+            let half_steps = |name: NoteName| (12 + name as u8 - tonic as u8) % 12;
+            let mut score = 0;
+
+            // the bass note
+            score += match half_steps(lowest) {
+                0 => 3,
+                // the sixth or the seventh
+                9..=11 if intervals.contains(&half_steps(lowest)) => 0,
+                bass if intervals.contains(&bass) => 1,
+                // an extension
+                _ => -1,
+            };
+
+            // omitted tones
+            let omitted = intervals
+                .iter()
+                .filter(|interval| !names.contains(tonic.step(**interval)))
+                .count();
+            score -= omitted as i32;
+            if id.kind.alteration() == Alteration::NoThree {
+                score -= 1;
+            }
+
+            score -= id.extensions.count() as i32;
+            if matches!(id.kind, ChordKind::Lydian | ChordKind::Phrygian) {
+                score -= 1;
+            }
+
+            // key context
+            if let Some(scale) = scale {
+                let degree = MajorScale::new(scale.tonic).name2degree(tonic);
+                if tonic == scale.tonic {
+                    score += 3;
+                }
+                if degree.belongs_to(scale.ty) {
+                    score += 1;
+                }
+                if id.kind.has_third() && scale.ty.contains(degree, id.kind.is_minor()) {
+                    score += 2;
+                }
+            }
+
+            candidates.insert(Candidate { id, score, tonic });
+        }
+
+        candidates
+    }
+
     #[cfg(test)]
     fn identify(&self) -> BTreeMap<NoteName, ChordId> {
         let names: BTreeSet<_> = self.notes().map(|note| note.name()).collect();
@@ -465,6 +530,62 @@ fn rm(notes: &mut Notes, name: NoteName) {
     notes.remove(note);
 }
 
+/// One of the names of a chord
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Candidate {
+    pub id: ChordId,
+    /// Only meaningful relative to the other candidates of the same chord; higher is better
+    pub score: i32,
+    pub tonic: NoteName,
+}
+
+/// The names of a chord, sorted from the most to the least plausible
+pub struct Candidates {
+    /// at most one candidate per tonic
+    candidates: [Option<Candidate>; 12],
+    len: usize,
+}
+
+impl Candidates {
+    const fn empty() -> Self {
+        Self {
+            candidates: [None; 12],
+            len: 0,
+        }
+    }
+
+    /// Keeps the candidates sorted; ties keep their insertion order
+    fn insert(&mut self, candidate: Candidate) {
+        let mut index = self.len;
+        while index > 0
+            && self.candidates[index - 1].is_some_and(|other| other.score < candidate.score)
+        {
+            self.candidates[index] = self.candidates[index - 1];
+            index -= 1;
+        }
+
+        self.candidates[index] = Some(candidate);
+        self.len += 1;
+    }
+
+    pub fn best(&self) -> Option<Candidate> {
+        self.candidates[0]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Candidate> + '_ {
+        self.candidates[..self.len].iter().flatten().copied()
+    }
+}
+
 #[derive(Clone, Copy)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub struct ChordId {
@@ -597,20 +718,27 @@ impl ChordId {
         S(*self)
     }
 
-    pub fn sub(&self, scale: Scale) -> impl fmt::Display {
-        struct S(ChordId, Scale);
+    /// The bass note, spelled as a chord tone when it is one, e.g. "/G♯" in C+/G♯
+    pub fn sub(&self, tonic: NoteName, scale: Scale) -> impl fmt::Display {
+        struct S(Option<SpelledPitch>);
 
         impl fmt::Display for S {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                if let Some(note) = self.0.bass {
-                    write!(f, "/{}", note.as_str(self.1))?
+                if let Some(bass) = self.0 {
+                    write!(f, "/{bass}")?
                 }
 
                 Ok(())
             }
         }
 
-        S(*self, scale)
+        S(self.spell(tonic, scale).bass())
+    }
+
+    /// The members of the chord whose root is `tonic`, spelled from the root. The root itself
+    /// is spelled like in `scale`
+    pub fn spell(&self, tonic: NoteName, scale: Scale) -> ChordSpelling {
+        ChordSpelling::new(tonic, self, scale)
     }
 
     pub fn normal(&self) -> impl fmt::Display {
@@ -680,6 +808,63 @@ impl ChordKind {
         Self::DiminishedSeventh,
     ];
 
+    /// Half steps from the root to each of the tones that make the chord what it is
+    pub(crate) fn intervals(&self) -> &'static [u8] {
+        use Alteration as A;
+        use ChordKind::*;
+
+        match self {
+            Power => &[0, 7],
+            Major => &[0, 4, 7],
+            Minor => &[0, 3, 7],
+            Sus2 => &[0, 2, 7],
+            Sus4 => &[0, 5, 7],
+            Augmented => &[0, 4, 8],
+            Diminished => &[0, 3, 6],
+            Lydian => &[0, 6, 7],
+            Phrygian => &[0, 1, 7],
+            MinorSixth => &[0, 3, 7, 9],
+            MajorSixth => &[0, 4, 7, 9],
+            MinorMajorSeventh(A::SharpFive) => &[0, 3, 8, 11],
+            MinorMajorSeventh(_) => &[0, 3, 7, 11],
+            MajorSeventh(A::FlatFive) => &[0, 4, 6, 11],
+            MajorSeventh(A::NoThree) => &[0, 7, 11],
+            MajorSeventh(A::SharpFive) => &[0, 4, 8, 11],
+            MajorSeventh(A::Sus2) => &[0, 2, 7, 11],
+            MajorSeventh(A::Sus4) => &[0, 5, 7, 11],
+            MajorSeventh(A::None) => &[0, 4, 7, 11],
+            MinorSeventh(A::FlatFive) => &[0, 3, 6, 10],
+            MinorSeventh(A::SharpFive) => &[0, 3, 8, 10],
+            MinorSeventh(_) => &[0, 3, 7, 10],
+            Seventh(A::FlatFive) => &[0, 4, 6, 10],
+            Seventh(A::NoThree) => &[0, 7, 10],
+            Seventh(A::SharpFive) => &[0, 4, 8, 10],
+            Seventh(A::Sus2) => &[0, 2, 7, 10],
+            Seventh(A::Sus4) => &[0, 5, 7, 10],
+            Seventh(A::None) => &[0, 4, 7, 10],
+            DiminishedSeventh => &[0, 3, 6, 9],
+        }
+    }
+
+    fn alteration(&self) -> Alteration {
+        use ChordKind::*;
+
+        match self {
+            MinorMajorSeventh(alteration)
+            | MajorSeventh(alteration)
+            | MinorSeventh(alteration)
+            | Seventh(alteration) => *alteration,
+            _ => Alteration::None,
+        }
+    }
+
+    /// Whether the chord is major or minor, as opposed to e.g. suspended
+    fn has_third(&self) -> bool {
+        self.intervals()
+            .iter()
+            .any(|interval| matches!(interval, 3 | 4))
+    }
+
     pub fn is_minor(&self) -> bool {
         use ChordKind::*;
 
@@ -760,12 +945,34 @@ impl Extensions {
         }
     }
 
+    /// Half steps from the root to each extension, in the octave above the chord tones
+    pub(crate) fn half_steps(&self) -> impl Iterator<Item = u8> {
+        let ninth = self.ninth.map(|accidental| accidental.alter(14));
+        let eleventh = self.eleventh.map(|accidental| accidental.alter(17));
+        let thirteenth = self.thirteenth.map(|accidental| accidental.alter(21));
+
+        [ninth, eleventh, thirteenth].into_iter().flatten()
+    }
+
+    fn count(&self) -> usize {
+        [self.ninth, self.eleventh, self.thirteenth]
+            .iter()
+            .filter(|accidental| accidental.is_some())
+            .count()
+    }
+
     fn add(&mut self, base: Note, extension: Note) -> bool {
         if extension < base {
             return false;
         }
 
-        match base.distance_to(extension) % 12 {
+        self.insert(base.distance_to(extension))
+    }
+
+    /// Adds the extension `half_steps` above the root; fails if the extension is not a
+    /// ninth, an eleventh or a thirteenth, or if one of the same number is already there
+    pub(crate) fn insert(&mut self, half_steps: i8) -> bool {
+        match half_steps % 12 {
             1 => {
                 if self.ninth.is_some() {
                     return false;
@@ -838,6 +1045,14 @@ pub enum Accidental {
 }
 
 impl Accidental {
+    fn alter(&self, half_steps: u8) -> u8 {
+        match self {
+            Accidental::Flat => half_steps - 1,
+            Accidental::Natural => half_steps,
+            Accidental::Sharp => half_steps + 1,
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Accidental::Flat => "♭",
@@ -1691,4 +1906,48 @@ mod tests {
             chord![Gb4, D5, E5].identify()
         );
     }
+
+    #[test]
+    fn ranked() {
+        use crate::ScaleType;
+
+        // C6 and Am7 share their notes; without context the bass decides
+        let chord = chord![C4, E4, G4, A4];
+        let candidates = chord.identify_ranked(None);
+        assert_eq!(2, candidates.len());
+        assert_eq!(C, candidates.best().unwrap().tonic);
+        assert_eq!(ChordKind::MajorSixth, candidates.best().unwrap().id.kind);
+
+        let tonics: Vec<_> = candidates.iter().map(|candidate| candidate.tonic).collect();
+        assert_eq!(vec![C, A], tonics);
+
+        // in A minor the same notes rather are the tonic chord
+        let a_minor = Scale {
+            tonic: A,
+            ty: ScaleType::Minor,
+        };
+        let best = chord.identify_ranked(Some(a_minor)).best().unwrap();
+        assert_eq!(A, best.tonic);
+        assert_eq!(Some(C), best.id.bass);
+
+        let c_major = Scale::major(C);
+        assert_eq!(
+            C,
+            chord.identify_ranked(Some(c_major)).best().unwrap().tonic
+        );
+    }
+
+    #[test]
+    fn ranked_omissions_and_extensions() {
+        // CΔ9 in root position ranks first
+        let candidates = chord![C4, E4, G4, B4, D5].identify_ranked(None);
+        assert_eq!(C, candidates.best().unwrap().tonic);
+
+        // the scores are sorted
+        let scores: Vec<_> = candidates.iter().map(|candidate| candidate.score).collect();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        // nothing to name
+        assert!(chord![C4, Db4].identify_ranked(None).is_empty());
+    }
 }
@@ -0,0 +1,665 @@
+//! Lead-sheet chord symbols, e.g. "Cmaj7", "F♯m7♭5" or "D/F#"
+//!
+//! Accepts the output of `ChordId`'s formatters as well as the usual ASCII spellings
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::chord_id::{Alteration, ChordId, ChordKind, Extensions};
+use crate::{Error, Note, NoteName, Notes, Scale};
+
+/// Accidentals after a note name, e.g. the two flats of B♭♭
+const MAX_ACCIDENTALS: usize = 2;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct ChordSymbol {
+    pub tonic: NoteName,
+    pub id: ChordId,
+}
+
+impl ChordSymbol {
+    /// The bass note, the lowest note, if the chord is not in root position
+    pub fn bass(&self) -> Option<NoteName> {
+        self.id.bass
+    }
+
+    /// Close position voicing with the root in the 4th octave, the extensions in the octave
+    /// above and the bass, if any, in the octave below
+    pub fn notes(&self) -> Notes {
+        let root = self.tonic as u8 + 60;
+
+        let mut notes = Notes::empty();
+        for half_steps in self
+            .id
+            .kind
+            .intervals()
+            .iter()
+            .copied()
+            .chain(self.id.extensions.half_steps())
+        {
+            notes.insert(Note::from_u8_lossy(root + half_steps));
+        }
+
+        if let Some(bass) = self.id.bass {
+            notes.insert(Note::from_u8_lossy(bass as u8 + 48));
+        }
+
+        notes
+    }
+
+    /// The symbol as `ChordId` formats it, e.g. "B♭9(♯11,13)"
+    pub fn display(&self, scale: Scale) -> impl fmt::Display {
+        struct S(ChordSymbol, Scale);
+
+        impl fmt::Display for S {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let S(ChordSymbol { tonic, id }, scale) = self;
+
+                write!(
+                    f,
+                    "{}{}{}{}",
+                    tonic.as_str(*scale),
+                    id.normal(),
+                    id.sup(),
+                    id.sub(*tonic, *scale)
+                )
+            }
+        }
+
+        S(*self, scale)
+    }
+}
+
+impl FromStr for ChordSymbol {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { rest: s.trim() };
+
+        let tonic = parser.note_name().ok_or(Error::MalformedChordSymbol)?;
+        let symbol = parser.symbol().ok_or(Error::MalformedChordSymbol)?;
+
+        let bass = if parser.eat("/") {
+            let bass = parser.note_name().ok_or(Error::MalformedChordSymbol)?;
+            (bass != tonic).then_some(bass)
+        } else {
+            None
+        };
+
+        if !parser.rest.is_empty() {
+            return Err(Error::MalformedChordSymbol);
+        }
+
+        let (kind, extensions) = symbol.build().ok_or(Error::MalformedChordSymbol)?;
+
+        Ok(Self {
+            tonic,
+            id: ChordId {
+                bass,
+                kind,
+                extensions,
+            },
+        })
+    }
+}
+
+/// What the symbol says, before it's validated
+#[derive(Default)]
+struct Symbol {
+    augmented: bool,
+    diminished: bool,
+    /// ninth, eleventh and thirteenth, in half steps above the root
+    extensions: [Option<i8>; 3],
+    flat_five: bool,
+    half_diminished: bool,
+    /// the extensions implied by e.g. "13"; overridden by the explicit ones, e.g. "♯11"
+    implied: [Option<i8>; 3],
+    lydian: bool,
+    major_seventh: bool,
+    minor: bool,
+    no_three: bool,
+    phrygian: bool,
+    power: bool,
+    seventh: bool,
+    sharp_five: bool,
+    sixth: bool,
+    sus2: bool,
+    sus4: bool,
+}
+
+impl Symbol {
+    fn extend(&mut self, half_steps: i8) -> Option<()> {
+        let slot = &mut self.extensions[slot(half_steps)?];
+        if slot.is_some() {
+            return None;
+        }
+
+        *slot = Some(half_steps);
+        Some(())
+    }
+
+    /// "9", "11" and "13" imply the seventh and the lower extensions
+    fn stack(&mut self, number: u8) -> Option<()> {
+        let extensions: &[i8] = match number {
+            7 => &[],
+            9 => &[2],
+            11 => &[2, 5],
+            13 => &[2, 5, 9],
+            _ => return None,
+        };
+
+        for half_steps in extensions {
+            self.implied[slot(*half_steps)?] = Some(*half_steps);
+        }
+
+        Some(())
+    }
+
+    fn build(&self) -> Option<(ChordKind, Extensions)> {
+        let alterations = [
+            self.flat_five,
+            self.sharp_five || self.augmented,
+            self.sus2,
+            self.sus4,
+            self.no_three,
+        ];
+        if alterations.iter().filter(|altered| **altered).count() > 1 {
+            return None;
+        }
+
+        let alteration = if self.flat_five {
+            Alteration::FlatFive
+        } else if self.sharp_five || self.augmented {
+            Alteration::SharpFive
+        } else if self.sus2 {
+            Alteration::Sus2
+        } else if self.sus4 {
+            Alteration::Sus4
+        } else if self.no_three {
+            Alteration::NoThree
+        } else {
+            Alteration::None
+        };
+
+        let is_triad = !self.seventh && !self.major_seventh && !self.sixth;
+        let is_plain = alteration == Alteration::None && !self.minor && !self.diminished;
+
+        let mut extensions = [None; 3];
+        for ((extension, explicit), implied) in
+            extensions.iter_mut().zip(self.extensions).zip(self.implied)
+        {
+            *extension = explicit.or(implied);
+        }
+
+        let kind = if self.power {
+            if !is_triad || !is_plain || extensions.iter().any(Option::is_some) {
+                return None;
+            }
+
+            ChordKind::Power
+        } else if self.lydian || self.phrygian {
+            if !is_triad || !is_plain || self.lydian && self.phrygian {
+                return None;
+            }
+
+            if self.lydian {
+                ChordKind::Lydian
+            } else {
+                ChordKind::Phrygian
+            }
+        } else if self.half_diminished {
+            if self.major_seventh || self.sixth || alteration != Alteration::None {
+                return None;
+            }
+
+            ChordKind::MinorSeventh(Alteration::FlatFive)
+        } else if self.diminished {
+            if self.major_seventh || self.sixth || alteration != Alteration::None {
+                return None;
+            }
+
+            if self.seventh {
+                ChordKind::DiminishedSeventh
+            } else {
+                ChordKind::Diminished
+            }
+        } else if self.sixth {
+            if self.seventh || self.major_seventh || alteration != Alteration::None {
+                return None;
+            }
+
+            if self.minor {
+                ChordKind::MinorSixth
+            } else {
+                ChordKind::MajorSixth
+            }
+        } else if self.major_seventh {
+            if self.minor {
+                match alteration {
+                    Alteration::None | Alteration::SharpFive => {
+                        ChordKind::MinorMajorSeventh(alteration)
+                    }
+                    _ => return None,
+                }
+            } else {
+                ChordKind::MajorSeventh(alteration)
+            }
+        } else if self.seventh {
+            if self.minor {
+                match alteration {
+                    Alteration::None | Alteration::FlatFive | Alteration::SharpFive => {
+                        ChordKind::MinorSeventh(alteration)
+                    }
+                    _ => return None,
+                }
+            } else {
+                ChordKind::Seventh(alteration)
+            }
+        } else {
+            match (self.minor, alteration) {
+                (true, Alteration::FlatFive) => ChordKind::Diminished,
+                (true, Alteration::None) => ChordKind::Minor,
+                (false, Alteration::None) => ChordKind::Major,
+                (false, Alteration::SharpFive) => ChordKind::Augmented,
+                (false, Alteration::Sus2) => ChordKind::Sus2,
+                (false, Alteration::Sus4) => ChordKind::Sus4,
+                _ => return None,
+            }
+        };
+
+        let mut inserted = Extensions::none();
+        for half_steps in extensions.into_iter().flatten() {
+            if !inserted.insert(half_steps) {
+                return None;
+            }
+        }
+
+        Some((kind, inserted))
+    }
+}
+
+/// Index of the ninth, the eleventh or the thirteenth
+fn slot(half_steps: i8) -> Option<usize> {
+    match half_steps {
+        1..=3 => Some(0),
+        5 | 6 => Some(1),
+        8 | 9 => Some(2),
+        _ => None,
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    fn eat(&mut self, prefix: &str) -> bool {
+        if let Some(rest) = self.rest.strip_prefix(prefix) {
+            self.rest = rest;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_any(&mut self, prefixes: &[&str]) -> bool {
+        prefixes.iter().any(|prefix| self.eat(prefix))
+    }
+
+    /// Whether the major marker, if any, makes a major seventh: "Δ" and "^" always do but
+    /// "maj" and "M" only before a 7, 9, 11 or 13, "CM" being the major triad
+    fn major(&mut self) -> Option<bool> {
+        if self.eat_any(&["Δ", "^"]) {
+            return Some(true);
+        }
+
+        self.eat_any(&["maj", "Maj", "M"]).then(|| {
+            ["7", "9", "11", "13"]
+                .iter()
+                .any(|number| self.rest.starts_with(number))
+        })
+    }
+
+    fn number(&mut self) -> Option<u8> {
+        let len = self
+            .rest
+            .bytes()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        let number = self.rest[..len].parse().ok()?;
+        self.rest = &self.rest[len..];
+        Some(number)
+    }
+
+    /// -1, 0 or +1 half step
+    fn accidental(&mut self) -> i8 {
+        if self.eat_any(&["♭", "b"]) {
+            -1
+        } else if self.eat_any(&["♯", "#"]) {
+            1
+        } else if self.eat("𝄫") {
+            -2
+        } else if self.eat("𝄪") {
+            2
+        } else {
+            self.eat("♮");
+            0
+        }
+    }
+
+    fn note_name(&mut self) -> Option<NoteName> {
+        let natural = match self.rest.as_bytes().first()? {
+            b'C' => NoteName::C,
+            b'D' => NoteName::D,
+            b'E' => NoteName::E,
+            b'F' => NoteName::F,
+            b'G' => NoteName::G,
+            b'A' => NoteName::A,
+            b'B' => NoteName::B,
+            _ => return None,
+        };
+        self.rest = &self.rest[1..];
+
+        let mut half_steps = 12;
+        for count in 0.. {
+            match self.accidental() {
+                0 => break,
+                // also keeps `half_steps` from wrapping around
+                _ if count == MAX_ACCIDENTALS => return None,
+                accidental => half_steps += accidental,
+            }
+        }
+
+        Some(natural.step(half_steps as u8))
+    }
+
+    fn symbol(&mut self) -> Option<Symbol> {
+        let mut symbol = Symbol::default();
+
+        // quality
+        if let Some(is_seventh) = self.major() {
+            symbol.major_seventh = is_seventh;
+        } else if self.eat_any(&["min", "mi", "m", "-"]) {
+            symbol.minor = true;
+        } else if self.eat_any(&["aug", "+"]) {
+            symbol.augmented = true;
+        } else if self.eat_any(&["dim", "o", "°"]) {
+            symbol.diminished = true;
+        } else if self.eat("ø") {
+            symbol.half_diminished = true;
+            symbol.seventh = true;
+        }
+
+        if !symbol.major_seventh && !symbol.half_diminished {
+            match self.major() {
+                Some(true) => symbol.major_seventh = true,
+                // after the quality, e.g. "CmMaj", it can only be the seventh
+                Some(false) => return None,
+                None => {}
+            }
+        }
+
+        // main number
+        match self.number() {
+            None => {}
+            Some(5) if !symbol.major_seventh => symbol.power = true,
+            Some(6) if !symbol.major_seventh => {
+                symbol.sixth = true;
+                // six-nine chords
+                if self.eat("/9") || self.eat("9") {
+                    symbol.extend(2)?;
+                }
+            }
+            Some(number) => {
+                if !symbol.major_seventh {
+                    symbol.seventh = true;
+                }
+                symbol.stack(number)?;
+            }
+        }
+
+        // alterations and added tones, optionally in parentheses
+        loop {
+            if self.eat_any(&["(", ")", ","]) {
+                continue;
+            }
+
+            if !symbol.major_seventh && self.eat_any(&["maj7", "Maj7", "M7"]) {
+                // e.g. Cm(maj7)
+                symbol.major_seventh = true;
+                symbol.seventh = false;
+            } else if self.eat("sus") {
+                match self.number() {
+                    Some(2) => symbol.sus2 = true,
+                    Some(4) | None => symbol.sus4 = true,
+                    _ => return None,
+                }
+            } else if self.eat("no3") {
+                symbol.no_three = true;
+            } else if self.eat("Lyd") {
+                symbol.lydian = true;
+            } else if self.eat("Phr") {
+                symbol.phrygian = true;
+            } else if self.eat("add") {
+                let accidental = self.accidental();
+                let half_steps = match self.number()? {
+                    2 | 9 => 2,
+                    4 | 11 => 5,
+                    6 | 13 => 9,
+                    _ => return None,
+                };
+                symbol.extend(half_steps + accidental)?;
+            } else if self.rest.starts_with(['♭', 'b', '♯', '#', '♮'])
+                || self.rest.starts_with(|c: char| c.is_ascii_digit())
+            {
+                let accidental = self.accidental();
+                match (accidental, self.number()?) {
+                    (-1, 5) => symbol.flat_five = true,
+                    (1, 5) => symbol.sharp_five = true,
+                    (_, 9) => symbol.extend(2 + accidental)?,
+                    (_, 11) => symbol.extend(5 + accidental)?,
+                    (_, 13) => symbol.extend(9 + accidental)?,
+                    _ => return None,
+                }
+            } else {
+                break;
+            }
+        }
+
+        Some(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+
+    use super::*;
+    use crate::Chord;
+
+    fn parse(s: &str) -> ChordSymbol {
+        s.parse().unwrap_or_else(|_| panic!("{s}"))
+    }
+
+    fn id(bass: Option<NoteName>, kind: ChordKind, extensions: &[i8]) -> ChordId {
+        let mut id = ChordId {
+            bass,
+            kind,
+            extensions: Extensions::none(),
+        };
+        for half_steps in extensions {
+            assert!(id.extensions.insert(*half_steps));
+        }
+        id
+    }
+
+    #[test]
+    fn lead_sheet() {
+        use Alteration as A;
+        use NoteName::*;
+
+        for (s, tonic, expected) in [
+            ("C", C, id(None, ChordKind::Major, &[])),
+            ("Cmaj7", C, id(None, ChordKind::MajorSeventh(A::None), &[])),
+            ("CΔ", C, id(None, ChordKind::MajorSeventh(A::None), &[])),
+            ("CM", C, id(None, ChordKind::Major, &[])),
+            ("CM7", C, id(None, ChordKind::MajorSeventh(A::None), &[])),
+            ("Cmaj9", C, id(None, ChordKind::MajorSeventh(A::None), &[2])),
+            (
+                "F#m7b5",
+                Gb,
+                id(None, ChordKind::MinorSeventh(A::FlatFive), &[]),
+            ),
+            (
+                "Gbø7",
+                Gb,
+                id(None, ChordKind::MinorSeventh(A::FlatFive), &[]),
+            ),
+            (
+                "Bb13#11",
+                Bb,
+                id(None, ChordKind::Seventh(A::None), &[2, 6, 9]),
+            ),
+            ("G7sus4", G, id(None, ChordKind::Seventh(A::Sus4), &[])),
+            ("D/F#", D, id(Some(Gb), ChordKind::Major, &[])),
+            ("Ebadd9", Eb, id(None, ChordKind::Major, &[2])),
+            ("Am", A, id(None, ChordKind::Minor, &[])),
+            ("A-7", A, id(None, ChordKind::MinorSeventh(A::None), &[])),
+            ("Bdim7", B, id(None, ChordKind::DiminishedSeventh, &[])),
+            ("Caug", C, id(None, ChordKind::Augmented, &[])),
+            ("C7#9", C, id(None, ChordKind::Seventh(A::None), &[3])),
+            (
+                "C7(b9,b13)",
+                C,
+                id(None, ChordKind::Seventh(A::None), &[1, 8]),
+            ),
+            ("C6/9", C, id(None, ChordKind::MajorSixth, &[2])),
+            (
+                "CmMaj7",
+                C,
+                id(None, ChordKind::MinorMajorSeventh(A::None), &[]),
+            ),
+            (
+                "Cm(maj7)",
+                C,
+                id(None, ChordKind::MinorMajorSeventh(A::None), &[]),
+            ),
+            (
+                "Cm(M7)",
+                C,
+                id(None, ChordKind::MinorMajorSeventh(A::None), &[]),
+            ),
+            ("Csus", C, id(None, ChordKind::Sus4, &[])),
+            ("C5", C, id(None, ChordKind::Power, &[])),
+            ("Cb", B, id(None, ChordKind::Major, &[])),
+        ] {
+            assert_eq!(
+                ChordSymbol {
+                    tonic,
+                    id: expected
+                },
+                parse(s),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        for s in [
+            "",
+            "H",
+            "c",
+            "C7x",
+            "C/",
+            "C/H",
+            "C8",
+            "Cadd5",
+            "Cm7sus4",
+            "C5b9",
+            "C7b9#9",
+            "Csus2sus4",
+            "Cbbb",
+            "CmMaj",
+        ] {
+            assert_eq!(
+                Err(Error::MalformedChordSymbol),
+                s.parse::<ChordSymbol>(),
+                "{s}"
+            );
+        }
+
+        // thirteen flats used to wrap around, or overflow
+        for tonic in ["B", "C"] {
+            let s = tonic.to_string() + &"b".repeat(13);
+            assert_eq!(
+                Err(Error::MalformedChordSymbol),
+                s.parse::<ChordSymbol>(),
+                "{s}"
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let scale = Scale::major(NoteName::C);
+
+        for chord in [
+            chord![C4, E4, G4],
+            chord![C4, E4, G4, D5],
+            chord![Eb4, G4, C5],
+            chord![C4, E4, G4, A4],
+            chord![C4, Eb4, G4, Bb4],
+            chord![C4, Eb4, Gb4, Bb4],
+            chord![C4, Eb4, Gb4, A4],
+            chord![C4, E4, Ab4],
+            chord![C4, E4, Ab4, B4],
+            chord![C4, E4, Bb4, D5, F5, A5],
+            chord![C4, E4, Bb4, Db5, Gb5],
+            chord![C4, E4, G4, B4, D5],
+            chord![C4, F4, G4, Bb4, D5],
+            chord![C4, D4, G4],
+            chord![C4, G4],
+            chord![C4, Gb4, G4],
+            chord![C4, Db4, G4],
+            chord![C4, Eb4, Gb4],
+            chord![C4, Eb4, G4, B4],
+            chord![C4, G4, B4],
+            chord![G3, C4, E4],
+        ] {
+            for candidate in chord.identify_ranked(None).iter() {
+                let symbol = ChordSymbol {
+                    tonic: candidate.tonic,
+                    id: candidate.id,
+                };
+                let s = symbol.display(scale).to_string();
+
+                assert_eq!(symbol, parse(&s), "{s}");
+            }
+        }
+    }
+
+    #[test]
+    fn notes() {
+        let symbol = parse("D/F#");
+        assert_eq!(notes![Gb3, D4, Gb4, A4], symbol.notes());
+
+        let symbol = parse("Cadd9");
+        assert_eq!(notes![C4, E4, G4, D5], symbol.notes());
+
+        // the voicing is named like the symbol
+        for s in [
+            "Cmaj7", "F#m7b5", "Bb13#11", "G7sus4", "D/F#", "Ebadd9", "Bdim7",
+        ] {
+            let symbol = parse(s);
+            let chord = Chord::try_from(symbol.notes()).unwrap();
+
+            assert_eq!(
+                Some(symbol.id),
+                chord.identify_with_tonic(symbol.tonic),
+                "{s}"
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Degree, Degrees};
+
+/// Longest name, in bytes
+pub const MAX_NAME_LEN: usize = 32;
+
+/// A scale defined by the user, e.g. a raga or one of Messiaen's modes
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct CustomScale {
+    degrees: Degrees,
+    name: [u8; MAX_NAME_LEN],
+    name_len: u8,
+    /// degrees spelled with their enharmonic alternative, e.g. ♯5 instead of ♭6
+    respelled: Degrees,
+}
+
+impl CustomScale {
+    /// The tonic is always part of the scale
+    pub fn new(name: &str, mut degrees: Degrees) -> Self {
+        degrees.insert(Degree::One);
+
+        let mut this = Self {
+            degrees,
+            name: [0; MAX_NAME_LEN],
+            name_len: 0,
+            respelled: Degrees::empty(),
+        };
+        this.set_name(name);
+        this
+    }
+
+    /// Bit `n` of `mask` is the degree `n` half steps above the tonic
+    pub fn from_mask(name: &str, mask: u16) -> Self {
+        let degrees = Degree::ALL
+            .into_iter()
+            .filter(|degree| mask & (1 << *degree as u8) != 0)
+            .collect();
+
+        Self::new(name, degrees)
+    }
+
+    pub fn mask(&self) -> u16 {
+        self.degrees
+            .iter()
+            .fold(0, |mask, degree| mask | (1 << degree as u8))
+    }
+
+    pub fn degrees(&self) -> Degrees {
+        self.degrees
+    }
+
+    pub fn name(&self) -> &str {
+        // `set_name` only truncates at character boundaries
+        core::str::from_utf8(&self.name[..usize::from(self.name_len)]).unwrap_or_default()
+    }
+
+    /// Names longer than `MAX_NAME_LEN` bytes are truncated
+    pub fn set_name(&mut self, name: &str) {
+        let mut len = name.len().min(MAX_NAME_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        self.name[..len].copy_from_slice(&name.as_bytes()[..len]);
+        self.name_len = len as u8;
+    }
+
+    /// Adds or removes `degree`; the tonic cannot be removed
+    pub fn toggle(&mut self, degree: Degree) {
+        if degree == Degree::One {
+            return;
+        }
+
+        if self.degrees.contains(degree) {
+            self.degrees.remove(degree);
+        } else {
+            self.degrees.insert(degree);
+        }
+    }
+
+    pub fn is_respelled(&self, degree: Degree) -> bool {
+        self.respelled.contains(degree)
+    }
+
+    /// Only the degrees that have an enharmonic alternative can be respelled
+    pub fn set_respelled(&mut self, degree: Degree, respelled: bool) {
+        if respelled && degree.as_alt_str().is_some() {
+            self.respelled.insert(degree);
+        } else {
+            self.respelled.remove(degree);
+        }
+    }
+
+    pub fn degree_str(&self, degree: Degree) -> &'static str {
+        if self.is_respelled(degree) {
+            degree.as_alt_str().unwrap_or(degree.as_str())
+        } else {
+            degree.as_str()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask() {
+        // jazz minor
+        let mask = 0b1010_1010_1101;
+        let scale = CustomScale::from_mask("jazz minor", mask);
+
+        assert_eq!(mask, scale.mask());
+        assert_eq!(7, scale.degrees().len());
+        assert!(scale.degrees().contains(Degree::Six));
+        assert!(!scale.degrees().contains(Degree::FlatSix));
+
+        // the tonic is always included
+        assert!(
+            CustomScale::from_mask("", 0)
+                .degrees()
+                .contains(Degree::One)
+        );
+    }
+
+    #[test]
+    fn toggle() {
+        let mut scale = CustomScale::from_mask("", 0);
+
+        scale.toggle(Degree::Five);
+        assert!(scale.degrees().contains(Degree::Five));
+        scale.toggle(Degree::Five);
+        assert!(!scale.degrees().contains(Degree::Five));
+
+        scale.toggle(Degree::One);
+        assert!(scale.degrees().contains(Degree::One));
+    }
+
+    #[test]
+    fn name() {
+        let mut scale = CustomScale::from_mask("Messiaen mode 3", 0);
+        assert_eq!("Messiaen mode 3", scale.name());
+
+        // truncated at a character boundary
+        let long = "♯".repeat(MAX_NAME_LEN);
+        scale.set_name(&long);
+        assert_eq!(&long[..30], scale.name());
+    }
+
+    #[test]
+    fn respell() {
+        let mut scale = CustomScale::from_mask("", 0xfff);
+
+        scale.set_respelled(Degree::FlatSix, true);
+        assert_eq!("♯5", scale.degree_str(Degree::FlatSix));
+
+        // naturals have no alternative
+        scale.set_respelled(Degree::Five, true);
+        assert!(!scale.is_respelled(Degree::Five));
+        assert_eq!("5", scale.degree_str(Degree::Five));
+    }
+}
@@ -103,6 +103,147 @@ impl Degree {
         Self::Seven,
     ];
 
+    pub const LOCRIAN: [Self; 7] = [
+        Self::One,
+        Self::FlatTwo,
+        Self::FlatThree,
+        Self::Four,
+        Self::SharpFour,
+        Self::FlatSix,
+        Self::FlatSeven,
+    ];
+
+    pub const HARMONIC_MAJOR: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::Three,
+        Self::Four,
+        Self::Five,
+        Self::FlatSix,
+        Self::Seven,
+    ];
+
+    pub const MELODIC_MINOR: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::FlatThree,
+        Self::Four,
+        Self::Five,
+        Self::Six,
+        Self::Seven,
+    ];
+
+    pub const DORIAN_FLAT_TWO: [Self; 7] = [
+        Self::One,
+        Self::FlatTwo,
+        Self::FlatThree,
+        Self::Four,
+        Self::Five,
+        Self::Six,
+        Self::FlatSeven,
+    ];
+
+    pub const LYDIAN_AUGMENTED: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::Three,
+        Self::SharpFour,
+        Self::FlatSix,
+        Self::Six,
+        Self::Seven,
+    ];
+
+    pub const LYDIAN_DOMINANT: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::Three,
+        Self::SharpFour,
+        Self::Five,
+        Self::Six,
+        Self::FlatSeven,
+    ];
+
+    pub const MIXOLYDIAN_FLAT_SIX: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::Three,
+        Self::Four,
+        Self::Five,
+        Self::FlatSix,
+        Self::FlatSeven,
+    ];
+
+    pub const LOCRIAN_NATURAL_TWO: [Self; 7] = [
+        Self::One,
+        Self::Two,
+        Self::FlatThree,
+        Self::Four,
+        Self::SharpFour,
+        Self::FlatSix,
+        Self::FlatSeven,
+    ];
+
+    pub const ALTERED: [Self; 7] = [
+        Self::One,
+        Self::FlatTwo,
+        Self::FlatThree,
+        Self::Three,
+        Self::SharpFour,
+        Self::FlatSix,
+        Self::FlatSeven,
+    ];
+
+    pub const MAJOR_PENTATONIC: [Self; 5] =
+        [Self::One, Self::Two, Self::Three, Self::Five, Self::Six];
+
+    pub const MINOR_PENTATONIC: [Self; 5] = [
+        Self::One,
+        Self::FlatThree,
+        Self::Four,
+        Self::Five,
+        Self::FlatSeven,
+    ];
+
+    pub const BLUES: [Self; 6] = [
+        Self::One,
+        Self::FlatThree,
+        Self::Four,
+        Self::SharpFour,
+        Self::Five,
+        Self::FlatSeven,
+    ];
+
+    pub const WHOLE_TONE: [Self; 6] = [
+        Self::One,
+        Self::Two,
+        Self::Three,
+        Self::SharpFour,
+        Self::FlatSix,
+        Self::FlatSeven,
+    ];
+
+    pub const DIMINISHED_HALF_WHOLE: [Self; 8] = [
+        Self::One,
+        Self::FlatTwo,
+        Self::FlatThree,
+        Self::Three,
+        Self::SharpFour,
+        Self::Five,
+        Self::Six,
+        Self::FlatSeven,
+    ];
+
+    pub const DIMINISHED_WHOLE_HALF: [Self; 8] = [
+        Self::One,
+        Self::Two,
+        Self::FlatThree,
+        Self::Four,
+        Self::SharpFour,
+        Self::FlatSix,
+        Self::Six,
+        Self::Seven,
+    ];
+
     pub(crate) fn from_u8_lossy(value: u8) -> Self {
         use Degree::*;
 
@@ -141,6 +282,35 @@ impl Degree {
         }
     }
 
+    /// The enharmonic spelling of the altered degrees, e.g. "♯5" for `FlatSix`
+    pub fn as_alt_str(&self) -> Option<&'static str> {
+        use Degree::*;
+
+        match self {
+            FlatTwo => Some("♯1"),
+            FlatThree => Some("♯2"),
+            SharpFour => Some("♭5"),
+            FlatSix => Some("♯5"),
+            FlatSeven => Some("♯6"),
+            _ => None,
+        }
+    }
+
+    /// Letters above the tonic, e.g. 2 for `FlatThree`, or 1 for its alternative spelling ♯2
+    pub(crate) fn letters(&self, is_alt: bool) -> u8 {
+        use Degree::*;
+
+        match (self, is_alt && self.as_alt_str().is_some()) {
+            (One, _) | (FlatTwo, true) => 0,
+            (FlatTwo | Two, _) | (FlatThree, true) => 1,
+            (FlatThree | Three, _) => 2,
+            (Four, _) | (SharpFour, false) => 3,
+            (SharpFour, true) | (Five, _) | (FlatSix, true) => 4,
+            (FlatSix | Six, _) | (FlatSeven, true) => 5,
+            (FlatSeven | Seven, _) => 6,
+        }
+    }
+
     pub fn roman_major(&self) -> &'static str {
         use Degree::*;
 
@@ -180,7 +350,7 @@ impl Degree {
     }
 
     pub fn belongs_to(&self, scale: ScaleType) -> bool {
-        scale.degrees().contains(self)
+        scale.degrees().contains(*self)
     }
 
     pub fn step(&self, half_steps: isize) -> Self {
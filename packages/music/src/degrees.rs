@@ -2,13 +2,14 @@ use crate::Degree;
 
 const SIZE: usize = 2;
 
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(test, derive(Debug))]
 pub struct Degrees {
     bits: [u8; SIZE],
 }
 
 impl Degrees {
-    pub fn empty() -> Self {
+    pub const fn empty() -> Self {
         Self { bits: [0; SIZE] }
     }
 
@@ -41,6 +42,16 @@ impl Degrees {
     }
 }
 
+impl FromIterator<Degree> for Degrees {
+    fn from_iter<T: IntoIterator<Item = Degree>>(iter: T) -> Self {
+        let mut degrees = Self::empty();
+        for degree in iter {
+            degrees.insert(degree);
+        }
+        degrees
+    }
+}
+
 impl IntoIterator for Degrees {
     type Item = Degree;
     type IntoIter = Iter;
@@ -0,0 +1,247 @@
+//! Harmony segmentation of broken chords
+//!
+//! Arpeggios and Alberti basses never sound all the notes of their chord at once. The window
+//! keeps the notes that stopped sounding recently so that the harmony they imply can be named.
+//! A new bass starts a new harmony: a note struck below the bass, or a note that moves the bass
+//! up by at most a fifth to a pitch class the harmony does not have yet, once it has sounded a
+//! whole triad
+
+use crate::{Note, NoteNames, Notes};
+
+/// Pitch classes a harmony sounds before a rising bass can end it, so that the upper notes of a
+/// broken chord are not taken for a new bass
+const MIN_NAMES: usize = 3;
+/// Half steps a bass can rise; higher notes, like the seventh of an arpeggio, belong to the
+/// harmony
+const MAX_RISE: i8 = 7;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Length {
+    Millis(f64),
+    Beats { count: f64, bpm: f64 },
+}
+
+impl Length {
+    pub const fn as_ms(&self) -> f64 {
+        match self {
+            Length::Millis(ms) => *ms,
+            Length::Beats { count, bpm } => *count * 60_000. / *bpm,
+        }
+    }
+}
+
+pub struct HarmonyWindow {
+    /// lowest note of the current harmony
+    bass: Option<Note>,
+    length_ms: f64,
+    /// notes that stopped sounding but are still in the window
+    released: Notes,
+    /// when each note stopped sounding, indexed by MIDI note number
+    released_ms: [f64; 128],
+    sounding: Notes,
+}
+
+impl HarmonyWindow {
+    pub const fn new(length: Length) -> Self {
+        Self {
+            bass: None,
+            length_ms: length.as_ms(),
+            released: Notes::empty(),
+            released_ms: [0.; 128],
+            sounding: Notes::empty(),
+        }
+    }
+
+    pub fn set_length(&mut self, length: Length) {
+        self.length_ms = length.as_ms();
+    }
+
+    /// Forgets the notes that no longer sound
+    pub fn clear(&mut self) {
+        self.released.clear();
+        self.bass = self.sounding.lowest();
+    }
+
+    /// The notes that sound plus the ones that stopped sounding less than the window length ago
+    pub fn notes(&self) -> Notes {
+        self.sounding.union(&self.released)
+    }
+
+    /// `sounding` are the notes that sound from `now_ms` on, e.g. held and sustained notes.
+    /// Returns the notes in the window
+    pub fn update(&mut self, sounding: &Notes, now_ms: f64) -> Notes {
+        let struck = sounding.difference(&self.sounding);
+        let is_new_bass = struck
+            .lowest()
+            .is_some_and(|lowest| self.is_new_bass(lowest, sounding));
+
+        for note in self.sounding.difference(sounding).iter() {
+            self.released_ms[usize::from(note.as_u8())] = now_ms;
+            self.released.insert(note);
+        }
+        self.sounding = sounding.clone();
+
+        if is_new_bass {
+            self.clear();
+        }
+
+        for note in self.released.clone().iter() {
+            let is_expired = now_ms - self.released_ms[usize::from(note.as_u8())] >= self.length_ms;
+            if is_expired || sounding.contains(note) {
+                self.released.remove(note);
+            }
+        }
+
+        let notes = self.notes();
+        if self.bass.is_none() || notes.is_empty() {
+            self.bass = notes.lowest();
+        }
+
+        notes
+    }
+
+    /// Whether `lowest`, the lowest of the struck notes, starts a new harmony
+    fn is_new_bass(&self, lowest: Note, sounding: &Notes) -> bool {
+        let Some(bass) = self.bass else {
+            return false;
+        };
+        if lowest < bass {
+            return true;
+        }
+
+        let names = self
+            .notes()
+            .iter()
+            .map(|note| note.name())
+            .collect::<NoteNames>();
+        sounding.lowest() == Some(lowest)
+            && bass.distance_to(lowest) <= MAX_RISE
+            && names.len() >= MIN_NAMES
+            && !names.contains(lowest.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Note;
+
+    /// Plays each note, `dur_ms` long, right after the previous one is released
+    fn play(window: &mut HarmonyWindow, notes: &[Note], start_ms: f64, dur_ms: f64) -> Notes {
+        let mut ms = start_ms;
+        for note in notes {
+            let mut sounding = Notes::empty();
+            sounding.insert(*note);
+            window.update(&sounding, ms);
+            ms += dur_ms;
+        }
+
+        window.notes()
+    }
+
+    #[test]
+    fn alberti() {
+        let mut window = HarmonyWindow::new(Length::Beats {
+            count: 4.,
+            bpm: 120.,
+        });
+
+        let c = play(
+            &mut window,
+            &[Note::C4, Note::G4, Note::E4, Note::G4],
+            0.,
+            250.,
+        );
+        assert_eq!(notes![C4, E4, G4], c);
+
+        // B is a new bass; C and E are left behind
+        let g7 = play(
+            &mut window,
+            &[Note::B3, Note::G4, Note::D4, Note::G4],
+            1_000.,
+            250.,
+        );
+        assert_eq!(notes![B3, D4, G4], g7);
+    }
+
+    #[test]
+    fn rising_bass() {
+        let mut window = HarmonyWindow::new(Length::Beats {
+            count: 4.,
+            bpm: 120.,
+        });
+
+        let c = play(
+            &mut window,
+            &[Note::C4, Note::G4, Note::E4, Note::G4],
+            0.,
+            250.,
+        );
+        assert_eq!(notes![C4, E4, G4], c);
+
+        // F is above C but it is a new bass all the same
+        let f = play(
+            &mut window,
+            &[Note::F4, Note::C5, Note::A4, Note::C5],
+            1_000.,
+            250.,
+        );
+        assert_eq!(notes![F4, A4, C5], f);
+    }
+
+    #[test]
+    fn seventh_arpeggio() {
+        let mut window = HarmonyWindow::new(Length::Millis(2_000.));
+
+        // the seventh is not a new bass
+        let c7 = play(
+            &mut window,
+            &[Note::C4, Note::E4, Note::G4, Note::Bb4],
+            0.,
+            250.,
+        );
+        assert_eq!(notes![C4, E4, G4, Bb4], c7);
+    }
+
+    #[test]
+    fn walking_bass() {
+        let mut window = HarmonyWindow::new(Length::Beats {
+            count: 4.,
+            bpm: 120.,
+        });
+        let (f3, a3) = (Note::from_u8_lossy(53), Note::from_u8_lossy(57));
+
+        let c6 = play(&mut window, &[Note::C3, Note::E3, Note::G3, a3], 0., 500.);
+        assert_eq!(
+            [Note::C3, Note::E3, Note::G3, a3]
+                .into_iter()
+                .collect::<Notes>(),
+            c6
+        );
+
+        // F is a fourth above C
+        let f6 = play(&mut window, &[f3, a3, Note::C4, Note::D4], 2_000., 500.);
+        assert_eq!(
+            [f3, a3, Note::C4, Note::D4].into_iter().collect::<Notes>(),
+            f6
+        );
+    }
+
+    #[test]
+    fn expiry() {
+        let mut window = HarmonyWindow::new(Length::Millis(500.));
+
+        play(&mut window, &[Note::C4, Note::E4, Note::G4], 0., 200.);
+        assert_eq!(notes![C4, E4, G4], window.update(&Notes::empty(), 600.));
+
+        // the C was released at 200 ms
+        assert_eq!(notes![E4, G4], window.update(&Notes::empty(), 800.));
+        assert_eq!(Notes::empty(), window.update(&Notes::empty(), 2_000.));
+
+        // a length of zero only keeps what sounds
+        window.set_length(Length::Millis(0.));
+        play(&mut window, &[Note::C4, Note::E4], 3_000., 200.);
+        assert_eq!(notes![E4], window.notes());
+    }
+}
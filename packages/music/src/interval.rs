@@ -1,3 +1,5 @@
+use crate::Note;
+
 #[derive(Clone, Copy)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[allow(non_camel_case_types)]
@@ -106,6 +108,36 @@ impl Interval {
             P15 => "P15",
         }
     }
+
+    /// Whether the interval sounds muddy when its lower note is `lower`, according to the low
+    /// interval limits of orchestration
+    pub fn is_below_low_limit(&self, lower: Note) -> bool {
+        use Interval::*;
+
+        match self {
+            // unlimited
+            P0 | P8 | P15 => false,
+
+            m2 => lower < Note::E3,
+            M2 => lower < Note::Eb3,
+            m3 => lower < Note::C3,
+            M3 => lower < Note::Bb2,
+            P4 => lower < Note::Bb2,
+            TT => lower < Note::Bb2,
+            P5 => lower < Note::Bb1,
+            m6 => lower < Note::G2,
+            M6 => lower < Note::F2,
+            m7 => lower < Note::F2,
+            M7 => lower < Note::F2,
+            m9 => lower < Note::E2,
+            M9 => lower < Note::Eb2,
+            m10 => lower < Note::C2,
+            M10 => lower < Note::Bb1,
+
+            // no data
+            P11 | A11 | P12 | m13 | M13 | m14 | M14 => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -125,4 +157,13 @@ mod tests {
         assert_eq!(M7, Interval::from_u8_lossy(11));
         assert_eq!(M14, Interval::from_u8_lossy(23));
     }
+
+    #[test]
+    fn is_below_low_limit() {
+        use Interval::*;
+
+        assert!(m3.is_below_low_limit(Note::A0));
+        assert!(!m3.is_below_low_limit(Note::C3));
+        assert!(!P8.is_below_low_limit(Note::A0));
+    }
 }
@@ -0,0 +1,285 @@
+//! Key finding from what is being played
+//!
+//! Krumhansl–Schmuckler algorithm: the pitch-class histogram of the recently played notes is
+//! correlated with the Krumhansl–Kessler profile of each of the 24 major and minor keys. Each
+//! pitch class is weighted by how long it has been sounding; older notes weigh less.
+
+use crate::{NoteName, Notes, Scale, ScaleType};
+
+/// Probe-tone ratings of each degree of a major key, starting from the tonic
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+/// Probe-tone ratings of each degree of a minor key, starting from the tonic
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Time constant of the decay of the histogram
+const TAU_MS: f64 = 10_000.;
+/// An estimate whose correlation is below this is too weak to be reported
+const MIN_CORRELATION: f64 = 0.6;
+/// How much better than the current key another key must correlate to replace it
+const HYSTERESIS: f64 = 0.1;
+
+pub struct KeyFinder {
+    /// the key reported by `update`
+    current: Option<Scale>,
+    /// decayed time, in ms, each pitch class has been sounding
+    histogram: [f64; 12],
+    last_ms: f64,
+    /// number of notes of each pitch class that are sounding
+    sounding: [u8; 12],
+}
+
+impl KeyFinder {
+    pub const fn new() -> Self {
+        Self {
+            current: None,
+            histogram: [0.; 12],
+            last_ms: 0.,
+            sounding: [0; 12],
+        }
+    }
+
+    /// The last key returned by `update`
+    pub fn current(&self) -> Option<Scale> {
+        self.current
+    }
+
+    /// `sounding` are the notes that sound from `now_ms` on, e.g. held and sustained notes.
+    /// Returns the new key when the estimate changes with enough confidence
+    pub fn update(&mut self, sounding: &Notes, now_ms: f64) -> Option<Scale> {
+        let dt = (now_ms - self.last_ms).max(0.);
+        let decay = exp(-dt / TAU_MS);
+        for (weight, count) in self.histogram.iter_mut().zip(self.sounding) {
+            // exact integral of a constant input through the exponential decay
+            *weight = *weight * decay + f64::from(count) * TAU_MS * (1. - decay);
+        }
+        self.last_ms = now_ms;
+
+        self.sounding = [0; 12];
+        for note in sounding.iter() {
+            self.sounding[note.name() as usize] += 1;
+        }
+
+        let (best, correlation) = self.estimate()?;
+        if correlation < MIN_CORRELATION || Some(best) == self.current {
+            return None;
+        }
+
+        if let Some(current) = self.current {
+            if correlation < self.correlation(current) + HYSTERESIS {
+                return None;
+            }
+        }
+
+        self.current = Some(best);
+        Some(best)
+    }
+
+    /// The best matching key and its correlation; `None` if nothing has been played
+    pub fn estimate(&self) -> Option<(Scale, f64)> {
+        if self.histogram.iter().all(|weight| *weight == 0.) {
+            return None;
+        }
+
+        NoteName::CIRCLE_OF_FIFTHS
+            .into_iter()
+            .flat_map(|tonic| [ScaleType::Major, ScaleType::Minor].map(|ty| Scale { tonic, ty }))
+            .map(|key| (key, self.correlation(key)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Pearson correlation between the histogram and the profile of `key`
+    fn correlation(&self, key: Scale) -> f64 {
+        let profile = if key.ty == ScaleType::Minor {
+            &MINOR_PROFILE
+        } else {
+            &MAJOR_PROFILE
+        };
+
+        let mean_x = self.histogram.iter().sum::<f64>() / 12.;
+        let mean_y = profile.iter().sum::<f64>() / 12.;
+
+        let (mut cov, mut var_x, mut var_y) = (0., 0., 0.);
+        for (pitch_class, weight) in self.histogram.iter().enumerate() {
+            let degree = (pitch_class + 12 - key.tonic as usize) % 12;
+            let dx = weight - mean_x;
+            let dy = profile[degree] - mean_y;
+            cov += dx * dy;
+            var_x += dx * dx;
+            var_y += dy * dy;
+        }
+
+        if var_x == 0. {
+            return 0.;
+        }
+
+        cov / sqrt(var_x * var_y)
+    }
+}
+
+impl Default for KeyFinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `e^x` for `x <= 0`; `core` has no floating point math functions
+fn exp(x: f64) -> f64 {
+    // e^x = (e^(x / 2^n))^(2^n) where the small exponent is handled by a Taylor series
+    let mut n = 0;
+    let mut y = x;
+    while y < -0.5 && n < 64 {
+        y /= 2.;
+        n += 1;
+    }
+
+    let mut result = 1.;
+    let mut term = 1.;
+    for k in 1..12 {
+        term *= y / f64::from(k);
+        result += term;
+    }
+    for _ in 0..n {
+        result *= result;
+    }
+    result
+}
+
+/// Newton's method; `core` has no floating point math functions
+fn sqrt(x: f64) -> f64 {
+    if x <= 0. {
+        return 0.;
+    }
+
+    let mut root = if x > 1. { x } else { 1. };
+    loop {
+        let next = (root + x / root) / 2.;
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Note;
+
+    fn play(finder: &mut KeyFinder, notes: &[Note], start_ms: f64, dur_ms: f64) -> Option<Scale> {
+        let mut changed = None;
+        for (index, note) in notes.iter().enumerate() {
+            let mut sounding = Notes::empty();
+            sounding.insert(*note);
+            let ms = start_ms + index as f64 * dur_ms;
+            changed = finder.update(&sounding, ms).or(changed);
+        }
+        changed.or(finder.update(&Notes::empty(), start_ms + notes.len() as f64 * dur_ms))
+    }
+
+    #[test]
+    fn math() {
+        for x in [0., -0.1, -1., -2.5, -10.0_f64] {
+            assert!((x.exp() - exp(x)).abs() < 1e-9, "{x}");
+        }
+
+        for x in [0.25, 1., 2., 1e6_f64] {
+            assert!((x.sqrt() - sqrt(x)).abs() < 1e-9, "{x}");
+        }
+    }
+
+    #[test]
+    fn major_scale() {
+        let mut finder = KeyFinder::new();
+        assert_eq!(None, finder.estimate());
+
+        let key = play(
+            &mut finder,
+            &[
+                Note::C4,
+                Note::D4,
+                Note::E4,
+                Note::F4,
+                Note::G4,
+                Note::A4,
+                Note::B4,
+                Note::C5,
+                Note::G4,
+                Note::E4,
+                Note::C4,
+            ],
+            0.,
+            500.,
+        );
+        assert_eq!(Some(Scale::major(NoteName::C)), key);
+    }
+
+    #[test]
+    fn minor_scale() {
+        let mut finder = KeyFinder::new();
+        let key = play(
+            &mut finder,
+            &[
+                Note::A4,
+                Note::C5,
+                Note::E5,
+                Note::A5,
+                Note::B4,
+                Note::Ab4,
+                Note::A4,
+                Note::E4,
+                Note::A4,
+            ],
+            0.,
+            500.,
+        );
+        assert_eq!(Some(Scale::minor(NoteName::A)), key);
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut finder = KeyFinder::new();
+        play(
+            &mut finder,
+            &[
+                Note::C4,
+                Note::E4,
+                Note::G4,
+                Note::C5,
+                Note::F4,
+                Note::A4,
+                Note::G4,
+                Note::B4,
+                Note::C4,
+            ],
+            0.,
+            500.,
+        );
+        assert_eq!(Some(Scale::major(NoteName::C)), finder.current());
+
+        // a single out-of-key note does not change the key
+        assert_eq!(None, play(&mut finder, &[Note::Gb4], 4500., 500.));
+        assert_eq!(Some(Scale::major(NoteName::C)), finder.current());
+
+        // modulating to G major does, once it has been established
+        let g_major = [
+            Note::G3,
+            Note::B4,
+            Note::D5,
+            Note::G4,
+            Note::Gb4,
+            Note::A4,
+            Note::D4,
+            Note::G5,
+            Note::B5,
+            Note::D5,
+            Note::Gb5,
+            Note::G4,
+        ];
+        let key = play(&mut finder, &g_major, 60_000., 500.);
+        assert_eq!(Some(Scale::major(NoteName::G)), key);
+    }
+}
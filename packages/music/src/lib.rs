@@ -5,32 +5,63 @@
 mod macros;
 mod chord;
 pub mod chord_id;
+pub mod chord_symbol;
+pub mod custom_scale;
 mod degree;
 mod degrees;
+pub mod harmony_window;
 mod interval;
+pub mod key_finder;
 mod major_scale;
+pub mod midi;
+pub mod neo_riemannian;
 mod note;
 mod note_name;
 mod note_names;
 pub mod notes;
+pub mod overtones;
+pub mod progression;
+pub mod roman;
 mod scale;
 mod scale_type;
+pub mod set_class;
+pub mod smf;
+pub mod spelled_interval;
+pub mod spelled_pitch;
+pub mod tuning;
+pub mod voicing;
 
 pub use chord::Chord;
+pub use chord_symbol::ChordSymbol;
+pub use custom_scale::CustomScale;
 pub use degree::Degree;
 pub use degrees::Degrees;
+pub use harmony_window::HarmonyWindow;
 pub use interval::Interval;
+pub use key_finder::KeyFinder;
 pub use major_scale::MajorScale;
+pub use midi::{MidiDecoder, MidiMessage};
 pub use note::Note;
 pub use note_name::NoteName;
 pub use note_names::NoteNames;
 pub use notes::Notes;
+pub use overtones::OvertoneSeries;
+pub use progression::Progression;
+pub use roman::RomanNumeral;
 pub use scale::Scale;
 pub use scale_type::ScaleType;
+pub use smf::Smf;
+pub use spelled_interval::SpelledInterval;
+pub use spelled_pitch::SpelledPitch;
+pub use tuning::Tuning;
+pub use voicing::Voicing;
 
 #[cfg_attr(test, derive(PartialEq))]
 #[derive(Debug)]
 pub enum Error {
     NoteOutOfRange,
     CannotInvert,
+    MalformedChordSymbol,
+    MalformedSmf,
+    UnsupportedSmf,
 }
@@ -0,0 +1,529 @@
+//! Incremental MIDI 1.0 byte-stream decoder
+//!
+//! Handles running status, several messages in one packet, system real-time messages interleaved
+//! with other messages and system exclusive messages. The contents of system exclusive messages
+//! are not kept.
+
+use crate::Note;
+
+pub const CONTROL_MODULATION_WHEEL: u8 = 1;
+pub const CONTROL_DATA_ENTRY_MSB: u8 = 6;
+pub const CONTROL_DATA_ENTRY_LSB: u8 = 38;
+pub const CONTROL_HOLD_PEDAL: u8 = 64;
+pub const CONTROL_SOSTENUTO_PEDAL: u8 = 66;
+pub const CONTROL_SOFT_PEDAL: u8 = 67;
+pub const CONTROL_RPN_LSB: u8 = 100;
+pub const CONTROL_RPN_MSB: u8 = 101;
+
+/// Value of `PitchBend` when the wheel is at rest
+pub const PITCH_BEND_CENTER: u16 = 0x2000;
+
+/// Registered parameter numbers, as `[MSB, LSB]`
+const RPN_PITCH_BEND_SENSITIVITY: [u8; 2] = [0, 0];
+const RPN_NULL: [u8; 2] = [0x7f, 0x7f];
+
+const STATUS_SYSEX: u8 = 0xf0;
+const STATUS_TIME_CODE_QUARTER_FRAME: u8 = 0xf1;
+const STATUS_SONG_POSITION: u8 = 0xf2;
+const STATUS_SONG_SELECT: u8 = 0xf3;
+const STATUS_TUNE_REQUEST: u8 = 0xf6;
+const STATUS_END_OF_SYSEX: u8 = 0xf7;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum MidiMessage {
+    /* channel voice messages */
+    /// A note-on with zero velocity is reported as a note-off with zero velocity
+    NoteOff {
+        channel: u8,
+        note: Note,
+        velocity: u8,
+    },
+    NoteOn {
+        channel: u8,
+        note: Note,
+        velocity: u8,
+    },
+    PolyphonicAftertouch {
+        channel: u8,
+        note: Note,
+        pressure: u8,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    ProgramChange {
+        channel: u8,
+        program: u8,
+    },
+    ChannelAftertouch {
+        channel: u8,
+        pressure: u8,
+    },
+    /// `value` is 14-bit; see `PITCH_BEND_CENTER`
+    PitchBend {
+        channel: u8,
+        value: u16,
+    },
+
+    /* system common messages */
+    /// `len` is the number of data bytes
+    SystemExclusive {
+        len: u32,
+    },
+    TimeCodeQuarterFrame(u8),
+    /// In MIDI beats (sixteenth notes) since the start of the song
+    SongPosition(u16),
+    SongSelect(u8),
+    TuneRequest,
+
+    /* system real-time messages */
+    TimingClock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+    Reset,
+}
+
+impl MidiMessage {
+    /// `None` for system messages
+    pub fn channel(&self) -> Option<u8> {
+        match *self {
+            MidiMessage::NoteOff { channel, .. }
+            | MidiMessage::NoteOn { channel, .. }
+            | MidiMessage::PolyphonicAftertouch { channel, .. }
+            | MidiMessage::ControlChange { channel, .. }
+            | MidiMessage::ProgramChange { channel, .. }
+            | MidiMessage::ChannelAftertouch { channel, .. }
+            | MidiMessage::PitchBend { channel, .. } => Some(channel),
+
+            _ => None,
+        }
+    }
+}
+
+/// Turns a stream of bytes into `MidiMessage`s; keep one per input
+pub struct MidiDecoder {
+    data: [u8; 2],
+    /// number of data bytes in `data`
+    len: u8,
+    /// number of data bytes received since the start of the current system exclusive message
+    sysex_len: Option<u32>,
+    /// status of the message being received; remains set after a channel message is complete so
+    /// it acts as the running status
+    status: Option<u8>,
+}
+
+impl MidiDecoder {
+    pub const fn new() -> Self {
+        Self {
+            data: [0; 2],
+            len: 0,
+            sysex_len: None,
+            status: None,
+        }
+    }
+
+    /// Decodes all the messages completed by `bytes`
+    pub fn decode<'a>(&'a mut self, bytes: &'a [u8]) -> impl Iterator<Item = MidiMessage> + 'a {
+        bytes.iter().filter_map(|byte| self.push(*byte))
+    }
+
+    /// Returns a message if `byte` completes one
+    pub fn push(&mut self, byte: u8) -> Option<MidiMessage> {
+        if byte >= 0xf8 {
+            // real-time messages can appear anywhere, even between the bytes of other messages
+            return real_time(byte);
+        }
+
+        if byte & 0x80 != 0 {
+            return self.push_status(byte);
+        }
+
+        if let Some(len) = &mut self.sysex_len {
+            *len += 1;
+            return None;
+        }
+
+        // data bytes without a status are ignored
+        let status = self.status?;
+        self.data[usize::from(self.len)] = byte;
+        self.len += 1;
+        if self.len < num_data_bytes(status) {
+            return None;
+        }
+
+        self.len = 0;
+        if status >= STATUS_SYSEX {
+            // system common messages do not set the running status
+            self.status = None;
+        }
+
+        Some(message(status, self.data))
+    }
+
+    fn push_status(&mut self, status: u8) -> Option<MidiMessage> {
+        self.len = 0;
+        self.status = None;
+
+        // any status byte terminates a system exclusive message; only the "End of Exclusive" one
+        // terminates it correctly
+        let sysex_len = self.sysex_len.take();
+
+        match status {
+            STATUS_SYSEX => {
+                self.sysex_len = Some(0);
+                None
+            }
+
+            STATUS_END_OF_SYSEX => sysex_len.map(|len| MidiMessage::SystemExclusive { len }),
+
+            STATUS_TUNE_REQUEST => Some(MidiMessage::TuneRequest),
+
+            STATUS_TIME_CODE_QUARTER_FRAME | STATUS_SONG_POSITION | STATUS_SONG_SELECT => {
+                self.status = Some(status);
+                None
+            }
+
+            // undefined system common messages
+            0xf4 | 0xf5 => None,
+
+            _ => {
+                self.status = Some(status);
+                None
+            }
+        }
+    }
+}
+
+impl Default for MidiDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Follows the Registered Parameter Number (RPN) messages of one channel to learn the range of
+/// its pitch bend wheel
+#[derive(Clone, Copy)]
+pub struct PitchBendSensitivity {
+    rpn: [u8; 2],
+    /// `[semitones, cents]`; `None` until the sender sets it
+    value: Option<[u8; 2]>,
+}
+
+impl PitchBendSensitivity {
+    pub const fn new() -> Self {
+        Self {
+            rpn: RPN_NULL,
+            value: None,
+        }
+    }
+
+    /// Returns `true` if the control change modified the sensitivity
+    pub fn control_change(&mut self, controller: u8, value: u8) -> bool {
+        match controller {
+            CONTROL_RPN_MSB => self.rpn[0] = value,
+            CONTROL_RPN_LSB => self.rpn[1] = value,
+            CONTROL_DATA_ENTRY_MSB if self.rpn == RPN_PITCH_BEND_SENSITIVITY => {
+                // the cents, if any, follow
+                self.value = Some([value, 0]);
+                return true;
+            }
+            CONTROL_DATA_ENTRY_LSB if self.rpn == RPN_PITCH_BEND_SENSITIVITY => {
+                if let Some(semitones_cents) = &mut self.value {
+                    semitones_cents[1] = value;
+                    return true;
+                }
+            }
+            _ => {}
+        }
+
+        false
+    }
+
+    /// Range of the pitch bend wheel in each direction, in semitones
+    pub fn semitones(&self) -> Option<f64> {
+        self.value
+            .map(|[semitones, cents]| f64::from(semitones) + f64::from(cents) / 100.)
+    }
+}
+
+impl Default for PitchBendSensitivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converts the value of a `PitchBend` message into semitones; `range` is the sensitivity in
+/// semitones
+pub fn pitch_bend_semitones(value: u16, range: f64) -> f64 {
+    let center = f64::from(PITCH_BEND_CENTER);
+    (f64::from(value) - center) / center * range
+}
+
+fn num_data_bytes(status: u8) -> u8 {
+    match status {
+        STATUS_TIME_CODE_QUARTER_FRAME | STATUS_SONG_SELECT => 1,
+        STATUS_SONG_POSITION => 2,
+        _ if matches!(status >> 4, 0xc | 0xd) => 1,
+        _ => 2,
+    }
+}
+
+fn message(status: u8, [data1, data2]: [u8; 2]) -> MidiMessage {
+    let channel = status & 0x0f;
+    let note = Note::from_u8_lossy(data1);
+    let fourteen_bits = u16::from(data1) | (u16::from(data2) << 7);
+
+    match status {
+        STATUS_TIME_CODE_QUARTER_FRAME => MidiMessage::TimeCodeQuarterFrame(data1),
+        STATUS_SONG_POSITION => MidiMessage::SongPosition(fourteen_bits),
+        STATUS_SONG_SELECT => MidiMessage::SongSelect(data1),
+        _ => match status >> 4 {
+            0x8 => MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity: data2,
+            },
+            0x9 if data2 == 0 => MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity: 0,
+            },
+            0x9 => MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity: data2,
+            },
+            0xa => MidiMessage::PolyphonicAftertouch {
+                channel,
+                note,
+                pressure: data2,
+            },
+            0xb => MidiMessage::ControlChange {
+                channel,
+                controller: data1,
+                value: data2,
+            },
+            0xc => MidiMessage::ProgramChange {
+                channel,
+                program: data1,
+            },
+            0xd => MidiMessage::ChannelAftertouch {
+                channel,
+                pressure: data1,
+            },
+            _ => MidiMessage::PitchBend {
+                channel,
+                value: fourteen_bits,
+            },
+        },
+    }
+}
+
+fn real_time(status: u8) -> Option<MidiMessage> {
+    let message = match status {
+        0xf8 => MidiMessage::TimingClock,
+        0xfa => MidiMessage::Start,
+        0xfb => MidiMessage::Continue,
+        0xfc => MidiMessage::Stop,
+        0xfe => MidiMessage::ActiveSensing,
+        0xff => MidiMessage::Reset,
+        // undefined
+        _ => return None,
+    };
+
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(bytes: &[u8]) -> Vec<MidiMessage> {
+        MidiDecoder::new().decode(bytes).collect()
+    }
+
+    fn note_on(channel: u8, note: Note, velocity: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel,
+            note,
+            velocity,
+        }
+    }
+
+    #[test]
+    fn channel_messages() {
+        assert_eq!(
+            vec![
+                note_on(0, Note::C4, 100),
+                MidiMessage::NoteOff {
+                    channel: 1,
+                    note: Note::C4,
+                    velocity: 64
+                },
+                MidiMessage::PolyphonicAftertouch {
+                    channel: 2,
+                    note: Note::E4,
+                    pressure: 3
+                },
+                MidiMessage::ControlChange {
+                    channel: 3,
+                    controller: CONTROL_HOLD_PEDAL,
+                    value: 127
+                },
+                MidiMessage::ProgramChange {
+                    channel: 4,
+                    program: 5
+                },
+                MidiMessage::ChannelAftertouch {
+                    channel: 5,
+                    pressure: 6
+                },
+                MidiMessage::PitchBend {
+                    channel: 15,
+                    value: PITCH_BEND_CENTER
+                },
+            ],
+            decode(&[
+                0x90, 60, 100, // note on
+                0x81, 60, 64, // note off
+                0xa2, 64, 3, // polyphonic aftertouch
+                0xb3, 64, 127, // control change
+                0xc4, 5, // program change
+                0xd5, 6, // channel aftertouch
+                0xef, 0x00, 0x40, // pitch bend
+            ])
+        );
+    }
+
+    #[test]
+    fn running_status() {
+        assert_eq!(
+            vec![
+                note_on(0, Note::C4, 100),
+                note_on(0, Note::E4, 100),
+                MidiMessage::NoteOff {
+                    channel: 0,
+                    note: Note::C4,
+                    velocity: 0
+                },
+                MidiMessage::ProgramChange {
+                    channel: 1,
+                    program: 2
+                },
+                MidiMessage::ProgramChange {
+                    channel: 1,
+                    program: 3
+                },
+            ],
+            decode(&[0x90, 60, 100, 64, 100, 60, 0, 0xc1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn split_across_packets() {
+        let mut decoder = MidiDecoder::new();
+
+        assert_eq!(None, decoder.decode(&[0x90, 60]).next());
+        assert_eq!(
+            vec![note_on(0, Note::C4, 100)],
+            decoder.decode(&[100, 64]).collect::<Vec<_>>()
+        );
+        // running status carries over to the next packet
+        assert_eq!(
+            vec![note_on(0, Note::E4, 90)],
+            decoder.decode(&[90]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn real_time_messages_are_interleaved() {
+        assert_eq!(
+            vec![
+                MidiMessage::TimingClock,
+                MidiMessage::ActiveSensing,
+                note_on(0, Note::C4, 100),
+                MidiMessage::Start,
+                MidiMessage::Stop,
+                note_on(0, Note::E4, 100),
+            ],
+            decode(&[0x90, 0xf8, 60, 0xfe, 100, 0xfa, 0xf9, 64, 0xfc, 100])
+        );
+    }
+
+    #[test]
+    fn system_exclusive() {
+        assert_eq!(
+            vec![
+                MidiMessage::TimingClock,
+                MidiMessage::SystemExclusive { len: 4 },
+                note_on(0, Note::C4, 100),
+            ],
+            decode(&[0xf0, 0x7e, 0x7f, 0xf8, 0x09, 0x01, 0xf7, 0x90, 60, 100])
+        );
+
+        // terminated by another status byte; the running status is cancelled by sysex
+        assert_eq!(
+            vec![note_on(1, Note::C4, 100)],
+            decode(&[0x90, 0xf0, 0x01, 0x91, 60, 100])
+        );
+        assert_eq!(
+            vec![MidiMessage::SystemExclusive { len: 0 }],
+            decode(&[0x90, 60, 0xf0, 0xf7, 64, 100])
+        );
+    }
+
+    #[test]
+    fn system_common_messages() {
+        assert_eq!(
+            vec![
+                MidiMessage::TimeCodeQuarterFrame(0x12),
+                MidiMessage::SongPosition(0x81),
+                MidiMessage::SongSelect(3),
+                MidiMessage::TuneRequest,
+            ],
+            // the data bytes after the song select are not a running status
+            decode(&[0xf1, 0x12, 0xf2, 0x01, 0x01, 0xf3, 3, 4, 0xf4, 0xf6, 5])
+        );
+    }
+
+    #[test]
+    fn data_without_status_is_ignored() {
+        assert_eq!(
+            vec![note_on(0, Note::C4, 100)],
+            decode(&[60, 100, 0x90, 60, 100])
+        );
+    }
+
+    #[test]
+    fn pitch_bend_sensitivity() {
+        let mut sensitivity = PitchBendSensitivity::new();
+        assert_eq!(None, sensitivity.semitones());
+
+        // data entry without selecting the parameter first
+        assert!(!sensitivity.control_change(CONTROL_DATA_ENTRY_MSB, 12));
+
+        assert!(!sensitivity.control_change(CONTROL_RPN_MSB, 0));
+        assert!(!sensitivity.control_change(CONTROL_RPN_LSB, 0));
+        assert!(sensitivity.control_change(CONTROL_DATA_ENTRY_MSB, 12));
+        assert_eq!(Some(12.), sensitivity.semitones());
+        assert!(sensitivity.control_change(CONTROL_DATA_ENTRY_LSB, 50));
+        assert_eq!(Some(12.5), sensitivity.semitones());
+
+        // fine tuning (RPN 1) is not the pitch bend sensitivity
+        sensitivity.control_change(CONTROL_RPN_LSB, 1);
+        assert!(!sensitivity.control_change(CONTROL_DATA_ENTRY_MSB, 2));
+        assert_eq!(Some(12.5), sensitivity.semitones());
+    }
+
+    #[test]
+    fn pitch_bend_to_semitones() {
+        assert_eq!(0., pitch_bend_semitones(PITCH_BEND_CENTER, 2.));
+        assert_eq!(-2., pitch_bend_semitones(0, 2.));
+        assert_eq!(1., pitch_bend_semitones(0x3000, 2.));
+    }
+}
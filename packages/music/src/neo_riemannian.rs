@@ -0,0 +1,295 @@
+//! Neo-Riemannian transformations between major and minor triads
+//!
+//! P, L and R keep two of the three notes and move the third one by a half or a whole step. N,
+//! S and H are compounds of those that still map triads one to one. Chromatic mediants, like C
+//! to E or C to A♭, are two steps away
+
+use core::fmt;
+
+use crate::{NoteName, NoteNames};
+
+/// Number of major and minor triads
+const TRIADS: usize = 24;
+/// Longest of the shortest paths between two triads using P, L and R
+const MAX_STEPS: usize = 5;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Triad {
+    pub root: NoteName,
+    pub is_minor: bool,
+}
+
+impl Triad {
+    pub const fn major(root: NoteName) -> Self {
+        Self {
+            root,
+            is_minor: false,
+        }
+    }
+
+    pub const fn minor(root: NoteName) -> Self {
+        Self {
+            root,
+            is_minor: true,
+        }
+    }
+
+    /// The triad made of exactly these pitch classes, if they form a major or minor triad
+    pub fn from_names(names: &NoteNames) -> Option<Self> {
+        if names.len() != 3 {
+            return None;
+        }
+
+        names.iter().find_map(|root| {
+            let third = if names.contains(root.step(4)) {
+                false
+            } else if names.contains(root.step(3)) {
+                true
+            } else {
+                return None;
+            };
+
+            names.contains(root.step(7)).then_some(Self {
+                root,
+                is_minor: third,
+            })
+        })
+    }
+
+    pub fn third(&self) -> NoteName {
+        self.root.step(if self.is_minor { 3 } else { 4 })
+    }
+
+    pub fn fifth(&self) -> NoteName {
+        self.root.step(7)
+    }
+
+    pub fn names(&self) -> NoteNames {
+        [self.root, self.third(), self.fifth()]
+            .into_iter()
+            .collect()
+    }
+
+    pub fn apply(&self, transformation: Transformation) -> Self {
+        use Transformation::*;
+
+        // half steps from the root of a major triad to the root of the minor triad it maps to;
+        // each transformation is its own inverse
+        let half_steps = match transformation {
+            P => 0,
+            L => 4,
+            R => 9,
+            N => 5,
+            S => 1,
+            H => 8,
+        };
+
+        if self.is_minor {
+            Self::major(self.root.step(12 - half_steps))
+        } else {
+            Self::minor(self.root.step(half_steps))
+        }
+    }
+
+    /// The single transformation from this triad to `other`, if any
+    pub fn transformation(&self, other: Self) -> Option<Transformation> {
+        Transformation::ALL
+            .into_iter()
+            .find(|transformation| self.apply(*transformation) == other)
+    }
+
+    /// The shortest way from this triad to `other`: a single transformation when there is one,
+    /// otherwise the fewest P, L and R steps
+    pub fn path(&self, other: Self) -> Path {
+        let mut path = Path::empty();
+        if *self == other {
+            return path;
+        }
+
+        if let Some(transformation) = self.transformation(other) {
+            path.push(transformation);
+            return path;
+        }
+
+        // breadth-first search, remembering the step that led to each triad
+        let mut previous: [Option<Transformation>; TRIADS] = [None; TRIADS];
+        let mut queue = [*self; TRIADS];
+        let (mut head, mut tail) = (0, 1);
+        while head < tail && previous[other.index()].is_none() {
+            let triad = queue[head];
+            head += 1;
+
+            for transformation in Transformation::PLR {
+                let next = triad.apply(transformation);
+                if next != *self && previous[next.index()].is_none() {
+                    previous[next.index()] = Some(transformation);
+                    queue[tail] = next;
+                    tail += 1;
+                }
+            }
+        }
+
+        let mut triad = other;
+        while let Some(transformation) = previous[triad.index()] {
+            path.push(transformation);
+            triad = triad.apply(transformation);
+        }
+        path.steps[..path.len].reverse();
+
+        path
+    }
+
+    fn index(&self) -> usize {
+        self.root as usize * 2 + usize::from(self.is_minor)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Transformation {
+    /// Parallel: C to Cm, moving the third
+    P,
+    /// Leading-tone exchange: C to Em, moving the root down a half step
+    L,
+    /// Relative: C to Am, moving the fifth up a whole step
+    R,
+    /// Nebenverwandt, RLP: C to Fm
+    N,
+    /// Slide, LPR: C to C♯m, keeping the third
+    S,
+    /// Hexatonic pole, LPL: C to A♭m
+    H,
+}
+
+impl Transformation {
+    pub const ALL: [Self; 6] = [Self::P, Self::L, Self::R, Self::N, Self::S, Self::H];
+    const PLR: [Self; 3] = [Self::P, Self::L, Self::R];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Transformation::P => "P",
+            Transformation::L => "L",
+            Transformation::R => "R",
+            Transformation::N => "N",
+            Transformation::S => "S",
+            Transformation::H => "H",
+        }
+    }
+}
+
+/// Transformations applied from left to right
+#[derive(Clone, Copy)]
+pub struct Path {
+    len: usize,
+    steps: [Transformation; MAX_STEPS],
+}
+
+impl Path {
+    fn empty() -> Self {
+        Self {
+            len: 0,
+            steps: [Transformation::P; MAX_STEPS],
+        }
+    }
+
+    fn push(&mut self, transformation: Transformation) {
+        self.steps[self.len] = transformation;
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = Transformation> + '_ {
+        self.steps[..self.len].iter().copied()
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in self.steps() {
+            f.write_str(step.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+
+    use super::*;
+    use NoteName::*;
+    use Transformation::*;
+
+    #[test]
+    fn apply() {
+        let c = Triad::major(C);
+
+        assert_eq!(Triad::minor(C), c.apply(P));
+        assert_eq!(Triad::minor(E), c.apply(L));
+        assert_eq!(Triad::minor(A), c.apply(R));
+        assert_eq!(Triad::minor(F), c.apply(N));
+        assert_eq!(Triad::minor(Db), c.apply(S));
+        assert_eq!(Triad::minor(Ab), c.apply(H));
+
+        // compounds
+        assert_eq!(c.apply(R).apply(L).apply(P), c.apply(N));
+        assert_eq!(c.apply(L).apply(P).apply(R), c.apply(S));
+        assert_eq!(c.apply(L).apply(P).apply(L), c.apply(H));
+
+        for root in NoteName::CIRCLE_OF_FIFTHS {
+            for triad in [Triad::major(root), Triad::minor(root)] {
+                for transformation in Transformation::ALL {
+                    assert_eq!(triad, triad.apply(transformation).apply(transformation));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn from_names() {
+        let names = |names: &[NoteName]| names.iter().copied().collect::<NoteNames>();
+
+        assert_eq!(Some(Triad::major(C)), Triad::from_names(&names(&[G, C, E])));
+        assert_eq!(
+            Some(Triad::minor(Gb)),
+            Triad::from_names(&names(&[Db, A, Gb]))
+        );
+        assert_eq!(None, Triad::from_names(&names(&[C, E, Ab])));
+        assert_eq!(None, Triad::from_names(&names(&[C, E, G, B])));
+        assert_eq!(names(&[B, D, Gb]), Triad::minor(B).names());
+    }
+
+    #[test]
+    fn path() {
+        let path = |from: Triad, to: Triad| from.path(to).to_string();
+        let c = Triad::major(C);
+
+        assert_eq!("", path(c, c));
+        assert_eq!("N", path(Triad::minor(F), c));
+        // chromatic mediants
+        assert_eq!("LP", path(c, Triad::major(E)));
+        assert_eq!("PL", path(c, Triad::major(Ab)));
+        assert_eq!("RP", path(c, Triad::major(A)));
+        assert_eq!("PR", path(c, Triad::major(Eb)));
+
+        for root in NoteName::CIRCLE_OF_FIFTHS {
+            for to in [Triad::major(root), Triad::minor(root)] {
+                let path = c.path(to);
+                let end = path.steps().fold(c, |triad, step| triad.apply(step));
+                assert_eq!(to, end);
+            }
+        }
+
+        assert_eq!(4, c.path(Triad::major(Gb)).len());
+        // the farthest
+        assert_eq!(MAX_STEPS, c.path(Triad::minor(Bb)).len());
+    }
+}
@@ -2,6 +2,7 @@
 
 use core::fmt;
 
+use crate::spelled_pitch::SpelledPitch;
 use crate::{Error, NoteName, scale::Scale};
 
 #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
@@ -26,7 +27,9 @@ impl Note {
     pub const C3: Self = Note(48);
     pub const Eb3: Self = Note(51);
     pub const E3: Self = Note(52);
+    pub const Gb3: Self = Note(54);
     pub const G3: Self = Note(55);
+    pub const B3: Self = Note(59);
 
     pub const C4: Self = Note(60);
     pub const Db4: Self = Note(61);
@@ -91,9 +94,15 @@ impl Note {
     }
 
     pub fn display(&self, scale: Scale) -> impl fmt::Display {
+        self.display_as(self.name().spell(scale))
+    }
+
+    /// Like `display` but with the given spelling, e.g. B♯3 rather than C4. The octave is the
+    /// one of the letter
+    pub fn display_as(&self, spelling: SpelledPitch) -> impl fmt::Display {
         struct S {
             note: Note,
-            scale: Scale,
+            spelling: SpelledPitch,
         }
 
         impl fmt::Display for S {
@@ -101,13 +110,18 @@ impl Note {
                 write!(
                     f,
                     "{}{}",
-                    self.note.name().as_str(self.scale),
-                    self.note.octave()
+                    self.spelling,
+                    self.spelling.octave(self.note.as_u8())
                 )
             }
         }
 
-        S { note: *self, scale }
+        debug_assert!(self.name() == spelling.name());
+
+        S {
+            note: *self,
+            spelling,
+        }
     }
 }
 
@@ -1,4 +1,5 @@
-use crate::{Error, Interval, Note, ScaleType, scale::Scale};
+use crate::spelled_pitch::{Letter, SpelledPitch};
+use crate::{Error, Interval, MajorScale, Note, ScaleType, scale::Scale};
 
 #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(test, derive(Debug))]
@@ -121,7 +122,25 @@ impl NoteName {
     pub fn as_str(&self, scale: Scale) -> &'static str {
         use NoteName::*;
 
-        if matches!(scale.ty, ScaleType::HarmonicMinor) {
+        // spelled like the melodic minor scale they are a mode of
+        if let Some(offset) = scale.ty.melodic_minor_offset() {
+            let parent = Scale {
+                tonic: scale.tonic.step(offset),
+                ty: ScaleType::MelodicMinor,
+            };
+            return self.as_str(parent);
+        }
+
+        // spelled from the letter of the degree, e.g. E♯ for ♯4 over B but F for ♭5
+        if let ScaleType::Custom(custom) = &scale.ty {
+            let degree = MajorScale::new(scale.tonic).name2degree(*self);
+            let tonic = scale.tonic.spell(Scale::major(scale.tonic));
+            let letters = degree.letters(custom.is_respelled(degree));
+            return tonic.above(letters, degree as u8).as_str();
+        }
+
+        // the raised seventh is spelled with a sharp
+        if matches!(scale.ty, ScaleType::HarmonicMinor | ScaleType::MelodicMinor) {
             match (self, scale.tonic) {
                 (Gb, G) | (Db, D) | (Ab, A) | (Eb, E) | (Bb, B) => {
                     return self.as_sharp_str();
@@ -139,12 +158,30 @@ impl NoteName {
         }
 
         let offset = match scale.ty {
-            ScaleType::Major => 0,
+            ScaleType::Major
+            | ScaleType::HarmonicMajor
+            | ScaleType::MajorPentatonic
+            | ScaleType::WholeTone
+            | ScaleType::DiminishedHalfWhole
+            | ScaleType::DiminishedWholeHalf
+            | ScaleType::Custom(_) => 0,
             ScaleType::Dorian => 10,
             ScaleType::Phrygian => 8,
             ScaleType::Lydian => 7,
             ScaleType::Mixolydian => 5,
-            ScaleType::Minor | ScaleType::HarmonicMinor => 3,
+            ScaleType::Locrian => 1,
+            ScaleType::Minor
+            | ScaleType::HarmonicMinor
+            | ScaleType::MelodicMinor
+            | ScaleType::MinorPentatonic
+            | ScaleType::Blues => 3,
+            // handled above
+            ScaleType::DorianFlatTwo
+            | ScaleType::LydianAugmented
+            | ScaleType::LydianDominant
+            | ScaleType::MixolydianFlatSix
+            | ScaleType::LocrianNaturalTwo
+            | ScaleType::Altered => unreachable!(),
         };
         let relative_tonic = scale.tonic.step(offset);
         match (relative_tonic, self) {
@@ -159,11 +196,36 @@ impl NoteName {
             _ => self.as_flat_str(),
         }
     }
+
+    /// The letter and accidentals of `as_str`
+    pub fn spell(&self, scale: Scale) -> SpelledPitch {
+        let mut chars = self.as_str(scale).chars();
+
+        let letter = match chars.next() {
+            Some('C') => Letter::C,
+            Some('D') => Letter::D,
+            Some('E') => Letter::E,
+            Some('F') => Letter::F,
+            Some('G') => Letter::G,
+            Some('A') => Letter::A,
+            _ => Letter::B,
+        };
+        let accidentals = chars
+            .map(|accidental| match accidental {
+                '𝄫' => -2,
+                '♭' => -1,
+                '𝄪' => 2,
+                _ => 1,
+            })
+            .sum();
+
+        SpelledPitch::new(letter, accidentals)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Degree, MajorScale};
+    use crate::{CustomScale, Degree};
 
     use super::*;
 
@@ -239,6 +301,109 @@ mod tests {
         assert_eq!("A♭", stringify(Ab, Ab));
     }
 
+    #[test]
+    fn modes_of_melodic_minor() {
+        use NoteName::*;
+
+        let stringify =
+            |name: NoteName, tonic: NoteName, ty: ScaleType| name.as_str(Scale { tonic, ty });
+
+        // same notes as A♭ melodic minor
+        let altered = [G, Ab, Bb, B, Db, Eb, F]
+            .map(|name| stringify(name, G, ScaleType::Altered))
+            .join(" ");
+        assert_eq!("G A♭ B♭ C♭ D♭ E♭ F", altered);
+
+        assert_eq!("G♯", stringify(Ab, A, ScaleType::MelodicMinor));
+        assert_eq!("F♯", stringify(Gb, C, ScaleType::LydianDominant));
+        assert_eq!("B♭", stringify(Bb, C, ScaleType::LydianDominant));
+    }
+
+    #[test]
+    fn custom_scale() {
+        use NoteName::*;
+
+        let mut custom = CustomScale::from_mask("", 0b0101_1001_0001);
+        custom.set_respelled(Degree::FlatSix, true);
+        let scale = Scale {
+            tonic: C,
+            ty: ScaleType::Custom(custom),
+        };
+
+        assert_eq!("G♯", Ab.as_str(scale));
+        assert_eq!("F♯", Gb.as_str(scale));
+        assert_eq!("B♭", Bb.as_str(scale));
+
+        custom.set_respelled(Degree::SharpFour, true);
+        let scale = Scale {
+            tonic: C,
+            ty: ScaleType::Custom(custom),
+        };
+        assert_eq!("G♭", Gb.as_str(scale));
+
+        // respelled degrees that land on white keys
+        let mut custom = CustomScale::from_mask("", 0b0101_1101_0001);
+        let b = Scale {
+            tonic: B,
+            ty: ScaleType::Custom(custom),
+        };
+        assert_eq!("E♯", F.as_str(b));
+        custom.set_respelled(Degree::SharpFour, true);
+        let b = Scale {
+            tonic: B,
+            ty: ScaleType::Custom(custom),
+        };
+        assert_eq!("F", F.as_str(b));
+
+        custom.set_respelled(Degree::FlatSix, true);
+        let g_flat = Scale {
+            tonic: Gb,
+            ty: ScaleType::Custom(custom),
+        };
+        assert_eq!("D", D.as_str(g_flat));
+        assert_eq!(SpelledPitch::new(Letter::D, 0), D.spell(g_flat));
+        custom.set_respelled(Degree::FlatSix, false);
+        let g_flat = Scale {
+            tonic: Gb,
+            ty: ScaleType::Custom(custom),
+        };
+        assert_eq!("E𝄫", D.as_str(g_flat));
+        assert_eq!(SpelledPitch::new(Letter::E, -2), D.spell(g_flat));
+    }
+
+    #[test]
+    fn locrian() {
+        use NoteName::*;
+
+        let stringify = |name: NoteName| {
+            name.as_str(Scale {
+                tonic: B,
+                ty: ScaleType::Locrian,
+            })
+        };
+
+        assert_eq!("C", stringify(C));
+        assert_eq!("F", stringify(F));
+    }
+
+    #[test]
+    fn spell() {
+        for tonic in NoteName::CIRCLE_OF_FIFTHS {
+            for ty in [
+                ScaleType::Major,
+                ScaleType::HarmonicMinor,
+                ScaleType::Altered,
+            ] {
+                let scale = Scale { tonic, ty };
+                for name in NoteName::CIRCLE_OF_FIFTHS {
+                    let spelled = name.spell(scale);
+                    assert_eq!(name, spelled.name());
+                    assert_eq!(name.as_str(scale), spelled.to_string());
+                }
+            }
+        }
+    }
+
     #[test]
     fn with_octave() {
         // extremes of piano range
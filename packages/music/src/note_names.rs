@@ -1,8 +1,14 @@
+use core::fmt;
+
 use crate::NoteName;
+use crate::set_class::Forte;
 
 const SIZE: usize = 2;
+const ALL: u16 = 0xfff;
 
-#[derive(Clone)]
+/// A pitch-class set
+#[derive(Clone, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
 pub struct NoteNames {
     bits: [u8; SIZE],
 }
@@ -34,6 +40,125 @@ impl NoteNames {
         let pos = name as usize;
         self.bits[pos / 8] |= 1 << (pos % 8);
     }
+
+    /// Bit `n` is pitch class `n`, with C as 0
+    pub(crate) fn from_mask(mask: u16) -> Self {
+        Self {
+            bits: (mask & ALL).to_le_bytes(),
+        }
+    }
+
+    pub(crate) fn mask(&self) -> u16 {
+        u16::from_le_bytes(self.bits)
+    }
+
+    /// Tₙ, e.g. T₄ of {C, E, G} is {E, G♯, B}
+    pub fn transpose(&self, half_steps: u8) -> Self {
+        let n = u32::from(half_steps % 12);
+        let mask = self.mask();
+        Self::from_mask((mask << n) | (mask >> ((12 - n) % 12)))
+    }
+
+    /// I, the inversion around C: each pitch class `n` becomes `12 - n`. Tₙ of the inversion is
+    /// TₙI
+    pub fn invert(&self) -> Self {
+        self.iter()
+            .map(|name| NoteName::from_u8_lossy(12 - name as u8))
+            .collect()
+    }
+
+    /// The pitch classes that are not in the set
+    pub fn complement(&self) -> Self {
+        Self::from_mask(!self.mask())
+    }
+
+    /// The pitch classes in ascending order, starting from the one that packs them the most
+    /// tightly, e.g. B C E for {C, E, B}. Ties are broken like Rahn does: the interval from the
+    /// first to the second-to-last is compared and so on
+    pub fn normal_form(&self) -> impl Iterator<Item = NoteName> {
+        let first = self
+            .iter()
+            .min_by_key(|first| self.transpose(12 - *first as u8).mask())
+            .map_or(0, |first| first as u8);
+
+        let mask = self.mask();
+        (first..first + 12)
+            .filter(move |pc| mask & (1 << (pc % 12)) != 0)
+            .map(NoteName::from_u8_lossy)
+    }
+
+    /// The representative of the set class, transposed to start on C; the most packed of the
+    /// normal forms of the set and of its inversion, per Rahn
+    pub fn prime_form(&self) -> Self {
+        let inversion = self.invert();
+
+        (0..12)
+            .flat_map(|n| [self.transpose(n), inversion.transpose(n)])
+            .min_by_key(NoteNames::mask)
+            .unwrap_or_else(NoteNames::empty)
+    }
+
+    /// Whether both sets belong to the same set class, i.e. they are related by Tₙ or TₙI
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        self.prime_form() == other.prime_form()
+    }
+
+    /// How many times each interval class, from the minor second to the tritone, occurs
+    /// between the pitch classes of the set
+    pub fn interval_vector(&self) -> [u8; 6] {
+        let mut vector = [0; 6];
+        for (index, lower) in self.iter().enumerate() {
+            for upper in self.iter().skip(index + 1) {
+                let interval = upper as usize - lower as usize;
+                vector[interval.min(12 - interval) - 1] += 1;
+            }
+        }
+
+        vector
+    }
+
+    /// Z-related sets share their interval vector without being equivalent
+    pub fn is_z_related(&self, other: &Self) -> bool {
+        self.interval_vector() == other.interval_vector() && !self.is_equivalent(other)
+    }
+
+    /// The name Forte gave to the set class, e.g. 4-Z15
+    pub fn forte(&self) -> Option<Forte> {
+        Forte::of(self)
+    }
+
+    /// As integers, e.g. [0,4,7] for {C, E, G}, with `t` and `e` for 10 and 11
+    pub fn display_integers(&self) -> impl fmt::Display {
+        struct S(NoteNames);
+
+        impl fmt::Display for S {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                const DIGITS: [&str; 12] =
+                    ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "t", "e"];
+
+                f.write_str("[")?;
+                for (index, name) in self.0.iter().enumerate() {
+                    if index != 0 {
+                        f.write_str(",")?;
+                    }
+                    f.write_str(DIGITS[name as usize])?;
+                }
+                f.write_str("]")
+            }
+        }
+
+        S(self.clone())
+    }
+}
+
+impl FromIterator<NoteName> for NoteNames {
+    fn from_iter<I: IntoIterator<Item = NoteName>>(iter: I) -> Self {
+        let mut names = NoteNames::empty();
+        for name in iter {
+            names.insert(name);
+        }
+        names
+    }
 }
 
 impl IntoIterator for NoteNames {
@@ -67,3 +192,68 @@ impl Iterator for Iter {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    use super::*;
+    use NoteName::*;
+
+    fn set(names: &[NoteName]) -> NoteNames {
+        names.iter().copied().collect()
+    }
+
+    #[test]
+    fn transpose_and_invert() {
+        assert_eq!(set(&[E, Ab, B]), set(&[C, E, G]).transpose(4));
+        assert_eq!(set(&[Bb, C, D]), set(&[C, D, E]).transpose(10));
+        assert_eq!(set(&[C, Ab, F]), set(&[C, E, G]).invert());
+        assert_eq!(
+            set(&[D, Gb, A, C]),
+            set(&[D, Gb, A, C]).complement().complement()
+        );
+        assert_eq!(8, set(&[C, E, G, B]).complement().len());
+    }
+
+    #[test]
+    fn normal_form() {
+        let normal = |names: &[NoteName]| set(names).normal_form().collect::<Vec<_>>();
+
+        assert_eq!([B, C, E], *normal(&[C, E, B]));
+        assert_eq!([C, E, G], *normal(&[G, C, E]));
+        assert_eq!([C, Db, E, G], *normal(&[C, Db, E, G]));
+        // symmetric sets start on the lowest pitch class
+        assert_eq!([C, Eb, Gb, A], *normal(&[A, Eb, C, Gb]));
+    }
+
+    #[test]
+    fn prime_form() {
+        let prime = |names: &[NoteName]| set(names).prime_form().display_integers().to_string();
+
+        // major and minor triads are inversions of each other
+        assert_eq!("[0,3,7]", prime(&[C, E, G]));
+        assert_eq!("[0,3,7]", prime(&[A, C, E]));
+        assert_eq!("[0,1,5]", prime(&[C, E, B]));
+        assert_eq!("[0,2,5,8]", prime(&[G, B, D, F]));
+        assert_eq!("[0,1,4,6]", prime(&[C, Db, E, Gb]));
+        // 5-20; Forte's prime form would be [0,1,3,7,8]
+        assert_eq!("[0,1,5,6,8]", prime(&[C, Db, Eb, G, Ab]));
+        assert_eq!("[0,2,4,6,8,t]", prime(&[C, D, E, Gb, Ab, Bb]));
+    }
+
+    #[test]
+    fn interval_vector() {
+        assert_eq!([0, 0, 1, 1, 1, 0], set(&[C, E, G]).interval_vector());
+        assert_eq!([1, 1, 1, 1, 1, 1], set(&[C, Db, E, Gb]).interval_vector());
+        assert_eq!(
+            [2, 5, 4, 3, 6, 1],
+            set(&[C, D, E, F, G, A, B]).interval_vector()
+        );
+
+        // 4-Z15 and 4-Z29
+        assert!(set(&[C, Db, E, Gb]).is_z_related(&set(&[C, Db, Eb, G])));
+        assert!(!set(&[C, E, G]).is_z_related(&set(&[A, C, E])));
+    }
+}
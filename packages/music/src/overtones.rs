@@ -0,0 +1,187 @@
+//! Which partials of a note sound, and how loud, depending on the instrument
+//!
+//! The `n`th partial vibrates `n` times as fast as the fundamental, the first partial. It
+//! rarely falls on a key: the fifth partial is about 14 cents flatter than the major third
+//! two octaves up
+
+use crate::{Note, Tuning};
+
+/// Partials a custom envelope can shape, from the second one on
+pub const CUSTOM_LEN: usize = 16;
+
+/// How the power of the partials falls off, relative to the fundamental
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Envelope {
+    /// Every partial at `1/n`, like bowed strings and brass
+    Sawtooth,
+    /// Only the odd partials, at `1/n`, like the clarinet
+    Square,
+    /// The power of each partial from the second one on; the ones past the end are silent
+    Custom([f64; CUSTOM_LEN]),
+}
+
+impl Envelope {
+    /// A custom envelope, the powers starting at the second partial. Those past `CUSTOM_LEN`
+    /// are dropped
+    pub const fn from_powers(powers: &[f64]) -> Self {
+        let mut custom = [0.; CUSTOM_LEN];
+        let mut i = 0;
+        while i < powers.len() && i < CUSTOM_LEN {
+            custom[i] = powers[i];
+            i += 1;
+        }
+
+        Self::Custom(custom)
+    }
+
+    /// Power of the `n`th partial, the fundamental having 1
+    pub fn power(&self, n: u8) -> f64 {
+        match self {
+            _ if n <= 1 => 1.,
+            Envelope::Sawtooth => 1. / f64::from(n),
+            Envelope::Square if n % 2 == 0 => 0.,
+            Envelope::Square => 1. / f64::from(n),
+            Envelope::Custom(powers) => powers.get(usize::from(n) - 2).copied().unwrap_or(0.),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Partial {
+    /// 2 for the first overtone
+    pub n: u8,
+    /// The closest key
+    pub key: Note,
+    pub power: f64,
+    /// How far above `key`, as tuned, the partial sounds
+    pub cents: f64,
+}
+
+/// The partials above the fundamental
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct OvertoneSeries {
+    pub envelope: Envelope,
+    /// Number of overtones, i.e. partials above the fundamental
+    pub count: u8,
+}
+
+impl OvertoneSeries {
+    pub const fn new(envelope: Envelope, count: u8) -> Self {
+        Self { envelope, count }
+    }
+
+    /// The overtones of `fundamental` that can be heard, lowest first, their cents measured
+    /// from the keys as tuned. Those above the highest MIDI note are left out
+    pub fn partials<'a>(
+        &'a self,
+        fundamental: Note,
+        tuning: &'a Tuning,
+    ) -> impl Iterator<Item = Partial> + 'a {
+        (2..=self.count.saturating_add(1))
+            .map_while(move |n| {
+                let (key, cents) = tuning.partial(fundamental, n)?;
+                Some(Partial {
+                    n,
+                    key,
+                    power: self.envelope.power(n),
+                    cents,
+                })
+            })
+            .filter(|partial| partial.power > 0.)
+    }
+}
+
+impl Default for OvertoneSeries {
+    /// Nine overtones of a sawtooth, up to the major third three octaves up
+    fn default() -> Self {
+        Self::new(Envelope::Sawtooth, 9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::NoteName;
+    use crate::tuning::{STANDARD_A4, Temperament};
+
+    #[test]
+    fn sawtooth() {
+        let partials = OvertoneSeries::default()
+            .partials(Note::A4.step(-24).unwrap(), &Tuning::default())
+            .collect::<Vec<_>>();
+
+        let keys = partials
+            .iter()
+            .map(|partial| partial.key.as_u8() - 45)
+            .collect::<Vec<_>>();
+        assert_eq!([12, 19, 24, 28, 31, 34, 36, 38, 40], *keys);
+
+        assert_eq!(0.5, partials[0].power);
+        assert_eq!(0.1, partials[8].power);
+        // the minor seventh
+        assert!((partials[5].cents + 31.174).abs() < 0.01);
+    }
+
+    #[test]
+    fn square() {
+        let series = OvertoneSeries::new(Envelope::Square, 6);
+        let n = series
+            .partials(Note::C4, &Tuning::default())
+            .map(|partial| partial.n)
+            .collect::<Vec<_>>();
+
+        assert_eq!([3, 5, 7], *n);
+    }
+
+    #[test]
+    fn custom() {
+        let series = OvertoneSeries::new(Envelope::from_powers(&[0.25, 0.5]), 16);
+        let powers = series
+            .partials(Note::C4, &Tuning::default())
+            .map(|partial| partial.power)
+            .collect::<Vec<_>>();
+
+        assert_eq!([0.25, 0.5], *powers);
+        assert_eq!(1., Envelope::from_powers(&[]).power(1));
+
+        let long = Envelope::from_powers(&[0.5; CUSTOM_LEN + 4]);
+        assert_eq!(0.5, long.power(CUSTOM_LEN as u8 + 1));
+        assert_eq!(0., long.power(CUSTOM_LEN as u8 + 2));
+    }
+
+    #[test]
+    fn top_of_the_range() {
+        let series = OvertoneSeries::new(Envelope::Sawtooth, 255);
+        let equal = Tuning::default();
+        let last = series.partials(Note::C4, &equal).last().unwrap();
+
+        assert_eq!(127, last.key.as_u8());
+        assert!(
+            series
+                .partials(Note::from_u8_lossy(127), &equal)
+                .next()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn tuned() {
+        let series = OvertoneSeries::default();
+        let just = Tuning::new(STANDARD_A4, Temperament::Just, NoteName::C);
+        let cents = |tuning: &Tuning| {
+            series
+                .partials(Note::C4, tuning)
+                .map(|partial| partial.cents)
+                .collect::<Vec<_>>()
+        };
+
+        // the fifth partial is a pure major third two octaves up
+        assert!((cents(&Tuning::default())[3] + 13.686).abs() < 0.01);
+        assert!(cents(&just)[3].abs() < 0.01);
+    }
+}
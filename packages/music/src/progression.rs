@@ -0,0 +1,297 @@
+//! Common chord progressions and cadences
+//!
+//! Fed with the roman numerals of the chords as they are played, in a single key
+
+use crate::roman::{Quality, Seventh};
+use crate::{Degree, RomanNumeral};
+
+/// Longest pattern, in chords
+const LEN: usize = 4;
+/// Chords changing faster than this are the keys of a single chord going down one by one
+const MIN_DURATION_MS: f64 = 150.;
+/// A longer pause after the last chord stopped sounding starts a new phrase
+const MAX_GAP_MS: f64 = 8_000.;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Pattern {
+    /// ⅱ–Ⅴ–Ⅰ
+    TwoFiveOne,
+    /// Ⅰ–ⅵ–Ⅳ–Ⅴ
+    OneSixFourFive,
+    /// Ⅴ–Ⅰ
+    AuthenticCadence,
+    /// Ⅳ–Ⅰ
+    PlagalCadence,
+    /// ending on Ⅴ
+    HalfCadence,
+    /// Ⅴ–ⅵ
+    DeceptiveCadence,
+    /// a dominant seventh resolving down a half step to Ⅰ or to another dominant, e.g. ♭Ⅱ⁷–Ⅰ
+    TritoneSubstitution,
+    /// ♭Ⅶ⁷–Ⅰ
+    Backdoor,
+}
+
+impl Pattern {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Pattern::TwoFiveOne => "ⅱ–Ⅴ–Ⅰ",
+            Pattern::OneSixFourFive => "Ⅰ–ⅵ–Ⅳ–Ⅴ",
+            Pattern::AuthenticCadence => "authentic cadence",
+            Pattern::PlagalCadence => "plagal cadence",
+            Pattern::HalfCadence => "half cadence",
+            Pattern::DeceptiveCadence => "deceptive cadence",
+            Pattern::TritoneSubstitution => "tritone substitution",
+            Pattern::Backdoor => "backdoor progression",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    /// when the chord started sounding
+    ms: f64,
+    /// when the chord was last heard sounding
+    end_ms: f64,
+    numeral: RomanNumeral,
+}
+
+impl Entry {
+    fn is(&self, root: Degree, qualities: &[Quality]) -> bool {
+        self.numeral.root() == root && qualities.contains(&self.numeral.quality)
+    }
+
+    /// Major triad or dominant seventh
+    fn is_dominant(&self, root: Degree) -> bool {
+        self.is(root, &[Quality::Major])
+            && matches!(self.numeral.seventh, None | Some(Seventh::Minor))
+    }
+}
+
+/// Remembers the last few chords
+pub struct Progression {
+    /// oldest first
+    entries: [Option<Entry>; LEN],
+    pattern: Option<Pattern>,
+}
+
+impl Progression {
+    pub const fn new() -> Self {
+        Self {
+            entries: [None; LEN],
+            pattern: None,
+        }
+    }
+
+    /// Forgets the chords played so far, e.g. when the key changes
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// The last chords, oldest first
+    pub fn numerals(&self) -> impl Iterator<Item = RomanNumeral> + '_ {
+        self.entries.iter().flatten().map(|entry| entry.numeral)
+    }
+
+    /// The pattern that ends with the last chord
+    pub fn pattern(&self) -> Option<Pattern> {
+        self.pattern
+    }
+
+    /// `numeral` is the chord that started sounding at `ms`. Returns the pattern it completes
+    pub fn push(&mut self, numeral: RomanNumeral, ms: f64) -> Option<Pattern> {
+        let entry = Entry {
+            ms,
+            end_ms: ms,
+            numeral,
+        };
+
+        match self.last() {
+            Some(last) if ms - last.end_ms > MAX_GAP_MS => {
+                self.clear();
+                self.entries[LEN - 1] = Some(entry);
+            }
+
+            // e.g. adding the seventh or changing the inversion
+            Some(last)
+                if last.numeral.root() == numeral.root()
+                    && last.numeral.quality == numeral.quality =>
+            {
+                self.entries[LEN - 1] = Some(Entry {
+                    end_ms: ms,
+                    numeral,
+                    ..last
+                });
+                return None;
+            }
+
+            Some(last) if ms - last.ms < MIN_DURATION_MS => {
+                self.entries[LEN - 1] = Some(entry);
+            }
+
+            _ => {
+                self.entries.rotate_left(1);
+                self.entries[LEN - 1] = Some(entry);
+            }
+        }
+
+        self.pattern = self.detect();
+        self.pattern
+    }
+
+    /// The last chord still sounded at `ms`, e.g. some of its keys were just released
+    pub fn sound(&mut self, ms: f64) {
+        if let Some(last) = &mut self.entries[LEN - 1] {
+            last.end_ms = last.end_ms.max(ms);
+        }
+    }
+
+    fn last(&self) -> Option<Entry> {
+        self.entries[LEN - 1]
+    }
+
+    fn detect(&self) -> Option<Pattern> {
+        use Degree::*;
+        use Quality::*;
+
+        let [a, b, c, d] = self.entries;
+        let d = d?;
+
+        if let (Some(a), Some(b), Some(c)) = (a, b, c) {
+            if a.is(One, &[Major])
+                && b.is(Six, &[Minor])
+                && c.is(Four, &[Major])
+                && d.is_dominant(Five)
+            {
+                return Some(Pattern::OneSixFourFive);
+            }
+        }
+
+        let c = c?;
+
+        if let Some(b) = b {
+            if b.is(Two, &[Minor, HalfDiminished])
+                && c.is_dominant(Five)
+                && d.is(One, &[Major, Minor])
+            {
+                return Some(Pattern::TwoFiveOne);
+            }
+        }
+
+        let resolves = d.is(One, &[Major, Minor]);
+        // the chords a dominant seventh resolves to down a fifth, e.g. Ⅴ⁷/Ⅴ to Ⅴ
+        let is_target = resolves || d.numeral.applied_to.is_some() || d.is_dominant(Five);
+
+        if c.numeral.quality == Major
+            && c.numeral.seventh == Some(Seventh::Minor)
+            && c.numeral.root() == d.numeral.root().step(1)
+            && is_target
+        {
+            Some(Pattern::TritoneSubstitution)
+        } else if c.is_dominant(FlatSeven) && resolves {
+            Some(Pattern::Backdoor)
+        } else if c.is_dominant(Five) && resolves {
+            Some(Pattern::AuthenticCadence)
+        } else if c.is_dominant(Five) && (d.is(Six, &[Minor]) || d.is(FlatSix, &[Major])) {
+            Some(Pattern::DeceptiveCadence)
+        } else if c.is(Four, &[Major, Minor]) && resolves {
+            Some(Pattern::PlagalCadence)
+        } else if [One, Two, Four, Six].contains(&c.numeral.root()) && d.is_dominant(Five) {
+            Some(Pattern::HalfCadence)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Progression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChordSymbol, NoteName, Scale};
+
+    fn play(progression: &mut Progression, symbols: &[&str]) -> Option<Pattern> {
+        let scale = Scale::major(NoteName::C);
+
+        let mut pattern = None;
+        for s in symbols {
+            let symbol: ChordSymbol = s.parse().unwrap();
+            let numeral = RomanNumeral::analyze(symbol.tonic, &symbol.id, scale).unwrap();
+            let ms = progression.last().map_or(0., |last| last.ms + 1_000.);
+            pattern = progression.push(numeral, ms);
+        }
+
+        pattern
+    }
+
+    #[test]
+    fn patterns() {
+        for (symbols, expected) in [
+            (&["Dm7", "G7", "Cmaj7"][..], Some(Pattern::TwoFiveOne)),
+            (&["C", "Am", "F", "G"], Some(Pattern::OneSixFourFive)),
+            (&["G", "C"], Some(Pattern::AuthenticCadence)),
+            (&["F", "C"], Some(Pattern::PlagalCadence)),
+            (&["Fm", "C"], Some(Pattern::PlagalCadence)),
+            (&["F", "G"], Some(Pattern::HalfCadence)),
+            (&["G7", "Am"], Some(Pattern::DeceptiveCadence)),
+            (&["Db7", "C"], Some(Pattern::TritoneSubstitution)),
+            (&["Ab7", "G7"], Some(Pattern::TritoneSubstitution)),
+            (&["Eb7", "Dm7"], None),
+            (&["Bb7", "C"], Some(Pattern::Backdoor)),
+            (&["C", "Dm"], None),
+            (&["C"], None),
+        ] {
+            let mut progression = Progression::new();
+            assert_eq!(expected, play(&mut progression, symbols), "{symbols:?}");
+        }
+    }
+
+    #[test]
+    fn same_chord() {
+        let mut progression = Progression::new();
+
+        // adding the seventh does not make a new chord
+        assert_eq!(None, play(&mut progression, &["Dm", "Dm7", "G", "G7"]));
+        assert_eq!(2, progression.numerals().count());
+
+        assert_eq!(Some(Pattern::TwoFiveOne), play(&mut progression, &["C"]));
+        assert_eq!(Some(Pattern::TwoFiveOne), progression.pattern());
+    }
+
+    #[test]
+    fn timing() {
+        let scale = Scale::major(NoteName::C);
+        let numeral = |s: &str| {
+            let symbol: ChordSymbol = s.parse().unwrap();
+            RomanNumeral::analyze(symbol.tonic, &symbol.id, scale).unwrap()
+        };
+
+        // the keys of a chord are not pressed at exactly the same time
+        let mut progression = Progression::new();
+        progression.push(numeral("G"), 0.);
+        progression.push(numeral("Em"), 1_000.);
+        progression.push(numeral("C"), 1_050.);
+        assert_eq!(2, progression.numerals().count());
+        assert_eq!(Some(Pattern::AuthenticCadence), progression.pattern());
+
+        // a long pause starts over
+        assert_eq!(None, progression.push(numeral("G"), 20_000.));
+        assert_eq!(None, progression.push(numeral("C"), 40_000.));
+        assert_eq!(1, progression.numerals().count());
+
+        // the pause is measured from when the chord stopped sounding, e.g. after a fermata
+        let mut progression = Progression::new();
+        progression.push(numeral("G"), 0.);
+        progression.sound(9_000.);
+        assert_eq!(
+            Some(Pattern::AuthenticCadence),
+            progression.push(numeral("C"), 9_500.)
+        );
+    }
+}
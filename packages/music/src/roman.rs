@@ -0,0 +1,328 @@
+//! Roman numeral analysis
+//!
+//! Numerals are relative to the major scale of the tonic, e.g. ♭Ⅲ in a minor key, like
+//! `Degree::roman_major` and `Degree::roman_minor`
+
+use core::fmt;
+
+use crate::chord_id::{Alteration, ChordId, ChordKind};
+use crate::{Degree, MajorScale, NoteName, Scale, ScaleType};
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct RomanNumeral {
+    /// Relative to the tonic of the key or, for applied chords, to the tonicized degree
+    pub degree: Degree,
+    pub quality: Quality,
+    pub seventh: Option<Seventh>,
+    pub inversion: Inversion,
+    /// The degree an applied chord tonicizes, e.g. Ⅴ in Ⅴ⁷/Ⅴ
+    pub applied_to: Option<Target>,
+    /// Belongs to the parallel key, e.g. ♭Ⅵ or ⅳ in a major key
+    pub is_borrowed: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Quality {
+    Major,
+    Minor,
+    Diminished,
+    HalfDiminished,
+    Augmented,
+}
+
+/// The interval from the root to the seventh
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Seventh {
+    Diminished,
+    Minor,
+    Major,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Inversion {
+    Root,
+    First,
+    Second,
+    /// Only seventh chords
+    Third,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct Target {
+    pub degree: Degree,
+    pub is_minor: bool,
+}
+
+impl RomanNumeral {
+    /// Degree of the root relative to the tonic of the key, even for applied chords
+    pub fn root(&self) -> Degree {
+        match self.applied_to {
+            Some(Target { degree, .. }) => degree.step(self.degree as isize),
+            None => self.degree,
+        }
+    }
+
+    /// `None` for the chords roman numerals do not describe, e.g. suspended chords, or when
+    /// the bass is not a chord tone
+    pub fn analyze(tonic: NoteName, id: &ChordId, scale: Scale) -> Option<Self> {
+        use ChordKind as K;
+
+        let (quality, seventh) = match id.kind {
+            K::Major => (Quality::Major, None),
+            K::Minor => (Quality::Minor, None),
+            K::Diminished => (Quality::Diminished, None),
+            K::Augmented => (Quality::Augmented, None),
+            K::Seventh(Alteration::None) => (Quality::Major, Some(Seventh::Minor)),
+            K::Seventh(Alteration::SharpFive) => (Quality::Augmented, Some(Seventh::Minor)),
+            K::MajorSeventh(Alteration::None) => (Quality::Major, Some(Seventh::Major)),
+            K::MajorSeventh(Alteration::SharpFive) => (Quality::Augmented, Some(Seventh::Major)),
+            K::MinorSeventh(Alteration::None) => (Quality::Minor, Some(Seventh::Minor)),
+            K::MinorSeventh(Alteration::FlatFive) => {
+                (Quality::HalfDiminished, Some(Seventh::Minor))
+            }
+            K::MinorMajorSeventh(Alteration::None) => (Quality::Minor, Some(Seventh::Major)),
+            K::DiminishedSeventh => (Quality::Diminished, Some(Seventh::Diminished)),
+            _ => return None,
+        };
+
+        // which chord tone, counting from the root, is in the bass
+        let inversion = match id.bass {
+            None => Inversion::Root,
+            Some(bass) => {
+                let half_steps = (12 + bass as u8 - tonic as u8) % 12;
+                match id
+                    .kind
+                    .intervals()
+                    .iter()
+                    .position(|tone| *tone == half_steps)
+                {
+                    Some(1) => Inversion::First,
+                    Some(2) => Inversion::Second,
+                    Some(3) => Inversion::Third,
+                    _ => return None,
+                }
+            }
+        };
+
+        let degree = MajorScale::new(scale.tonic).name2degree(tonic);
+        let mut numeral = Self {
+            degree,
+            quality,
+            seventh,
+            inversion,
+            applied_to: None,
+            is_borrowed: false,
+        };
+
+        let intervals = id.kind.intervals();
+        if is_diatonic(degree, intervals, scale.ty) {
+            return Some(numeral);
+        }
+
+        // secondary dominants and leading-tone chords
+        let (applied, target) = match (quality, seventh) {
+            (Quality::Major, None | Some(Seventh::Minor)) => (Degree::Five, degree.step(-7)),
+            (Quality::Diminished | Quality::HalfDiminished, _) => (Degree::Seven, degree.step(1)),
+            _ => (degree, Degree::One),
+        };
+
+        // the tonic is not tonicized; its dominant is just Ⅴ. A major tonic triad in minor is
+        // rather borrowed than the dominant of ⅳ
+        if target != Degree::One && (degree != Degree::One || seventh.is_some()) {
+            let is_major = scale.ty.contains(target, false);
+            let is_minor = scale.ty.contains(target, true);
+            // diminished triads are not tonicized
+            let is_stable = target.belongs_to(scale.ty)
+                && target.step(7).belongs_to(scale.ty)
+                && (is_major || is_minor);
+
+            if is_stable {
+                numeral.degree = applied;
+                numeral.applied_to = Some(Target {
+                    degree: target,
+                    is_minor: !is_major,
+                });
+
+                return Some(numeral);
+            }
+        }
+
+        let parallel = if scale.ty.degrees().contains(Degree::Three) {
+            ScaleType::Minor
+        } else {
+            ScaleType::Major
+        };
+        numeral.is_borrowed = is_diatonic(degree, intervals, parallel);
+
+        Some(numeral)
+    }
+}
+
+/// Whether all the chord tones belong to the scale. In minor, the raised sixth and seventh
+/// degrees are diatonic too
+fn is_diatonic(root: Degree, intervals: &[u8], ty: ScaleType) -> bool {
+    let belongs_to = |ty: ScaleType| {
+        intervals
+            .iter()
+            .all(|interval| root.step(*interval as isize).belongs_to(ty))
+    };
+
+    if ty == ScaleType::Minor {
+        [
+            ScaleType::Minor,
+            ScaleType::HarmonicMinor,
+            ScaleType::MelodicMinor,
+        ]
+        .into_iter()
+        .any(belongs_to)
+    } else {
+        belongs_to(ty)
+    }
+}
+
+impl fmt::Display for RomanNumeral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (numeral, symbol) = match self.quality {
+            Quality::Major => (self.degree.roman_major(), ""),
+            Quality::Minor => (self.degree.roman_minor(), ""),
+            Quality::Diminished => (self.degree.roman_minor(), "°"),
+            Quality::HalfDiminished => (self.degree.roman_minor(), "ø"),
+            Quality::Augmented => (self.degree.roman_major(), "+"),
+        };
+
+        f.write_str(numeral)?;
+        f.write_str(symbol)?;
+
+        if self.seventh == Some(Seventh::Major) {
+            f.write_str("Δ")?;
+        }
+
+        let figures = match (self.seventh.is_some(), self.inversion) {
+            (false, Inversion::Root) => "",
+            (false, Inversion::First) => "⁶",
+            (false, Inversion::Second | Inversion::Third) => "⁶₄",
+            (true, Inversion::Root) => "⁷",
+            (true, Inversion::First) => "⁶₅",
+            (true, Inversion::Second) => "⁴₃",
+            (true, Inversion::Third) => "⁴₂",
+        };
+        f.write_str(figures)?;
+
+        if let Some(Target { degree, is_minor }) = self.applied_to {
+            f.write_str("/")?;
+            f.write_str(if is_minor {
+                degree.roman_minor()
+            } else {
+                degree.roman_major()
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::{String, ToString};
+
+    use super::*;
+    use crate::ChordSymbol;
+
+    fn analyze(s: &str, scale: Scale) -> Option<RomanNumeral> {
+        let symbol: ChordSymbol = s.parse().unwrap();
+        RomanNumeral::analyze(symbol.tonic, &symbol.id, scale)
+    }
+
+    fn display(s: &str, scale: Scale) -> String {
+        analyze(s, scale).unwrap().to_string()
+    }
+
+    #[test]
+    fn diatonic() {
+        let c_major = Scale::major(NoteName::C);
+
+        assert_eq!("Ⅰ", display("C", c_major));
+        assert_eq!("ⅱ⁷", display("Dm7", c_major));
+        assert_eq!("ⅣΔ⁷", display("Fmaj7", c_major));
+        assert_eq!("ⅶø⁷", display("Bm7b5", c_major));
+        assert_eq!("ⅶ°", display("Bdim", c_major));
+
+        let a_minor = Scale::minor(NoteName::A);
+        assert_eq!("ⅰ", display("Am", a_minor));
+        assert_eq!("♭Ⅲ", display("C", a_minor));
+        // harmonic minor
+        assert_eq!("Ⅴ⁷", display("E7", a_minor));
+        assert!(!analyze("E7", a_minor).unwrap().is_borrowed);
+    }
+
+    #[test]
+    fn inversions() {
+        let c_major = Scale::major(NoteName::C);
+
+        assert_eq!("Ⅰ⁶", display("C/E", c_major));
+        assert_eq!("Ⅰ⁶₄", display("C/G", c_major));
+        assert_eq!("Ⅴ⁷", display("G7", c_major));
+        assert_eq!("Ⅴ⁶₅", display("G7/B", c_major));
+        assert_eq!("Ⅴ⁴₃", display("G7/D", c_major));
+        assert_eq!("Ⅴ⁴₂", display("G7/F", c_major));
+
+        // not a chord tone
+        assert_eq!(None, analyze("C/D", c_major));
+        assert_eq!(None, analyze("C/F#", c_major));
+        assert_eq!(None, analyze("G7/F#", c_major));
+        assert_eq!(None, analyze("C/Eb", c_major));
+        assert_eq!(None, analyze("Cm/E", c_major));
+    }
+
+    #[test]
+    fn applied() {
+        let c_major = Scale::major(NoteName::C);
+
+        assert_eq!("Ⅴ⁷/Ⅴ", display("D7", c_major));
+        assert_eq!("Ⅴ/ⅵ", display("E", c_major));
+        assert_eq!("Ⅴ⁶₅/ⅱ", display("A7/C#", c_major));
+        assert_eq!("ⅶ°/ⅱ", display("C#dim", c_major));
+        assert_eq!("ⅶ°⁷/Ⅴ", display("F#dim7", c_major));
+        assert_eq!(
+            Degree::SharpFour,
+            analyze("F#dim7", c_major).unwrap().root()
+        );
+
+        // Ⅰ⁷ tonicizes Ⅳ
+        assert_eq!("Ⅴ⁷/Ⅳ", display("C7", c_major));
+    }
+
+    #[test]
+    fn borrowed() {
+        let c_major = Scale::major(NoteName::C);
+
+        for (s, expected) in [("Ab", "♭Ⅵ"), ("Fm", "ⅳ"), ("Bb", "♭Ⅶ"), ("Eb", "♭Ⅲ")] {
+            let numeral = analyze(s, c_major).unwrap();
+            assert!(numeral.is_borrowed, "{s}");
+            assert_eq!(expected, numeral.to_string());
+        }
+
+        // Picardy third
+        let numeral = analyze("A", Scale::minor(NoteName::A)).unwrap();
+        assert!(numeral.is_borrowed);
+        assert_eq!("Ⅰ", numeral.to_string());
+
+        // chromatic, but not from the parallel key
+        let numeral = analyze("F#", c_major).unwrap();
+        assert!(!numeral.is_borrowed);
+        assert_eq!(None, numeral.applied_to);
+    }
+
+    #[test]
+    fn unsupported() {
+        let c_major = Scale::major(NoteName::C);
+
+        assert_eq!(None, analyze("Csus4", c_major));
+        assert_eq!(None, analyze("C5", c_major));
+    }
+}
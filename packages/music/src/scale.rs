@@ -1,6 +1,7 @@
 use crate::{NoteName, ScaleType};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
 pub struct Scale {
     pub tonic: NoteName,
     pub ty: ScaleType,
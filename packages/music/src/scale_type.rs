@@ -1,6 +1,8 @@
 use crate::degree::Degree;
+use crate::{CustomScale, Degrees};
 
 #[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
 pub enum ScaleType {
     Major,
     Dorian,
@@ -8,7 +10,23 @@ pub enum ScaleType {
     Lydian,
     Mixolydian,
     Minor,
+    Locrian,
     HarmonicMinor,
+    HarmonicMajor,
+    MelodicMinor,
+    DorianFlatTwo,
+    LydianAugmented,
+    LydianDominant,
+    MixolydianFlatSix,
+    LocrianNaturalTwo,
+    Altered,
+    MajorPentatonic,
+    MinorPentatonic,
+    Blues,
+    WholeTone,
+    DiminishedHalfWhole,
+    DiminishedWholeHalf,
+    Custom(CustomScale),
 }
 
 impl ScaleType {
@@ -19,10 +37,25 @@ impl ScaleType {
         Self::Lydian,
         Self::Mixolydian,
         Self::Minor,
+        Self::Locrian,
         Self::HarmonicMinor,
+        Self::HarmonicMajor,
+        Self::MelodicMinor,
+        Self::DorianFlatTwo,
+        Self::LydianAugmented,
+        Self::LydianDominant,
+        Self::MixolydianFlatSix,
+        Self::LocrianNaturalTwo,
+        Self::Altered,
+        Self::MajorPentatonic,
+        Self::MinorPentatonic,
+        Self::Blues,
+        Self::WholeTone,
+        Self::DiminishedHalfWhole,
+        Self::DiminishedWholeHalf,
     ];
 
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         use ScaleType::*;
 
         match self {
@@ -32,38 +65,153 @@ impl ScaleType {
             Lydian => "Lydian",
             Mixolydian => "Mixolydian",
             Minor => "Minor",
+            Locrian => "Locrian",
             HarmonicMinor => "Harmonic Minor",
+            HarmonicMajor => "Harmonic Major",
+            MelodicMinor => "Melodic Minor",
+            DorianFlatTwo => "Dorian ♭2",
+            LydianAugmented => "Lydian Augmented",
+            LydianDominant => "Lydian Dominant",
+            MixolydianFlatSix => "Mixolydian ♭6",
+            LocrianNaturalTwo => "Locrian ♮2",
+            Altered => "Altered",
+            MajorPentatonic => "Major Pentatonic",
+            MinorPentatonic => "Minor Pentatonic",
+            Blues => "Blues",
+            WholeTone => "Whole Tone",
+            DiminishedHalfWhole => "Diminished (half-whole)",
+            DiminishedWholeHalf => "Diminished (whole-half)",
+            Custom(custom) => custom.name(),
         }
     }
 
-    pub fn degrees(&self) -> &'static [Degree] {
+    /// Not all scales have 7 degrees
+    pub fn degrees(&self) -> Degrees {
         use ScaleType::*;
 
-        match self {
+        let degrees: &[Degree] = match self {
             Major => &Degree::IONIAN,
             Dorian => &Degree::DORIAN,
             Phrygian => &Degree::PHRYGIAN,
             Lydian => &Degree::LYDIAN,
             Mixolydian => &Degree::MIXOLYDIAN,
             Minor => &Degree::AEOLIAN,
+            Locrian => &Degree::LOCRIAN,
             HarmonicMinor => &Degree::HARMONIC_MINOR,
+            HarmonicMajor => &Degree::HARMONIC_MAJOR,
+            MelodicMinor => &Degree::MELODIC_MINOR,
+            DorianFlatTwo => &Degree::DORIAN_FLAT_TWO,
+            LydianAugmented => &Degree::LYDIAN_AUGMENTED,
+            LydianDominant => &Degree::LYDIAN_DOMINANT,
+            MixolydianFlatSix => &Degree::MIXOLYDIAN_FLAT_SIX,
+            LocrianNaturalTwo => &Degree::LOCRIAN_NATURAL_TWO,
+            Altered => &Degree::ALTERED,
+            MajorPentatonic => &Degree::MAJOR_PENTATONIC,
+            MinorPentatonic => &Degree::MINOR_PENTATONIC,
+            Blues => &Degree::BLUES,
+            WholeTone => &Degree::WHOLE_TONE,
+            DiminishedHalfWhole => &Degree::DIMINISHED_HALF_WHOLE,
+            DiminishedWholeHalf => &Degree::DIMINISHED_WHOLE_HALF,
+            Custom(custom) => return custom.degrees(),
+        };
+
+        degrees.iter().copied().collect()
+    }
+
+    /// Custom scales may spell some degrees differently, e.g. "♯5" instead of "♭6"
+    pub fn degree_str(&self, degree: Degree) -> &'static str {
+        match self {
+            ScaleType::Custom(custom) => custom.degree_str(degree),
+            _ => degree.as_str(),
+        }
+    }
+
+    /// The melodic minor scale these are modes of and the number of half steps from the tonic of
+    /// the mode to the tonic of that scale
+    pub(crate) fn melodic_minor_offset(&self) -> Option<u8> {
+        use ScaleType::*;
+
+        match self {
+            DorianFlatTwo => Some(10),
+            LydianAugmented => Some(9),
+            LydianDominant => Some(7),
+            MixolydianFlatSix => Some(5),
+            LocrianNaturalTwo => Some(3),
+            Altered => Some(1),
+            _ => None,
         }
     }
 
+    /// Whether the chord rooted at `chord_tonic` has its root and its third in the scale. The
+    /// diminished chords count as minor and the augmented ones as major
     pub fn contains(&self, chord_tonic: Degree, is_minor_chord: bool) -> bool {
+        let third = chord_tonic.step(if is_minor_chord { 3 } else { 4 });
+
+        chord_tonic.belongs_to(*self) && third.belongs_to(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn num_degrees() {
+        for ty in ScaleType::ALL {
+            let degrees = ty.degrees();
+
+            assert!(degrees.contains(Degree::One), "{ty:?}");
+            assert!((5..=8).contains(&degrees.len()), "{ty:?}");
+        }
+    }
+
+    #[test]
+    fn modes_of_melodic_minor() {
+        for ty in ScaleType::ALL {
+            let Some(offset) = ty.melodic_minor_offset() else {
+                continue;
+            };
+
+            let degrees = ScaleType::MelodicMinor
+                .degrees()
+                .iter()
+                .map(|degree| degree.step(offset.into()))
+                .collect::<Degrees>();
+
+            assert_eq!(ty.degrees(), degrees, "{ty:?}");
+        }
+    }
+
+    #[test]
+    fn contains() {
         use Degree::*;
         use ScaleType::*;
 
-        matches!(
-            (self, chord_tonic, is_minor_chord),
-            (Major, One | Four | Five, false)
-                | (Major, Two | Three | Six | Seven, true)
-                | (Dorian, FlatThree | Five | FlatSeven, false)
-                | (Phrygian, FlatTwo | FlatThree | FlatSix, false)
-                | (Lydian, One | Two | Five, false)
-                | (Mixolydian, One | Four | FlatSeven, false)
-                | (Minor, FlatThree | FlatSix | FlatSeven, false)
-                | (HarmonicMinor, FlatThree | Five, false)
-        )
+        // Ⅰ Ⅳ Ⅴ; ⅱ ⅲ ⅵ ⅶ°
+        for degree in Major.degrees() {
+            let is_major = matches!(degree, One | Four | Five);
+            assert_eq!(is_major, Major.contains(degree, false));
+            assert_eq!(!is_major, Major.contains(degree, true));
+        }
+
+        // ⅰ ⅱ ♭Ⅲ Ⅳ ⅴ ⅵ° ♭Ⅶ
+        assert!(Dorian.contains(Four, false));
+        assert!(Dorian.contains(Five, true));
+
+        // ♭Ⅲ+
+        assert!(HarmonicMinor.contains(FlatThree, false));
+        assert!(HarmonicMinor.contains(FlatSix, false));
+
+        assert!(MajorPentatonic.contains(One, false));
+        assert!(MajorPentatonic.contains(Six, true));
+        assert!(!MajorPentatonic.contains(Two, false));
+        assert!(!MajorPentatonic.contains(Two, true));
+
+        assert!(!Major.contains(FlatTwo, false));
+
+        let bhairav = Custom(CustomScale::from_mask("Bhairav", 0b1001_1011_0011));
+        assert!(bhairav.contains(One, false));
+        assert!(bhairav.contains(FlatSix, false));
+        assert!(!bhairav.contains(Two, true));
     }
 }
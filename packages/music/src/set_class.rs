@@ -0,0 +1,287 @@
+//! Forte's names of the set classes, e.g. 3-11 for major and minor triads
+//!
+//! Forte lists the set classes of 3 to 9 pitch classes. A set class and the set class of its
+//! complement get the same ordinal, e.g. 7-35 is the complement of 5-35, so only the classes of
+//! up to 6 pitch classes are tabulated here. Dyads and their complements are numbered by their
+//! interval class. The "Z" marks classes that share their interval vector with another class
+
+use core::fmt;
+
+use crate::NoteNames;
+
+/// Set classes of 2 to 6 pitch classes, in Forte's order. Forte's prime forms where they differ
+/// from Rahn's; what matters is that each entry belongs to its class
+const SET_CLASSES: [&[u16]; 5] = [
+    &[
+        pcs(&[0, 1]),
+        pcs(&[0, 2]),
+        pcs(&[0, 3]),
+        pcs(&[0, 4]),
+        pcs(&[0, 5]),
+        pcs(&[0, 6]),
+    ],
+    &[
+        pcs(&[0, 1, 2]),
+        pcs(&[0, 1, 3]),
+        pcs(&[0, 1, 4]),
+        pcs(&[0, 1, 5]),
+        pcs(&[0, 1, 6]),
+        pcs(&[0, 2, 4]),
+        pcs(&[0, 2, 5]),
+        pcs(&[0, 2, 6]),
+        pcs(&[0, 2, 7]),
+        pcs(&[0, 3, 6]),
+        pcs(&[0, 3, 7]),
+        pcs(&[0, 4, 8]),
+    ],
+    &[
+        pcs(&[0, 1, 2, 3]),
+        pcs(&[0, 1, 2, 4]),
+        pcs(&[0, 1, 3, 4]),
+        pcs(&[0, 1, 2, 5]),
+        pcs(&[0, 1, 2, 6]),
+        pcs(&[0, 1, 2, 7]),
+        pcs(&[0, 1, 4, 5]),
+        pcs(&[0, 1, 5, 6]),
+        pcs(&[0, 1, 6, 7]),
+        pcs(&[0, 2, 3, 5]),
+        pcs(&[0, 1, 3, 5]),
+        pcs(&[0, 2, 3, 6]),
+        pcs(&[0, 1, 3, 6]),
+        pcs(&[0, 2, 3, 7]),
+        pcs(&[0, 1, 4, 6]),
+        pcs(&[0, 1, 5, 7]),
+        pcs(&[0, 3, 4, 7]),
+        pcs(&[0, 1, 4, 7]),
+        pcs(&[0, 1, 4, 8]),
+        pcs(&[0, 1, 5, 8]),
+        pcs(&[0, 2, 4, 6]),
+        pcs(&[0, 2, 4, 7]),
+        pcs(&[0, 2, 5, 7]),
+        pcs(&[0, 2, 4, 8]),
+        pcs(&[0, 2, 6, 8]),
+        pcs(&[0, 3, 5, 8]),
+        pcs(&[0, 2, 5, 8]),
+        pcs(&[0, 3, 6, 9]),
+        pcs(&[0, 1, 3, 7]),
+    ],
+    &[
+        pcs(&[0, 1, 2, 3, 4]),
+        pcs(&[0, 1, 2, 3, 5]),
+        pcs(&[0, 1, 2, 4, 5]),
+        pcs(&[0, 1, 2, 3, 6]),
+        pcs(&[0, 1, 2, 3, 7]),
+        pcs(&[0, 1, 2, 5, 6]),
+        pcs(&[0, 1, 2, 6, 7]),
+        pcs(&[0, 2, 3, 4, 6]),
+        pcs(&[0, 1, 2, 4, 6]),
+        pcs(&[0, 1, 3, 4, 6]),
+        pcs(&[0, 2, 3, 4, 7]),
+        pcs(&[0, 1, 3, 5, 6]),
+        pcs(&[0, 1, 2, 4, 8]),
+        pcs(&[0, 1, 2, 5, 7]),
+        pcs(&[0, 1, 2, 6, 8]),
+        pcs(&[0, 1, 3, 4, 7]),
+        pcs(&[0, 1, 3, 4, 8]),
+        pcs(&[0, 1, 4, 5, 7]),
+        pcs(&[0, 1, 3, 6, 7]),
+        pcs(&[0, 1, 3, 7, 8]),
+        pcs(&[0, 1, 4, 5, 8]),
+        pcs(&[0, 1, 4, 7, 8]),
+        pcs(&[0, 2, 3, 5, 7]),
+        pcs(&[0, 1, 3, 5, 7]),
+        pcs(&[0, 2, 3, 5, 8]),
+        pcs(&[0, 2, 4, 5, 8]),
+        pcs(&[0, 1, 3, 5, 8]),
+        pcs(&[0, 2, 3, 6, 8]),
+        pcs(&[0, 1, 3, 6, 8]),
+        pcs(&[0, 1, 4, 6, 8]),
+        pcs(&[0, 1, 3, 6, 9]),
+        pcs(&[0, 1, 4, 6, 9]),
+        pcs(&[0, 2, 4, 6, 8]),
+        pcs(&[0, 2, 4, 6, 9]),
+        pcs(&[0, 2, 4, 7, 9]),
+        pcs(&[0, 1, 2, 4, 7]),
+        pcs(&[0, 3, 4, 5, 8]),
+        pcs(&[0, 1, 2, 5, 8]),
+    ],
+    &[
+        pcs(&[0, 1, 2, 3, 4, 5]),
+        pcs(&[0, 1, 2, 3, 4, 6]),
+        pcs(&[0, 1, 2, 3, 5, 6]),
+        pcs(&[0, 1, 2, 4, 5, 6]),
+        pcs(&[0, 1, 2, 3, 6, 7]),
+        pcs(&[0, 1, 2, 5, 6, 7]),
+        pcs(&[0, 1, 2, 6, 7, 8]),
+        pcs(&[0, 2, 3, 4, 5, 7]),
+        pcs(&[0, 1, 2, 3, 5, 7]),
+        pcs(&[0, 1, 3, 4, 5, 7]),
+        pcs(&[0, 1, 2, 4, 5, 7]),
+        pcs(&[0, 1, 2, 4, 6, 7]),
+        pcs(&[0, 1, 3, 4, 6, 7]),
+        pcs(&[0, 1, 3, 4, 5, 8]),
+        pcs(&[0, 1, 2, 4, 5, 8]),
+        pcs(&[0, 1, 4, 5, 6, 8]),
+        pcs(&[0, 1, 2, 4, 7, 8]),
+        pcs(&[0, 1, 2, 5, 7, 8]),
+        pcs(&[0, 1, 3, 4, 7, 8]),
+        pcs(&[0, 1, 4, 5, 8, 9]),
+        pcs(&[0, 2, 3, 4, 6, 8]),
+        pcs(&[0, 1, 2, 4, 6, 8]),
+        pcs(&[0, 2, 3, 5, 6, 8]),
+        pcs(&[0, 1, 3, 4, 6, 8]),
+        pcs(&[0, 1, 3, 5, 6, 8]),
+        pcs(&[0, 1, 3, 5, 7, 8]),
+        pcs(&[0, 1, 3, 4, 6, 9]),
+        pcs(&[0, 1, 3, 5, 6, 9]),
+        pcs(&[0, 1, 3, 6, 8, 9]),
+        pcs(&[0, 1, 3, 6, 7, 9]),
+        pcs(&[0, 1, 3, 5, 8, 9]),
+        pcs(&[0, 2, 4, 5, 7, 9]),
+        pcs(&[0, 2, 3, 5, 7, 9]),
+        pcs(&[0, 1, 3, 5, 7, 9]),
+        pcs(&[0, 2, 4, 6, 8, 10]),
+        pcs(&[0, 1, 2, 3, 4, 7]),
+        pcs(&[0, 1, 2, 3, 4, 8]),
+        pcs(&[0, 1, 2, 3, 7, 8]),
+        pcs(&[0, 2, 3, 4, 5, 8]),
+        pcs(&[0, 1, 2, 3, 5, 8]),
+        pcs(&[0, 1, 2, 3, 6, 8]),
+        pcs(&[0, 1, 2, 3, 6, 9]),
+        pcs(&[0, 1, 2, 5, 6, 8]),
+        pcs(&[0, 1, 2, 5, 6, 9]),
+        pcs(&[0, 2, 3, 4, 6, 9]),
+        pcs(&[0, 1, 2, 4, 6, 9]),
+        pcs(&[0, 1, 2, 4, 7, 9]),
+        pcs(&[0, 1, 2, 5, 7, 9]),
+        pcs(&[0, 1, 3, 4, 7, 9]),
+        pcs(&[0, 1, 4, 6, 7, 9]),
+    ],
+];
+
+/// e.g. 4-Z15
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Forte {
+    pub cardinality: u8,
+    pub ordinal: u8,
+    pub is_z: bool,
+}
+
+impl Forte {
+    /// `None` for sets of fewer than 2 or more than 10 pitch classes
+    pub fn of(names: &NoteNames) -> Option<Self> {
+        let cardinality = names.len();
+        let (table, prime) = if cardinality <= 6 {
+            (
+                SET_CLASSES.get(cardinality.checked_sub(2)?)?,
+                names.prime_form(),
+            )
+        } else {
+            (
+                SET_CLASSES.get(10_usize.checked_sub(cardinality)?)?,
+                names.complement().prime_form(),
+            )
+        };
+
+        let index = table
+            .iter()
+            .position(|mask| NoteNames::from_mask(*mask).prime_form() == prime)?;
+
+        // complements of Z-related sets are Z-related too
+        let vector = prime.interval_vector();
+        let is_z = table.iter().enumerate().any(|(other, mask)| {
+            other != index && NoteNames::from_mask(*mask).interval_vector() == vector
+        });
+
+        Some(Self {
+            cardinality: cardinality as u8,
+            ordinal: index as u8 + 1,
+            is_z,
+        })
+    }
+}
+
+impl fmt::Display for Forte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let z = if self.is_z { "Z" } else { "" };
+        write!(f, "{}-{}{}", self.cardinality, z, self.ordinal)
+    }
+}
+
+const fn pcs(pitch_classes: &[u8]) -> u16 {
+    let mut mask = 0;
+    let mut index = 0;
+    while index < pitch_classes.len() {
+        mask |= 1 << pitch_classes[index];
+        index += 1;
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::string::ToString;
+
+    use super::*;
+    use crate::NoteName;
+
+    fn forte(names: &[NoteName]) -> String {
+        let names: NoteNames = names.iter().copied().collect();
+        names.forte().unwrap().to_string()
+    }
+
+    #[test]
+    fn table() {
+        // every set class is listed once
+        for (table, expected) in SET_CLASSES.iter().zip([6, 12, 29, 38, 50]) {
+            let classes = table
+                .iter()
+                .map(|mask| NoteNames::from_mask(*mask).prime_form().mask())
+                .collect::<BTreeSet<_>>();
+            assert_eq!(expected, classes.len());
+        }
+
+        // Z-related classes come in pairs
+        for (table, expected) in SET_CLASSES.iter().zip([0, 0, 2, 6, 30]) {
+            let z = table
+                .iter()
+                .filter_map(|mask| NoteNames::from_mask(*mask).forte())
+                .filter(|forte| forte.is_z)
+                .count();
+            assert_eq!(expected, z);
+        }
+
+        let all = (0..=0xfff_u16)
+            .map(NoteNames::from_mask)
+            .filter(|names| (2..=10).contains(&names.len()))
+            .map(|names| names.prime_form().mask())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(6 + 12 + 29 + 38 + 50 + 38 + 29 + 12 + 6, all.len());
+    }
+
+    #[test]
+    fn names() {
+        use NoteName::*;
+
+        assert_eq!("3-11", forte(&[C, E, G]));
+        assert_eq!("3-11", forte(&[A, C, E]));
+        assert_eq!("3-12", forte(&[C, E, Ab]));
+        assert_eq!("4-28", forte(&[C, Eb, Gb, A]));
+        assert_eq!("4-27", forte(&[G, B, D, F]));
+        assert_eq!("4-Z15", forte(&[C, Db, E, Gb]));
+        assert_eq!("4-Z29", forte(&[C, Db, Eb, G]));
+        assert_eq!("5-35", forte(&[C, D, E, G, A]));
+        assert_eq!("6-20", forte(&[C, Eb, E, G, Ab, B]));
+        assert_eq!("6-35", forte(&[C, D, E, Gb, Ab, Bb]));
+        assert_eq!("6-Z17", forte(&[C, Db, D, E, G, Ab]));
+        assert_eq!("7-35", forte(&[C, D, E, F, G, A, B]));
+        assert_eq!("8-28", forte(&[C, Db, Eb, E, Gb, G, A, Bb]));
+        assert_eq!("2-6", forte(&[C, Gb]));
+
+        assert_eq!(None, NoteNames::empty().forte());
+        let all: NoteNames = NoteName::CIRCLE_OF_FIFTHS.into_iter().collect();
+        assert_eq!(None, all.forte());
+    }
+}
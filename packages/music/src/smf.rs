@@ -0,0 +1,949 @@
+//! Standard MIDI File (SMF) reader and writer
+//!
+//! Supports reading format 0 and format 1 files. Tracks are merged into a single stream of events
+//! sorted by tick; tempo meta events are applied across all tracks to compute millisecond
+//! timestamps.
+//!
+//! Files are written in format 0.
+
+use crate::midi::CONTROL_HOLD_PEDAL;
+use crate::{Error, Note};
+
+/// Maximum number of tracks in a format 1 file
+pub const MAX_TRACKS: usize = 64;
+
+/// 120 BPM, the default tempo when a file does not specify one
+const DEFAULT_TEMPO: u32 = 500_000; // μs per quarter note
+
+/// Ticks per quarter note of the files produced by `write`. At the default tempo, one tick lasts
+/// one millisecond
+pub const WRITE_DIVISION: u16 = 500;
+
+const HOLD_PEDAL_THRESHOLD: u8 = 64;
+const META_END_OF_TRACK: u8 = 0x2f;
+const META_SET_TEMPO: u8 = 0x51;
+
+/// A parsed, validated Standard MIDI File
+pub struct Smf<B> {
+    bytes: B,
+    format: Format,
+    division: Division,
+    start: Position,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Format {
+    /// A single multi-channel track
+    SingleTrack,
+    /// One or more simultaneous tracks
+    MultiTrack,
+}
+
+/// Meaning of the delta-times in a file
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Division {
+    TicksPerQuarterNote(u16),
+    Smpte {
+        frames_per_second: u8,
+        ticks_per_frame: u8,
+    },
+}
+
+impl<B> Smf<B>
+where
+    B: AsRef<[u8]>,
+{
+    pub fn parse(bytes: B) -> Result<Self, Error> {
+        let file = bytes.as_ref();
+        let Chunk {
+            ty,
+            data: header,
+            mut rest,
+        } = chunk(file)?;
+        if ty != b"MThd" || header.len() < 6 {
+            return Err(Error::MalformedSmf);
+        }
+
+        let format = match u16::from_be_bytes([header[0], header[1]]) {
+            0 => Format::SingleTrack,
+            1 => Format::MultiTrack,
+            _ => return Err(Error::UnsupportedSmf),
+        };
+
+        let division = u16::from_be_bytes([header[4], header[5]]);
+        let division = if division & 0x8000 == 0 {
+            if division == 0 {
+                return Err(Error::MalformedSmf);
+            }
+
+            Division::TicksPerQuarterNote(division)
+        } else {
+            let frames_per_second = ((division >> 8) as u8).wrapping_neg();
+            let ticks_per_frame = division as u8;
+            if !matches!(frames_per_second, 24 | 25 | 29 | 30) || ticks_per_frame == 0 {
+                return Err(Error::MalformedSmf);
+            }
+
+            Division::Smpte {
+                frames_per_second,
+                ticks_per_frame,
+            }
+        };
+
+        let mut start = Position {
+            num_tracks: 0,
+            segment_ms: 0.,
+            segment_tick: 0,
+            tempo: DEFAULT_TEMPO,
+            tracks: [Track::EMPTY; MAX_TRACKS],
+        };
+        while !rest.is_empty() {
+            let Chunk {
+                ty,
+                data,
+                rest: next,
+            } = chunk(rest)?;
+            rest = next;
+
+            // skip unknown chunk types
+            if ty != b"MTrk" {
+                continue;
+            }
+
+            if start.num_tracks == MAX_TRACKS {
+                return Err(Error::UnsupportedSmf);
+            }
+
+            let offset = file.len() - next.len() - data.len();
+            start.tracks[start.num_tracks] = Track::new(file, offset, data.len())?;
+            start.num_tracks += 1;
+        }
+
+        if format == Format::SingleTrack && start.num_tracks != 1 {
+            return Err(Error::MalformedSmf);
+        }
+
+        let smf = Self {
+            bytes,
+            format,
+            division,
+            start,
+        };
+
+        // validate all events upfront so that iterating over them cannot fail
+        let mut events = smf.events();
+        while events.next_event()?.is_some() {}
+
+        Ok(smf)
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn division(&self) -> Division {
+        self.division
+    }
+
+    pub fn num_tracks(&self) -> usize {
+        self.start.num_tracks
+    }
+
+    /// Note and sustain pedal events of all tracks, sorted by time
+    pub fn events(&self) -> Events<'_> {
+        self.events_at(self.start)
+    }
+
+    /// Resumes iterating over the events of this file from `position`
+    ///
+    /// `position` must have been obtained from `Events::position` on this same file
+    pub fn events_at(&self, position: Position) -> Events<'_> {
+        Events {
+            bytes: self.bytes.as_ref(),
+            division: self.division,
+            position,
+        }
+    }
+}
+
+/// An `Event` and the moment it happens, measured from the start of the file
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct TimedEvent {
+    pub tick: u64,
+    pub ms: f64,
+    pub event: Event,
+}
+
+#[derive(Clone, Copy)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Event {
+    NoteOn {
+        channel: u8,
+        note: Note,
+        velocity: u8,
+    },
+    NoteOff {
+        channel: u8,
+        note: Note,
+    },
+    SustainPedal {
+        channel: u8,
+        on: bool,
+    },
+}
+
+pub struct Events<'a> {
+    bytes: &'a [u8],
+    division: Division,
+    position: Position,
+}
+
+/// State of an `Events` iterator that does not borrow the file
+#[derive(Clone, Copy)]
+pub struct Position {
+    num_tracks: usize,
+    segment_ms: f64,
+    segment_tick: u64,
+    tempo: u32,
+    tracks: [Track; MAX_TRACKS],
+}
+
+impl Events<'_> {
+    /// Where the iterator is in the file; see `Smf::events_at`
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    fn next_event(&mut self) -> Result<Option<TimedEvent>, Error> {
+        loop {
+            let position = &mut self.position;
+            let Some(track) = position.tracks[..position.num_tracks]
+                .iter_mut()
+                .filter(|track| !track.ended)
+                // `min_by_key` returns the first of equal elements so ties go to the lower track
+                .min_by_key(|track| track.tick)
+            else {
+                return Ok(None);
+            };
+
+            let tick = track.tick;
+            let Some(raw) = track.next(self.bytes)? else {
+                continue;
+            };
+
+            let event = match raw {
+                RawEvent::Tempo(tempo) => {
+                    self.position.segment_ms = self.tick2ms(tick);
+                    self.position.segment_tick = tick;
+                    self.position.tempo = tempo;
+                    continue;
+                }
+
+                RawEvent::Channel(event) => event,
+            };
+
+            return Ok(Some(TimedEvent {
+                tick,
+                ms: self.tick2ms(tick),
+                event,
+            }));
+        }
+    }
+
+    fn tick2ms(&self, tick: u64) -> f64 {
+        let Position {
+            segment_ms,
+            segment_tick,
+            tempo,
+            ..
+        } = self.position;
+
+        match self.division {
+            Division::TicksPerQuarterNote(ticks_per_quarter_note) => {
+                let ticks = (tick - segment_tick) as f64;
+                segment_ms + ticks * tempo as f64 / (ticks_per_quarter_note as f64 * 1000.)
+            }
+
+            Division::Smpte {
+                frames_per_second,
+                ticks_per_frame,
+            } => {
+                let frames_per_second = if frames_per_second == 29 {
+                    29.97
+                } else {
+                    frames_per_second as f64
+                };
+
+                tick as f64 * 1000. / (frames_per_second * ticks_per_frame as f64)
+            }
+        }
+    }
+}
+
+impl Iterator for Events<'_> {
+    type Item = TimedEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // `Smf::parse` already validated the events
+        self.next_event().ok().flatten()
+    }
+}
+
+/// Cursor into the data of a track chunk
+#[derive(Clone, Copy)]
+struct Track {
+    /// offset, from the start of the file, of the next event
+    pos: usize,
+    /// offset of the end of the chunk
+    end: usize,
+    ended: bool,
+    running_status: Option<u8>,
+    /// absolute tick of the next event
+    tick: u64,
+}
+
+enum RawEvent {
+    Channel(Event),
+    Tempo(u32),
+}
+
+impl Track {
+    const EMPTY: Self = Self {
+        pos: 0,
+        end: 0,
+        ended: true,
+        running_status: None,
+        tick: 0,
+    };
+
+    fn new(file: &[u8], offset: usize, len: usize) -> Result<Self, Error> {
+        let mut track = Self {
+            pos: offset,
+            end: offset + len,
+            ended: false,
+            running_status: None,
+            tick: 0,
+        };
+        track.read_delta(file)?;
+        Ok(track)
+    }
+
+    fn data<'a>(&self, file: &'a [u8]) -> &'a [u8] {
+        file.get(self.pos..self.end).unwrap_or_default()
+    }
+
+    /// Moves the cursor to the start of `rest`, which must be a suffix of the track data
+    fn advance(&mut self, rest: &[u8]) {
+        self.pos = self.end - rest.len();
+    }
+
+    fn read_delta(&mut self, file: &[u8]) -> Result<(), Error> {
+        let data = self.data(file);
+        if data.is_empty() {
+            // tolerate tracks that lack an "End of Track" event
+            self.ended = true;
+            return Ok(());
+        }
+
+        let (delta, rest) = vlq(data).ok_or(Error::MalformedSmf)?;
+        self.tick += u64::from(delta);
+        self.advance(rest);
+
+        Ok(())
+    }
+
+    /// Consumes the event at `self.tick`; returns `None` if it's not an event of interest
+    fn next(&mut self, file: &[u8]) -> Result<Option<RawEvent>, Error> {
+        let data = self.data(file);
+        let (&first, rest) = data.split_first().ok_or(Error::MalformedSmf)?;
+
+        let event = match first {
+            0xff => {
+                self.running_status = None;
+
+                let (&ty, rest) = rest.split_first().ok_or(Error::MalformedSmf)?;
+                let (len, rest) = vlq(rest).ok_or(Error::MalformedSmf)?;
+                let (data, rest) = split_at(rest, len as usize)?;
+                self.advance(rest);
+
+                match ty {
+                    META_END_OF_TRACK => {
+                        self.ended = true;
+                        return Ok(None);
+                    }
+
+                    META_SET_TEMPO => {
+                        let &[a, b, c] = data else {
+                            return Err(Error::MalformedSmf);
+                        };
+
+                        let tempo = u32::from_be_bytes([0, a, b, c]);
+                        if tempo == 0 {
+                            return Err(Error::MalformedSmf);
+                        }
+
+                        Some(RawEvent::Tempo(tempo))
+                    }
+
+                    _ => None,
+                }
+            }
+
+            0xf0 | 0xf7 => {
+                self.running_status = None;
+
+                let (len, rest) = vlq(rest).ok_or(Error::MalformedSmf)?;
+                let (_sysex, rest) = split_at(rest, len as usize)?;
+                self.advance(rest);
+
+                None
+            }
+
+            0xf1..=0xfe => return Err(Error::MalformedSmf),
+
+            _ => {
+                let (status, rest) = if first & 0x80 != 0 {
+                    self.running_status = Some(first);
+                    (first, rest)
+                } else {
+                    // running status: `first` is the first data byte
+                    (self.running_status.ok_or(Error::MalformedSmf)?, data)
+                };
+
+                let num_data_bytes = if matches!(status >> 4, 0xc | 0xd) {
+                    1
+                } else {
+                    2
+                };
+                let (data, rest) = split_at(rest, num_data_bytes)?;
+                if data.iter().any(|byte| byte & 0x80 != 0) {
+                    return Err(Error::MalformedSmf);
+                }
+                self.advance(rest);
+
+                channel_event(status, data).map(RawEvent::Channel)
+            }
+        };
+
+        self.read_delta(file)?;
+
+        Ok(event)
+    }
+}
+
+/// Encodes `events` as a format 0 file
+///
+/// Timestamps are taken from `TimedEvent::ms`; `TimedEvent::tick` is ignored. `events` is
+/// iterated twice: once to compute the size of the track and once to encode it
+pub fn write<I>(events: I, out: &mut impl Extend<u8>)
+where
+    I: IntoIterator<Item = TimedEvent>,
+    I::IntoIter: Clone,
+{
+    const HEADER_LEN: u32 = 6;
+    #[rustfmt::skip]
+    const TEMPO: [u8; 7] = [
+        0x00, 0xff, META_SET_TEMPO, 0x03,
+        (DEFAULT_TEMPO >> 16) as u8, (DEFAULT_TEMPO >> 8) as u8, DEFAULT_TEMPO as u8,
+    ];
+    const END_OF_TRACK: [u8; 4] = [0x00, 0xff, META_END_OF_TRACK, 0x00];
+
+    let events = events.into_iter();
+
+    let mut track_len = (TEMPO.len() + END_OF_TRACK.len()) as u32;
+    let mut last_tick = 0;
+    for event in events.clone() {
+        track_len += encode(event, &mut last_tick).len() as u32;
+    }
+
+    out.extend(*b"MThd");
+    out.extend(HEADER_LEN.to_be_bytes());
+    out.extend(0u16.to_be_bytes()); // format
+    out.extend(1u16.to_be_bytes()); // number of tracks
+    out.extend(WRITE_DIVISION.to_be_bytes());
+
+    out.extend(*b"MTrk");
+    out.extend(track_len.to_be_bytes());
+    out.extend(TEMPO);
+
+    let mut last_tick = 0;
+    for event in events {
+        out.extend(encode(event, &mut last_tick));
+    }
+
+    out.extend(END_OF_TRACK);
+}
+
+/// Delta-time followed by a channel message
+struct Encoded {
+    buf: [u8; 7],
+    len: usize,
+}
+
+impl Encoded {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl IntoIterator for Encoded {
+    type Item = u8;
+    type IntoIter = core::iter::Take<core::array::IntoIter<u8, 7>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.buf.into_iter().take(self.len)
+    }
+}
+
+fn encode(TimedEvent { ms, event, .. }: TimedEvent, last_tick: &mut u64) -> Encoded {
+    const MAX_DELTA: u64 = 0x0fff_ffff;
+
+    // `as` saturates negative values to zero
+    let tick = ((ms + 0.5) as u64).max(*last_tick);
+    let delta = (tick - *last_tick).min(MAX_DELTA) as u32;
+    *last_tick += u64::from(delta);
+
+    let mut buf = [0; 7];
+    let mut len = 0;
+    for shift in [21, 14, 7] {
+        if delta >> shift != 0 {
+            buf[len] = 0x80 | (delta >> shift) as u8;
+            len += 1;
+        }
+    }
+    buf[len] = delta as u8 & 0x7f;
+    len += 1;
+
+    let message = match event {
+        Event::NoteOn {
+            channel,
+            note,
+            velocity,
+        } => [
+            0x90 | (channel & 0x0f),
+            note.as_u8(),
+            velocity.clamp(1, 0x7f),
+        ],
+
+        Event::NoteOff { channel, note } => [0x80 | (channel & 0x0f), note.as_u8(), 0x40],
+
+        Event::SustainPedal { channel, on } => [
+            0xb0 | (channel & 0x0f),
+            CONTROL_HOLD_PEDAL,
+            if on { 0x7f } else { 0x00 },
+        ],
+    };
+    buf[len..len + 3].copy_from_slice(&message);
+    len += 3;
+
+    Encoded { buf, len }
+}
+
+fn channel_event(status: u8, data: &[u8]) -> Option<Event> {
+    let channel = status & 0x0f;
+
+    let event = match (status >> 4, data) {
+        (0x8, &[note, _]) | (0x9, &[note, 0]) => Event::NoteOff {
+            channel,
+            note: Note::from_u8_lossy(note),
+        },
+
+        (0x9, &[note, velocity]) => Event::NoteOn {
+            channel,
+            note: Note::from_u8_lossy(note),
+            velocity,
+        },
+
+        (0xb, &[CONTROL_HOLD_PEDAL, value]) => Event::SustainPedal {
+            channel,
+            on: value >= HOLD_PEDAL_THRESHOLD,
+        },
+
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+struct Chunk<'a> {
+    ty: &'a [u8],
+    data: &'a [u8],
+    /// bytes after the chunk
+    rest: &'a [u8],
+}
+
+fn chunk(bytes: &[u8]) -> Result<Chunk<'_>, Error> {
+    let (header, rest) = split_at(bytes, 8)?;
+    let (ty, len) = header.split_at(4);
+    let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]);
+    let (data, rest) = split_at(rest, len as usize)?;
+
+    Ok(Chunk { ty, data, rest })
+}
+
+fn split_at(bytes: &[u8], mid: usize) -> Result<(&[u8], &[u8]), Error> {
+    bytes.split_at_checked(mid).ok_or(Error::MalformedSmf)
+}
+
+/// Variable-length quantity: up to 4 bytes, 7 bits per byte, most significant bits first
+fn vlq(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let mut value = 0;
+    for (index, byte) in bytes.iter().take(4).enumerate() {
+        value = (value << 7) | u32::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            return Some((value, &bytes[index + 1..]));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(format: u16, num_tracks: u16, division: u16) -> Vec<u8> {
+        let mut bytes = b"MThd".to_vec();
+        bytes.extend(6u32.to_be_bytes());
+        bytes.extend(format.to_be_bytes());
+        bytes.extend(num_tracks.to_be_bytes());
+        bytes.extend(division.to_be_bytes());
+        bytes
+    }
+
+    fn track(bytes: &mut Vec<u8>, events: &[u8]) {
+        bytes.extend(b"MTrk");
+        bytes.extend((events.len() as u32).to_be_bytes());
+        bytes.extend(events);
+    }
+
+    fn note_on(channel: u8, note: Note, velocity: u8) -> Event {
+        Event::NoteOn {
+            channel,
+            note,
+            velocity,
+        }
+    }
+
+    fn note_off(channel: u8, note: Note) -> Event {
+        Event::NoteOff { channel, note }
+    }
+
+    #[test]
+    fn vlq() {
+        assert_eq!(Some((0, &[][..])), super::vlq(&[0x00]));
+        assert_eq!(Some((0x7f, &[][..])), super::vlq(&[0x7f]));
+        assert_eq!(Some((0x80, &[][..])), super::vlq(&[0x81, 0x00]));
+        assert_eq!(Some((0x2000, &[][..])), super::vlq(&[0xc0, 0x00]));
+        assert_eq!(
+            Some((0x0fff_ffff, &[0x42][..])),
+            super::vlq(&[0xff, 0xff, 0xff, 0x7f, 0x42])
+        );
+
+        // too long
+        assert_eq!(None, super::vlq(&[0x80, 0x80, 0x80, 0x80, 0x00]));
+        // truncated
+        assert_eq!(None, super::vlq(&[0x81]));
+    }
+
+    #[test]
+    fn format0() {
+        let mut bytes = header(0, 1, 96);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0x90, 60, 100, // C4 on
+            0x60, 0x80, 60, 0,   // C4 off, one quarter note later
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        assert_eq!(Format::SingleTrack, smf.format());
+        assert_eq!(Division::TicksPerQuarterNote(96), smf.division());
+
+        let events: Vec<_> = smf.events().collect();
+        assert_eq!(
+            vec![
+                TimedEvent {
+                    tick: 0,
+                    ms: 0.,
+                    event: note_on(0, Note::C4, 100),
+                },
+                TimedEvent {
+                    tick: 96,
+                    ms: 500.,
+                    event: note_off(0, Note::C4),
+                },
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn running_status() {
+        let mut bytes = header(0, 1, 96);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0x91, 60, 100, // C4 on
+            0x00, 64, 100,       // E4 on
+            0x00, 67, 100,       // G4 on
+            0x60, 60, 0,         // C4 off: velocity 0
+            0x00, 64, 0,         // E4 off
+            0x00, 67, 0,         // G4 off
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        let events: Vec<_> = smf.events().map(|event| event.event).collect();
+        assert_eq!(
+            vec![
+                note_on(1, Note::C4, 100),
+                note_on(1, Note::E4, 100),
+                note_on(1, Note::G4, 100),
+                note_off(1, Note::C4),
+                note_off(1, Note::E4),
+                note_off(1, Note::G4),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn running_status_is_cancelled_by_meta_events() {
+        let mut bytes = header(0, 1, 96);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0x90, 60, 100,
+            0x00, 0xff, 0x01, 0x01, b'!', // text
+            0x00, 64, 100,
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        assert_eq!(Err(Error::MalformedSmf), Smf::parse(&bytes).map(|_| ()));
+    }
+
+    #[test]
+    fn tempo_changes() {
+        let mut bytes = header(1, 2, 480);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            // conductor track
+            0x00, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40, // 60 BPM
+            0x83, 0x60, 0xff, 0x51, 0x03, 0x07, 0xa1, 0x20, // 120 BPM, after 480 ticks
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0x90, 60, 100,
+            0x83, 0x60, 60, 0,  // tick 480
+            0x83, 0x60, 62, 90, // tick 960
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        assert_eq!(2, smf.num_tracks());
+
+        let events: Vec<_> = smf.events().map(|event| (event.tick, event.ms)).collect();
+        assert_eq!(vec![(0, 0.), (480, 1000.), (960, 1500.)], events);
+    }
+
+    #[test]
+    fn tracks_are_merged() {
+        let mut bytes = header(1, 2, 96);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0x90, 48, 100, // C3 on
+            0x81, 0x40, 0x80, 48, 0, // C3 off @ 192
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x60, 0x91, 64, 100, // E4 on @ 96
+            0x00, 0xb1, 64, 127, // pedal down @ 96
+            0x81, 0x40, 0xb1, 64, 0, // pedal up @ 288
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        let events: Vec<_> = smf
+            .events()
+            .map(|event| (event.tick, event.event))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, note_on(0, Note::C3, 100)),
+                (96, note_on(1, Note::E4, 100)),
+                (
+                    96,
+                    Event::SustainPedal {
+                        channel: 1,
+                        on: true
+                    }
+                ),
+                (192, note_off(0, Note::C3)),
+                (
+                    288,
+                    Event::SustainPedal {
+                        channel: 1,
+                        on: false
+                    }
+                ),
+            ],
+            events
+        );
+    }
+
+    #[test]
+    fn skips_sysex_unknown_chunks_and_other_messages() {
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(b"XFIH");
+        bytes.extend(2u32.to_be_bytes());
+        bytes.extend([0xde, 0xad]);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0xf0, 0x03, 0x7e, 0x7f, 0xf7, // sysex
+            0x00, 0xc0, 0x05,                   // program change
+            0x00, 0xe0, 0x00, 0x40,             // pitch bend
+            0x00, 0x90, 60, 100,
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        let events: Vec<_> = smf.events().map(|event| event.event).collect();
+        assert_eq!(vec![note_on(0, Note::C4, 100)], events);
+    }
+
+    #[test]
+    fn smpte_division() {
+        // 25 fps, 40 ticks per frame => 1 tick = 1 ms
+        let mut bytes = header(0, 1, u16::from_be_bytes([-25i8 as u8, 40]));
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x87, 0x68, 0x90, 60, 100, // @ 1000
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        let events: Vec<_> = smf.events().map(|event| (event.tick, event.ms)).collect();
+        assert_eq!(vec![(1000, 1000.)], events);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(Error::MalformedSmf), Smf::parse(b"MThd").map(|_| ()));
+
+        let bytes = header(2, 1, 96);
+        assert_eq!(Err(Error::UnsupportedSmf), Smf::parse(&bytes).map(|_| ()));
+
+        // truncated event
+        let mut bytes = header(0, 1, 96);
+        track(&mut bytes, &[0x00, 0x90, 60]);
+        assert_eq!(Err(Error::MalformedSmf), Smf::parse(&bytes).map(|_| ()));
+
+        // truncated chunk
+        let mut bytes = header(0, 1, 96);
+        track(&mut bytes, &[0x00, 0x90, 60, 100]);
+        bytes.pop();
+        assert_eq!(Err(Error::MalformedSmf), Smf::parse(&bytes).map(|_| ()));
+    }
+
+    #[test]
+    fn write_then_parse() {
+        let events = [
+            (0., note_on(0, Note::C4, 100)),
+            (
+                0.4,
+                Event::SustainPedal {
+                    channel: 0,
+                    on: true,
+                },
+            ),
+            (250.6, note_off(0, Note::C4)),
+            (20_000., note_on(3, Note::G4, 1)),
+            // out of order timestamps are clamped
+            (19_000., note_off(3, Note::G4)),
+        ]
+        .map(|(ms, event)| TimedEvent { tick: 0, ms, event });
+
+        let mut bytes = vec![];
+        write(events, &mut bytes);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        assert_eq!(
+            Division::TicksPerQuarterNote(WRITE_DIVISION),
+            smf.division()
+        );
+
+        let parsed: Vec<_> = smf.events().collect();
+        let expected = [0, 0, 251, 20_000, 20_000];
+        assert_eq!(events.len(), parsed.len());
+        for ((parsed, event), tick) in parsed.iter().zip(events).zip(expected) {
+            assert_eq!(event.event, parsed.event);
+            assert_eq!(tick, parsed.tick);
+            assert_eq!(tick as f64, parsed.ms);
+        }
+    }
+
+    #[test]
+    fn events_at() {
+        let mut bytes = header(0, 1, 96);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40, // 60 BPM
+            0x00, 0x90, 60, 100,
+            0x60, 64, 100,
+            0x60, 67, 100,
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(bytes).unwrap();
+        let position = {
+            let mut events = smf.events();
+            events.next().unwrap();
+            events.position()
+        };
+
+        let rest: Vec<_> = smf
+            .events_at(position)
+            .map(|event| (event.ms, event.event))
+            .collect();
+        assert_eq!(
+            vec![
+                (1000., note_on(0, Note::E4, 100)),
+                (2000., note_on(0, Note::G4, 100)),
+            ],
+            rest
+        );
+    }
+
+    #[test]
+    fn identify_chord() {
+        let mut bytes = header(0, 1, 96);
+        #[rustfmt::skip]
+        track(&mut bytes, &[
+            0x00, 0x90, 60, 100,
+            0x00, 64, 100,
+            0x00, 67, 100,
+            0x60, 60, 0,
+            0x00, 0xff, 0x2f, 0x00,
+        ]);
+
+        let smf = Smf::parse(&bytes).unwrap();
+        let mut held = crate::Notes::empty();
+        for TimedEvent { event, tick, .. } in smf.events() {
+            if tick > 0 {
+                break;
+            }
+
+            if let Event::NoteOn { note, .. } = event {
+                held.insert(note);
+            }
+        }
+
+        let chord = crate::Chord::try_from(held).unwrap();
+        let id = chord.identify_with_tonic(crate::NoteName::C).unwrap();
+        assert!(matches!(id.kind, crate::chord_id::ChordKind::Major));
+    }
+}
@@ -0,0 +1,393 @@
+//! Intervals named after the letters they span, e.g. A4 as opposed to d5
+//!
+//! Unlike `Interval`, the number of letters is kept apart from the number of half steps so that
+//! augmented and diminished intervals can be told from their enharmonic equivalents. Intervals
+//! may be compound, of any size, and descending
+
+use core::fmt;
+use core::ops::{Add, Neg, Sub};
+
+use crate::spelled_pitch::SpelledPitch;
+use crate::{Error, Note};
+
+/// Half steps of the major and perfect simple intervals, from the unison to the seventh
+const MAJOR_OR_PERFECT: [i16; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Quality {
+    /// e.g. 2 for doubly diminished
+    Diminished(u8),
+    Minor,
+    Perfect,
+    Major,
+    /// e.g. 2 for doubly augmented
+    Augmented(u8),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct SpelledInterval {
+    /// 1 for unisons, 8 for octaves, 10 for tenths and so on
+    number: u8,
+    /// of the ascending interval; negative for e.g. diminished unisons
+    half_steps: i16,
+    is_descending: bool,
+}
+
+impl SpelledInterval {
+    /// Ascending interval; `None` if `number` is zero or if the quality does not exist for it,
+    /// e.g. a major fifth
+    pub fn new(quality: Quality, number: u8) -> Option<Self> {
+        let reference = reference(number.checked_sub(1)?);
+        let is_perfect = is_perfect(number);
+
+        let offset = match (quality, is_perfect) {
+            (Quality::Perfect, true) | (Quality::Major, false) => 0,
+            (Quality::Minor, false) => -1,
+            (Quality::Augmented(n), _) if n > 0 => i16::from(n),
+            (Quality::Diminished(n), true) if n > 0 => -i16::from(n),
+            (Quality::Diminished(n), false) if n > 0 => -1 - i16::from(n),
+            _ => return None,
+        };
+
+        Some(Self {
+            number,
+            half_steps: reference + offset,
+            is_descending: false,
+        })
+    }
+
+    /// The usual name of an ascending interval `half_steps` wide; tritones are augmented fourths
+    pub fn from_half_steps(half_steps: u8) -> Self {
+        const NUMBERS: [u8; 12] = [1, 2, 2, 3, 3, 4, 4, 5, 6, 6, 7, 7];
+
+        let octaves = half_steps / 12;
+        Self {
+            number: NUMBERS[usize::from(half_steps % 12)] + 7 * octaves,
+            half_steps: i16::from(half_steps),
+            is_descending: false,
+        }
+    }
+
+    /// The interval from `from` to `to`, spelled as `from_spelling` and `to_spelling`. Its
+    /// direction is the one of the letters, e.g. B♯3 to C4 is an ascending diminished second
+    pub fn between(
+        from: Note,
+        from_spelling: SpelledPitch,
+        to: Note,
+        to_spelling: SpelledPitch,
+    ) -> Self {
+        let letters = letter_index(to, to_spelling) - letter_index(from, from_spelling);
+        let half_steps = i16::from(from.distance_to(to));
+
+        let is_descending = letters < 0 || (letters == 0 && half_steps < 0);
+        let (letters, half_steps) = if is_descending {
+            (-letters, -half_steps)
+        } else {
+            (letters, half_steps)
+        };
+
+        Self {
+            number: letters as u8 + 1,
+            half_steps,
+            is_descending,
+        }
+    }
+
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    pub fn quality(&self) -> Quality {
+        let offset = self.half_steps - reference(self.number - 1);
+
+        match (offset, is_perfect(self.number)) {
+            (0, true) => Quality::Perfect,
+            (0, false) => Quality::Major,
+            (-1, false) => Quality::Minor,
+            (1.., _) => Quality::Augmented(offset as u8),
+            (_, true) => Quality::Diminished(offset.unsigned_abs() as u8),
+            (_, false) => Quality::Diminished((offset.unsigned_abs() - 1) as u8),
+        }
+    }
+
+    pub fn is_descending(&self) -> bool {
+        self.is_descending
+    }
+
+    /// Negative if the interval is descending
+    pub fn half_steps(&self) -> i16 {
+        if self.is_descending {
+            -self.half_steps
+        } else {
+            self.half_steps
+        }
+    }
+
+    /// Wider than an octave
+    pub fn is_compound(&self) -> bool {
+        self.number > 8
+    }
+
+    /// The interval within an octave with the same letters and accidentals, e.g. M3 for M10
+    pub fn simple(&self) -> Self {
+        let octaves = (self.number - 1) / 7;
+        // octaves stay octaves
+        let octaves = if self.number > 1 && (self.number - 1) % 7 == 0 {
+            octaves - 1
+        } else {
+            octaves
+        };
+
+        Self {
+            number: self.number - 7 * octaves,
+            half_steps: self.half_steps - 12 * i16::from(octaves),
+            is_descending: self.is_descending,
+        }
+    }
+
+    /// The simple interval that completes this one to an octave, e.g. m6 for M3 and d5 for A4.
+    /// Compound intervals are reduced first
+    pub fn invert(&self) -> Self {
+        let simple = self.simple();
+
+        Self {
+            number: 9 - simple.number,
+            half_steps: 12 - simple.half_steps,
+            is_descending: self.is_descending,
+        }
+    }
+}
+
+/// Half steps of the major or perfect interval that spans `letters` letters above the root
+fn reference(letters: u8) -> i16 {
+    MAJOR_OR_PERFECT[usize::from(letters % 7)] + 12 * i16::from(letters / 7)
+}
+
+/// Unisons, fourths, fifths and their compounds
+fn is_perfect(number: u8) -> bool {
+    matches!((number - 1) % 7, 0 | 3 | 4)
+}
+
+/// Letters from C-1
+fn letter_index(note: Note, spelling: SpelledPitch) -> i16 {
+    (spelling.octave(note.as_u8()) + 1) * 7 + spelling.letter as i16
+}
+
+impl Neg for SpelledInterval {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            is_descending: !self.is_descending,
+            ..self
+        }
+    }
+}
+
+impl Add<SpelledInterval> for SpelledPitch {
+    type Output = SpelledPitch;
+
+    fn add(self, interval: SpelledInterval) -> SpelledPitch {
+        let letters = i16::from(interval.number - 1);
+        let half_steps = interval.half_steps;
+
+        let (letters, half_steps) = if interval.is_descending {
+            (-letters, -half_steps)
+        } else {
+            (letters, half_steps)
+        };
+
+        self.above(letters.rem_euclid(7) as u8, half_steps.rem_euclid(12) as u8)
+    }
+}
+
+impl Sub<SpelledInterval> for SpelledPitch {
+    type Output = SpelledPitch;
+
+    fn sub(self, interval: SpelledInterval) -> SpelledPitch {
+        self + -interval
+    }
+}
+
+/// The ascending interval, within an octave, from `lower` up to `self`
+impl Sub for SpelledPitch {
+    type Output = SpelledInterval;
+
+    fn sub(self, lower: SpelledPitch) -> SpelledInterval {
+        let letters = (self.letter as i16 - lower.letter as i16).rem_euclid(7);
+        let naturals =
+            (self.letter.natural() as i16 - lower.letter.natural() as i16).rem_euclid(12);
+
+        SpelledInterval {
+            number: letters as u8 + 1,
+            half_steps: naturals + i16::from(self.accidentals - lower.accidentals),
+            is_descending: false,
+        }
+    }
+}
+
+impl Add<SpelledInterval> for Note {
+    type Output = Result<Note, Error>;
+
+    fn add(self, interval: SpelledInterval) -> Result<Note, Error> {
+        let half_steps = i8::try_from(interval.half_steps()).map_err(|_| Error::NoteOutOfRange)?;
+        self.step(half_steps)
+    }
+}
+
+impl Sub<SpelledInterval> for Note {
+    type Output = Result<Note, Error>;
+
+    fn sub(self, interval: SpelledInterval) -> Result<Note, Error> {
+        self + -interval
+    }
+}
+
+impl fmt::Display for SpelledInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_descending {
+            f.write_str("↓")?;
+        }
+
+        let (symbol, count) = match self.quality() {
+            Quality::Diminished(count) => ("d", count),
+            Quality::Minor => ("m", 1),
+            Quality::Perfect => ("P", 1),
+            Quality::Major => ("M", 1),
+            Quality::Augmented(count) => ("A", count),
+        };
+
+        for _ in 0..count {
+            f.write_str(symbol)?;
+        }
+
+        write!(f, "{}", self.number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::ToString;
+
+    use super::*;
+    use crate::spelled_pitch::Letter;
+
+    const C: SpelledPitch = SpelledPitch::new(Letter::C, 0);
+    const G_SHARP: SpelledPitch = SpelledPitch::new(Letter::G, 1);
+    const A_FLAT: SpelledPitch = SpelledPitch::new(Letter::A, -1);
+
+    fn interval(quality: Quality, number: u8) -> SpelledInterval {
+        SpelledInterval::new(quality, number).unwrap()
+    }
+
+    #[test]
+    fn qualities() {
+        assert_eq!(8, interval(Quality::Augmented(1), 5).half_steps());
+        assert_eq!(8, interval(Quality::Minor, 6).half_steps());
+        assert_eq!(6, interval(Quality::Augmented(1), 4).half_steps());
+        assert_eq!(6, interval(Quality::Diminished(1), 5).half_steps());
+        assert_eq!(9, interval(Quality::Diminished(1), 7).half_steps());
+        assert_eq!(16, interval(Quality::Major, 10).half_steps());
+        assert_eq!(31, interval(Quality::Perfect, 19).half_steps());
+
+        assert_eq!(None, SpelledInterval::new(Quality::Major, 5));
+        assert_eq!(None, SpelledInterval::new(Quality::Perfect, 3));
+        assert_eq!(None, SpelledInterval::new(Quality::Perfect, 0));
+        assert_eq!(None, SpelledInterval::new(Quality::Augmented(0), 4));
+
+        for half_steps in 0..=40 {
+            let interval = SpelledInterval::from_half_steps(half_steps);
+            assert_eq!(i16::from(half_steps), interval.half_steps());
+            assert_eq!(
+                Some(interval),
+                SpelledInterval::new(interval.quality(), interval.number())
+            );
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("A4", SpelledInterval::from_half_steps(6).to_string());
+        assert_eq!("P8", SpelledInterval::from_half_steps(12).to_string());
+        assert_eq!("m10", SpelledInterval::from_half_steps(15).to_string());
+        assert_eq!("M31", SpelledInterval::from_half_steps(52).to_string());
+        assert_eq!("AA4", interval(Quality::Augmented(2), 4).to_string());
+        assert_eq!("dd7", interval(Quality::Diminished(2), 7).to_string());
+        assert_eq!("↓m3", (-interval(Quality::Minor, 3)).to_string());
+    }
+
+    #[test]
+    fn between() {
+        let between = |from: Note, from_spelling, to: Note, to_spelling| {
+            SpelledInterval::between(from, from_spelling, to, to_spelling).to_string()
+        };
+
+        assert_eq!("A5", between(Note::C4, C, Note::Ab4, G_SHARP));
+        assert_eq!("m6", between(Note::C4, C, Note::Ab4, A_FLAT));
+        let f = SpelledPitch::new(Letter::F, 0);
+        assert_eq!("↓m3", between(Note::Ab4, A_FLAT, Note::F4, f));
+        assert_eq!("A12", between(Note::C3, C, Note::Ab4, G_SHARP));
+
+        let b = SpelledPitch::new(Letter::B, 0);
+        assert_eq!("m2", between(Note::B3, b, Note::C4, C));
+
+        // same key, different letters
+        let b_sharp = SpelledPitch::new(Letter::B, 1);
+        assert_eq!("d2", between(Note::C4, b_sharp, Note::C4, C));
+        assert_eq!("↓d2", between(Note::C4, C, Note::C4, b_sharp));
+
+        // at the top of the range, B♭°⁷
+        let b_flat = SpelledPitch::new(Letter::B, -1);
+        let a_double_flat = SpelledPitch::new(Letter::A, -2);
+        let top = Note::from_u8_lossy(127);
+        assert_eq!(
+            "d7",
+            between(Note::from_u8_lossy(118), b_flat, top, a_double_flat)
+        );
+    }
+
+    #[test]
+    fn inversion() {
+        assert_eq!("m6", interval(Quality::Major, 3).invert().to_string());
+        assert_eq!(
+            "d5",
+            interval(Quality::Augmented(1), 4).invert().to_string()
+        );
+        assert_eq!("P4", interval(Quality::Perfect, 5).invert().to_string());
+        assert_eq!("m2", interval(Quality::Major, 7).invert().to_string());
+        assert_eq!("P1", interval(Quality::Perfect, 8).invert().to_string());
+        assert_eq!("P8", interval(Quality::Perfect, 1).invert().to_string());
+        // compound intervals are reduced
+        assert_eq!("m6", interval(Quality::Major, 10).invert().to_string());
+        assert_eq!("M3", interval(Quality::Major, 10).simple().to_string());
+        assert!(interval(Quality::Major, 9).is_compound());
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(G_SHARP, C + interval(Quality::Augmented(1), 5));
+        assert_eq!(A_FLAT, C + interval(Quality::Minor, 6));
+        assert_eq!(A_FLAT, C - interval(Quality::Major, 3));
+        assert_eq!(C, G_SHARP - interval(Quality::Augmented(1), 5));
+        assert_eq!(
+            SpelledPitch::new(Letter::D, 0),
+            C + interval(Quality::Major, 9)
+        );
+
+        assert_eq!("A5", (G_SHARP - C).to_string());
+        assert_eq!("m6", (A_FLAT - C).to_string());
+        assert_eq!(
+            "dd4",
+            (A_FLAT - SpelledPitch::new(Letter::E, 1)).to_string()
+        );
+
+        assert_eq!(Ok(Note::Ab4), Note::C4 + interval(Quality::Augmented(1), 5));
+        assert_eq!(Ok(Note::C3), Note::C4 - interval(Quality::Perfect, 8));
+        assert_eq!(
+            Err(Error::NoteOutOfRange),
+            Note::C8 + interval(Quality::Perfect, 22)
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Pitches spelled with a letter and accidentals, e.g. G♯ as opposed to A♭
+//!
+//! `NoteName` only tells the pitch class apart. Chord tones are spelled by stacking letters on
+//! the root: the fifth of an augmented chord on C is G♯ and the seventh of C°⁷ is B𝄫
+
+use core::fmt;
+
+use crate::chord_id::{ChordId, ChordKind};
+use crate::{NoteName, Scale};
+
+/// Chord tones and extensions
+const MAX_MEMBERS: usize = 7;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Letter {
+    C,
+    D,
+    E,
+    F,
+    G,
+    A,
+    B,
+}
+
+impl Letter {
+    const ALL: [Self; 7] = [
+        Self::C,
+        Self::D,
+        Self::E,
+        Self::F,
+        Self::G,
+        Self::A,
+        Self::B,
+    ];
+
+    /// The pitch class of the letter without accidentals
+    pub fn natural(&self) -> NoteName {
+        match self {
+            Letter::C => NoteName::C,
+            Letter::D => NoteName::D,
+            Letter::E => NoteName::E,
+            Letter::F => NoteName::F,
+            Letter::G => NoteName::G,
+            Letter::A => NoteName::A,
+            Letter::B => NoteName::B,
+        }
+    }
+
+    /// The letter `letters` letters above, e.g. the third letter above C is E
+    pub fn step(&self, letters: u8) -> Self {
+        Self::ALL[(*self as usize + usize::from(letters)) % 7]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Letter::C => "C",
+            Letter::D => "D",
+            Letter::E => "E",
+            Letter::F => "F",
+            Letter::G => "G",
+            Letter::A => "A",
+            Letter::B => "B",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct SpelledPitch {
+    pub letter: Letter,
+    /// Number of sharps, or of flats if negative; at most two
+    pub accidentals: i8,
+}
+
+impl SpelledPitch {
+    pub const fn new(letter: Letter, accidentals: i8) -> Self {
+        Self {
+            letter,
+            accidentals,
+        }
+    }
+
+    /// The spelling with the fewest accidentals; flats for the black keys
+    pub fn simplest(name: NoteName) -> Self {
+        let natural = Letter::ALL
+            .into_iter()
+            .find(|letter| letter.natural() == name);
+
+        match natural {
+            Some(letter) => Self::new(letter, 0),
+            None => Self::new(Letter::ALL[(name as usize + 1) * 7 / 12], -1),
+        }
+    }
+
+    pub fn name(&self) -> NoteName {
+        self.letter
+            .natural()
+            .step((12 + self.accidentals).rem_euclid(12) as u8)
+    }
+
+    /// The pitch `letters` letters and `half_steps` above, e.g. the fifth above C is G and the
+    /// augmented fifth is G♯. When that takes more than two accidentals the simplest spelling
+    /// of the pitch is used instead
+    pub fn above(&self, letters: u8, half_steps: u8) -> Self {
+        let letter = self.letter.step(letters);
+        let name = self.name().step(half_steps % 12);
+
+        // in -6..6
+        let accidentals = (name as i8 - letter.natural() as i8 + 18).rem_euclid(12) - 6;
+        if accidentals.abs() > 2 {
+            Self::simplest(name)
+        } else {
+            Self::new(letter, accidentals)
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        // from 𝄫 to 𝄪
+        const SPELLINGS: [[&str; 5]; 7] = [
+            ["C𝄫", "C♭", "C", "C♯", "C𝄪"],
+            ["D𝄫", "D♭", "D", "D♯", "D𝄪"],
+            ["E𝄫", "E♭", "E", "E♯", "E𝄪"],
+            ["F𝄫", "F♭", "F", "F♯", "F𝄪"],
+            ["G𝄫", "G♭", "G", "G♯", "G𝄪"],
+            ["A𝄫", "A♭", "A", "A♯", "A𝄪"],
+            ["B𝄫", "B♭", "B", "B♯", "B𝄪"],
+        ];
+
+        SPELLINGS[self.letter as usize][(self.accidentals.clamp(-2, 2) + 2) as usize]
+    }
+
+    /// Octave of the letter, which is not the octave of the pitch for e.g. B♯ or C♭
+    pub(crate) fn octave(&self, midi: u8) -> i16 {
+        // MIDI 127 spelled A𝄫 does not fit in an i8 once the accidentals are taken away
+        (i16::from(midi) - i16::from(self.accidentals)).div_euclid(12) - 1
+    }
+}
+
+impl fmt::Display for SpelledPitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The notes of a chord spelled from its root
+#[derive(Clone, Copy)]
+pub struct ChordSpelling {
+    bass: Option<SpelledPitch>,
+    len: usize,
+    /// root first, then the other chord tones and the extensions
+    members: [SpelledPitch; MAX_MEMBERS],
+}
+
+impl ChordSpelling {
+    pub(crate) fn new(tonic: NoteName, id: &ChordId, scale: Scale) -> Self {
+        let root = tonic.spell(scale);
+
+        let mut spelling = Self {
+            bass: None,
+            len: 0,
+            members: [root; MAX_MEMBERS],
+        };
+
+        let tones = id.kind.intervals().iter().copied();
+        for half_steps in tones.chain(id.extensions.half_steps()) {
+            spelling.members[spelling.len] = root.above(letters(id.kind, half_steps), half_steps);
+            spelling.len += 1;
+        }
+
+        spelling.bass = id
+            .bass
+            .map(|bass| spelling.get(bass).unwrap_or_else(|| bass.spell(scale)));
+
+        spelling
+    }
+
+    pub fn root(&self) -> SpelledPitch {
+        self.members[0]
+    }
+
+    pub fn bass(&self) -> Option<SpelledPitch> {
+        self.bass
+    }
+
+    /// Root first, then the other chord tones from the bottom up and the extensions
+    pub fn members(&self) -> impl Iterator<Item = SpelledPitch> + '_ {
+        self.members[..self.len].iter().copied()
+    }
+
+    /// The spelling of the member whose pitch class is `name`, if any
+    pub fn get(&self, name: NoteName) -> Option<SpelledPitch> {
+        self.members()
+            .chain(self.bass)
+            .find(|member| member.name() == name)
+    }
+}
+
+/// Letters from the root to a chord tone `half_steps` above it, e.g. 2 for thirds
+fn letters(kind: ChordKind, half_steps: u8) -> u8 {
+    match half_steps {
+        0 => 0,
+        1 | 2 => 1,
+        3 | 4 => 2,
+        5 => 3,
+        6 if matches!(kind, ChordKind::Lydian) => 3,
+        6..=8 => 4,
+        9 if matches!(kind, ChordKind::DiminishedSeventh) => 6,
+        9 => 5,
+        10 | 11 => 6,
+        // extensions
+        13..=15 => 1,
+        16..=18 => 3,
+        _ => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::ChordSymbol;
+
+    fn spell(s: &str, scale: Scale) -> String {
+        let symbol: ChordSymbol = s.parse().unwrap();
+        symbol
+            .id
+            .spell(symbol.tonic, scale)
+            .members()
+            .map(|member| member.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn above() {
+        let c = SpelledPitch::new(Letter::C, 0);
+        assert_eq!("G♯", c.above(4, 8).to_string());
+        assert_eq!("A♭", c.above(5, 8).to_string());
+        assert_eq!("B𝄫", c.above(6, 9).to_string());
+
+        let f_sharp = SpelledPitch::new(Letter::F, 1);
+        assert_eq!("A♯", f_sharp.above(2, 4).to_string());
+        assert_eq!("C𝄪", f_sharp.above(4, 8).to_string());
+
+        // rather than E with three flats
+        let f_flat = SpelledPitch::new(Letter::F, -1);
+        assert_eq!("D♭", f_flat.above(6, 9).to_string());
+    }
+
+    #[test]
+    fn simplest() {
+        for name in NoteName::CIRCLE_OF_FIFTHS {
+            let spelled = SpelledPitch::simplest(name);
+            assert_eq!(name, spelled.name());
+            assert_eq!(name.as_flat_str(), spelled.to_string());
+        }
+    }
+
+    #[test]
+    fn chords() {
+        let c_major = Scale::major(NoteName::C);
+
+        assert_eq!("C E G♯", spell("C+", c_major));
+        assert_eq!("C E♭ G♭ B𝄫", spell("Cdim7", c_major));
+        assert_eq!("B D F A♭", spell("Bdim7", c_major));
+        assert_eq!("A C♯ E G", spell("A7", c_major));
+        assert_eq!("D F♯ A C E G♯", spell("D9#11", c_major));
+        assert_eq!("C E G B♭ D♯", spell("C7#9", c_major));
+        assert_eq!("C F♯ G", spell("CLyd", c_major));
+
+        let e_major = Scale::major(NoteName::E);
+        assert_eq!("C♯ E G♯ B", spell("C#m7", e_major));
+        assert_eq!("G♯ B♯ D♯ F♯", spell("G#7", e_major));
+        assert_eq!("D♯ F𝄪 A♯", spell("D#", e_major));
+    }
+
+    #[test]
+    fn bass() {
+        let c_major = Scale::major(NoteName::C);
+
+        let symbol: ChordSymbol = "C+/G#".parse().unwrap();
+        let spelling = symbol.id.spell(symbol.tonic, c_major);
+        assert_eq!(Some(SpelledPitch::new(Letter::G, 1)), spelling.bass());
+
+        // not a chord tone
+        let symbol: ChordSymbol = "C/Bb".parse().unwrap();
+        let spelling = symbol.id.spell(symbol.tonic, c_major);
+        assert_eq!(Some(SpelledPitch::new(Letter::B, -1)), spelling.bass());
+    }
+
+    #[test]
+    fn octave() {
+        let b_sharp = SpelledPitch::new(Letter::B, 1);
+        assert_eq!(3, b_sharp.octave(60));
+
+        let c_flat = SpelledPitch::new(Letter::C, -1);
+        assert_eq!(4, c_flat.octave(59));
+
+        // the top note of B♭°⁷
+        let a_double_flat = SpelledPitch::new(Letter::A, -2);
+        assert_eq!(9, a_double_flat.octave(127));
+    }
+}
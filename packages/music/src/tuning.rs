@@ -0,0 +1,362 @@
+//! Frequencies of the notes under a few tuning systems
+//!
+//! Deviations are in cents from 12-tone equal temperament with A4 at the reference pitch. Just
+//! intonation is tuned from the tonic; the other temperaments are laid out from C, the way
+//! they were used on keyboards
+
+use core::f64::consts::LN_2;
+
+use crate::{Note, NoteName};
+
+pub const STANDARD_A4: f64 = 440.;
+
+/// Ratios of the 5-limit just intervals, from the unison to the major seventh
+const JUST_RATIOS: [(u32, u32); 12] = [
+    (1, 1),
+    (16, 15),
+    (9, 8),
+    (6, 5),
+    (5, 4),
+    (4, 3),
+    (45, 32),
+    (3, 2),
+    (8, 5),
+    (5, 3),
+    (16, 9),
+    (15, 8),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Temperament {
+    Equal,
+    /// 5-limit, with pure thirds and fifths above the tonic
+    Just,
+    /// pure fifths from E♭ to G♯
+    Pythagorean,
+    /// fifths narrowed by a quarter of the syntonic comma from E♭ to G♯, for pure major thirds
+    QuarterCommaMeantone,
+    /// Werckmeister III: C–G–D–A and B–F♯ narrowed by a quarter of the Pythagorean comma
+    Werckmeister,
+    /// F–C–G–D–A–E–B narrowed by a sixth of the Pythagorean comma
+    Vallotti,
+}
+
+impl Temperament {
+    pub const ALL: [Self; 6] = [
+        Self::Equal,
+        Self::Just,
+        Self::Pythagorean,
+        Self::QuarterCommaMeantone,
+        Self::Werckmeister,
+        Self::Vallotti,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Temperament::Equal => "equal",
+            Temperament::Just => "just",
+            Temperament::Pythagorean => "Pythagorean",
+            Temperament::QuarterCommaMeantone => "¼-comma meantone",
+            Temperament::Werckmeister => "Werckmeister III",
+            Temperament::Vallotti => "Vallotti",
+        }
+    }
+
+    /// Deviation from equal temperament of each pitch class above the reference, in cents
+    fn deviations(&self) -> [f64; 12] {
+        let pure_fifth = cents(3. / 2.) - 700.;
+        let pythagorean_comma = 12. * pure_fifth;
+        let syntonic_comma = cents(81. / 80.);
+
+        match self {
+            Temperament::Equal => [0.; 12],
+
+            Temperament::Just => {
+                let mut deviations = [0.; 12];
+                for (half_steps, (num, den)) in JUST_RATIOS.into_iter().enumerate() {
+                    deviations[half_steps] =
+                        cents(f64::from(num) / f64::from(den)) - 100. * half_steps as f64;
+                }
+                deviations
+            }
+
+            Temperament::Pythagorean => chain(pure_fifth),
+
+            Temperament::QuarterCommaMeantone => chain(pure_fifth - syntonic_comma / 4.),
+
+            Temperament::Werckmeister => {
+                let narrow = pure_fifth - pythagorean_comma / 4.;
+                circle([
+                    narrow, narrow, narrow, pure_fifth, pure_fifth, narrow, pure_fifth, pure_fifth,
+                    pure_fifth, pure_fifth, pure_fifth,
+                ])
+            }
+
+            Temperament::Vallotti => {
+                let narrow = pure_fifth - pythagorean_comma / 6.;
+                circle([
+                    narrow, narrow, narrow, narrow, narrow, pure_fifth, pure_fifth, pure_fifth,
+                    pure_fifth, pure_fifth, pure_fifth,
+                ])
+            }
+        }
+    }
+}
+
+/// Fifths of the same size from E♭ to G♯; `fifth` is its deviation from the tempered fifth
+fn chain(fifth: f64) -> [f64; 12] {
+    let mut deviations = [0.; 12];
+    for fifths in -3i8..=8 {
+        deviations[(fifths * 7).rem_euclid(12) as usize] = f64::from(fifths) * fifth;
+    }
+    deviations
+}
+
+/// The circle of fifths from C to F, each deviating from the tempered fifth by the given cents.
+/// The last fifth, F–C, closes the circle
+fn circle(fifths: [f64; 11]) -> [f64; 12] {
+    let mut deviations = [0.; 12];
+    let mut deviation = 0.;
+    for (index, fifth) in fifths.into_iter().enumerate() {
+        deviation += fifth;
+        deviations[(index + 1) * 7 % 12] = deviation;
+    }
+    deviations
+}
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub struct Tuning {
+    /// Frequency of A4, in Hz
+    pub a4: f64,
+    pub temperament: Temperament,
+    /// Only just intonation depends on it
+    pub tonic: NoteName,
+}
+
+impl Tuning {
+    pub const fn new(a4: f64, temperament: Temperament, tonic: NoteName) -> Self {
+        Self {
+            a4,
+            temperament,
+            tonic,
+        }
+    }
+
+    /// Deviation from equal temperament, in cents
+    pub fn deviation(&self, name: NoteName) -> f64 {
+        let reference = match self.temperament {
+            Temperament::Just => self.tonic,
+            _ => NoteName::C,
+        };
+        let deviations = self.temperament.deviations();
+        let above = |name: NoteName| deviations[reference.distance_to(name) as usize];
+
+        // A4 keeps the reference pitch
+        above(name) - above(NoteName::A)
+    }
+
+    /// In Hz
+    pub fn frequency(&self, note: Note) -> f64 {
+        let cents = 100. * (f64::from(note.as_u8()) - 69.) + self.deviation(note.name());
+        self.a4 * exp2(cents / 1_200.)
+    }
+
+    /// Size of the interval from `from` to `to`, in cents; negative when descending
+    pub fn cents_between(&self, from: Note, to: Note) -> f64 {
+        100. * f64::from(from.distance_to(to)) + self.deviation(to.name())
+            - self.deviation(from.name())
+    }
+
+    /// How much wider, in cents, the interval between two notes is than its just ratio, e.g.
+    /// about 14 for an equal tempered major third
+    pub fn impurity(&self, from: Note, to: Note) -> f64 {
+        let (low, high) = if from <= to { (from, to) } else { (to, from) };
+        let half_steps = low.distance_to(high) as u8;
+        let (num, den) = JUST_RATIOS[usize::from(half_steps % 12)];
+        let pure = cents(f64::from(num) / f64::from(den)) + 1_200. * f64::from(half_steps / 12);
+
+        self.cents_between(low, high) - pure
+    }
+
+    /// The key closest to the `n`th partial of `fundamental`, the fundamental being the first
+    /// one, and how far above that key the partial sounds, in cents
+    pub fn partial(&self, fundamental: Note, n: u8) -> Option<(Note, f64)> {
+        let above = cents(f64::from(n));
+        let key = fundamental.step(round(above / 100.) as i8).ok()?;
+
+        Some((key, above - self.cents_between(fundamental, key)))
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self::new(STANDARD_A4, Temperament::Equal, NoteName::C)
+    }
+}
+
+/// Size of a frequency ratio, in cents
+pub fn cents(ratio: f64) -> f64 {
+    1_200. * log2(ratio)
+}
+
+/// `core` has no floating point math functions
+fn log2(x: f64) -> f64 {
+    if x <= 0. {
+        return f64::NEG_INFINITY;
+    }
+
+    // x = m * 2^e with m in [1, 2) for normal numbers
+    let bits = x.to_bits();
+    let exponent = ((bits >> 52) & 0x7ff) as i32 - 1_023;
+    let mantissa = f64::from_bits((bits & 0x000f_ffff_ffff_ffff) | (1_023 << 52));
+
+    // ln(m) = 2 atanh(z) with z = (m - 1) / (m + 1) in [0, 1/3)
+    let z = (mantissa - 1.) / (mantissa + 1.);
+    let z2 = z * z;
+    let mut power = z;
+    let mut ln = 0.;
+    for k in 0..20 {
+        ln += power / f64::from(2 * k + 1);
+        power *= z2;
+    }
+
+    f64::from(exponent) + 2. * ln / LN_2
+}
+
+/// `2^x`; `core` has no floating point math functions
+fn exp2(x: f64) -> f64 {
+    let whole = round(x);
+    // e^y with y in [-ln(2) / 2, ln(2) / 2] by its Taylor series
+    let y = (x - f64::from(whole)) * LN_2;
+    let mut result = 1.;
+    let mut term = 1.;
+    for k in 1..16 {
+        term *= y / f64::from(k);
+        result += term;
+    }
+
+    let scale = if whole < 0 { 0.5 } else { 2. };
+    for _ in 0..whole.unsigned_abs() {
+        result *= scale;
+    }
+    result
+}
+
+fn round(x: f64) -> i32 {
+    if x < 0. {
+        (x - 0.5) as i32
+    } else {
+        (x + 0.5) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 0.01,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn math() {
+        assert_close(1_200., cents(2.));
+        assert_close(701.955, cents(1.5));
+        assert_close(-386.314, cents(0.8));
+        assert_close(1_000., exp2(log2(1_000.)));
+        assert_close(0.125, exp2(-3.));
+    }
+
+    #[test]
+    fn frequency() {
+        let equal = Tuning::default();
+        assert_close(440., equal.frequency(Note::A4));
+        assert_close(261.626, equal.frequency(Note::C4));
+        assert_close(27.5, equal.frequency(Note::A0));
+
+        let baroque = Tuning::new(415., Temperament::Werckmeister, NoteName::C);
+        assert_close(415., baroque.frequency(Note::A4));
+    }
+
+    #[test]
+    fn deviations() {
+        let tuning = |temperament| Tuning::new(STANDARD_A4, temperament, NoteName::C);
+
+        // relative to C
+        let deviation = |temperament, name| {
+            let tuning = tuning(temperament);
+            tuning.deviation(name) - tuning.deviation(NoteName::C)
+        };
+
+        assert_close(-13.686, deviation(Temperament::Just, NoteName::E));
+        assert_close(
+            -13.686,
+            deviation(Temperament::QuarterCommaMeantone, NoteName::E),
+        );
+        assert_close(7.820, deviation(Temperament::Pythagorean, NoteName::E));
+        assert_close(-9.775, deviation(Temperament::Werckmeister, NoteName::E));
+        assert_close(-11.730, deviation(Temperament::Werckmeister, NoteName::Gb));
+        assert_close(-9.775, deviation(Temperament::Vallotti, NoteName::B));
+        assert_close(0., deviation(Temperament::Vallotti, NoteName::Bb));
+
+        for name in NoteName::CIRCLE_OF_FIFTHS {
+            assert_close(0., deviation(Temperament::Equal, name));
+        }
+
+        // just intonation follows the tonic
+        let d_just = Tuning::new(STANDARD_A4, Temperament::Just, NoteName::D);
+        assert_close(
+            -13.686,
+            d_just.deviation(NoteName::Gb) - d_just.deviation(NoteName::D),
+        );
+    }
+
+    #[test]
+    fn impurity() {
+        let equal = Tuning::default();
+        assert_close(13.686, equal.impurity(Note::C4, Note::E4));
+        assert_close(-1.955, equal.impurity(Note::C4, Note::G4));
+        assert_close(-1.955, equal.impurity(Note::G4, Note::C4));
+        assert_close(0., equal.impurity(Note::C4, Note::C5));
+
+        let meantone = Tuning::new(STANDARD_A4, Temperament::QuarterCommaMeantone, NoteName::C);
+        assert_close(0., meantone.impurity(Note::C4, Note::E4));
+        assert_close(-5.377, meantone.impurity(Note::C4, Note::G4));
+
+        let just = Tuning::new(STANDARD_A4, Temperament::Just, NoteName::C);
+        assert_close(0., just.impurity(Note::C4, Note::E4));
+        assert_close(0., just.impurity(Note::C4, Note::G5));
+        // the wolf fifth of just intonation
+        assert_close(-21.506, just.impurity(Note::D4, Note::A4));
+    }
+
+    #[test]
+    fn partials() {
+        let equal = Tuning::default();
+
+        let (key, cents) = equal.partial(Note::C4, 2).unwrap();
+        assert_eq!(Note::C5, key);
+        assert_close(0., cents);
+
+        let (key, cents) = equal.partial(Note::C4, 3).unwrap();
+        assert_eq!(Note::G5, key);
+        assert_close(1.955, cents);
+
+        let (key, cents) = equal.partial(Note::C4, 5).unwrap();
+        // E6
+        assert_eq!(Note::from_u8_lossy(88), key);
+        assert_close(-13.686, cents);
+
+        let (key, cents) = equal.partial(Note::C4, 7).unwrap();
+        // B♭6
+        assert_eq!(Note::from_u8_lossy(94), key);
+        assert_close(-31.174, cents);
+
+        assert_eq!(None, equal.partial(Note::from_u8_lossy(120), 8));
+    }
+}
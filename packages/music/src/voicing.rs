@@ -0,0 +1,320 @@
+//! Piano voicings of a chord
+
+use core::ops::RangeInclusive;
+
+use crate::chord_id::ChordId;
+use crate::{Interval, Note, NoteName, Notes};
+
+/// Most notes in a voicing: four chord tones, three extensions and a bass note
+const MAX_LEN: usize = 8;
+
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum Voicing {
+    /// All the chord tones within an octave above the root; the extensions on top
+    Close,
+    /// Close position with the second highest note an octave lower
+    Drop2,
+    /// Close position with the third highest note an octave lower
+    Drop3,
+    /// Root, seventh and third, e.g. C B♭ E
+    Shell,
+    /// Third, fifth, seventh and ninth, e.g. E G B♭ D. Dominant thirteenths replace the fifth
+    RootlessA,
+    /// Seventh, ninth, third and fifth, e.g. B♭ D E G
+    RootlessB,
+    /// Root and fifth, and the other tones in the octave above
+    Spread,
+}
+
+impl Voicing {
+    pub const ALL: [Self; 7] = [
+        Self::Close,
+        Self::Drop2,
+        Self::Drop3,
+        Self::Shell,
+        Self::RootlessA,
+        Self::RootlessB,
+        Self::Spread,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Voicing::Close => "Close",
+            Voicing::Drop2 => "Drop 2",
+            Voicing::Drop3 => "Drop 3",
+            Voicing::Shell => "Shell",
+            Voicing::RootlessA => "Rootless A",
+            Voicing::RootlessB => "Rootless B",
+            Voicing::Spread => "Spread",
+        }
+    }
+
+    /// The lowest placement of the voicing within `range` whose intervals are all above their
+    /// low interval limit. `None` if the chord lacks the tones the voicing is built from, e.g. a
+    /// seventh for shells, or if no placement fits
+    pub fn voice(
+        &self,
+        tonic: NoteName,
+        id: &ChordId,
+        range: RangeInclusive<Note>,
+    ) -> Option<Notes> {
+        let mut stack = self.stack(id)?;
+
+        if let Some(bass) = id.bass {
+            let pitch_class = (12 + bass as i8 - tonic as i8) % 12;
+            let lowest = stack.offsets().iter().min().copied().unwrap_or_default();
+            let mut offset = lowest - (lowest - pitch_class).rem_euclid(12);
+            if offset == lowest {
+                offset -= 12;
+            }
+            stack.push(offset);
+        }
+
+        stack.sort();
+
+        let lowest = i16::from(*stack.offsets().first()?);
+        let highest = i16::from(*stack.offsets().last()?);
+        let (low, high) = (
+            i16::from(range.start().as_u8()),
+            i16::from(range.end().as_u8()),
+        );
+
+        let mut root = i16::from(tonic as u8);
+        while root + lowest < low {
+            root += 12;
+        }
+
+        while root + highest <= high {
+            let notes = stack
+                .offsets()
+                .iter()
+                .map(|offset| Note::from_u8_lossy((root + i16::from(*offset)) as u8));
+
+            if !is_muddy(notes.clone()) {
+                return Some(notes.collect());
+            }
+
+            root += 12;
+        }
+
+        None
+    }
+
+    /// Offsets from the root in half steps
+    fn stack(&self, id: &ChordId) -> Option<Stack> {
+        let intervals = id.kind.intervals();
+        let extensions = id.extensions.half_steps();
+
+        // the suspended tones play the role of the third and sixths the role of the seventh
+        let third = intervals.iter().find(|i| (2..=5).contains(*i)).copied();
+        let fifth = intervals.iter().find(|i| (6..=8).contains(*i)).copied();
+        let seventh = intervals.iter().find(|i| (9..=11).contains(*i)).copied();
+
+        let mut stack = Stack::empty();
+        match self {
+            Voicing::Close | Voicing::Drop2 | Voicing::Drop3 => {
+                for half_steps in intervals.iter().copied().chain(extensions) {
+                    stack.push(half_steps as i8);
+                }
+
+                match self {
+                    Voicing::Drop2 => stack.lower(2)?,
+                    Voicing::Drop3 => stack.lower(3)?,
+                    _ => {}
+                }
+            }
+
+            Voicing::Shell => {
+                stack.push(0);
+                stack.push(seventh? as i8);
+                stack.push(third? as i8 + 12);
+            }
+
+            Voicing::RootlessA | Voicing::RootlessB => {
+                let (third, seventh) = (third? as i8, seventh? as i8);
+
+                let mut ninth = 14;
+                let mut fifth = fifth.unwrap_or(7) as i8;
+                for half_steps in extensions {
+                    match half_steps {
+                        13..=15 => ninth = half_steps as i8,
+                        // only in dominant chords
+                        21 if seventh == 10 && third == 4 => fifth = 9,
+                        _ => {}
+                    }
+                }
+
+                let offsets = if *self == Voicing::RootlessA {
+                    [third, fifth, seventh, ninth]
+                } else {
+                    [seventh, ninth, third + 12, fifth + 12]
+                };
+
+                for offset in offsets {
+                    stack.push(offset);
+                }
+            }
+
+            Voicing::Spread => {
+                let bottom = fifth.or(seventh)?;
+                stack.push(0);
+                stack.push(bottom as i8);
+
+                for half_steps in intervals.iter().copied().chain(extensions) {
+                    if half_steps != 0 && half_steps != bottom {
+                        stack.push((half_steps % 12) as i8 + 12);
+                    }
+                }
+            }
+        }
+
+        Some(stack)
+    }
+}
+
+/// Whether any two neighboring notes are below their low interval limit
+fn is_muddy(mut notes: impl Iterator<Item = Note>) -> bool {
+    let Some(mut lower) = notes.next() else {
+        return false;
+    };
+
+    for upper in notes {
+        let half_steps = lower.distance_to(upper).unsigned_abs();
+        // `Interval` does not name wider intervals, which are never muddy anyways
+        if half_steps <= 24 && Interval::from_u8_lossy(half_steps).is_below_low_limit(lower) {
+            return true;
+        }
+
+        lower = upper;
+    }
+
+    false
+}
+
+struct Stack {
+    len: usize,
+    offsets: [i8; MAX_LEN],
+}
+
+impl Stack {
+    const fn empty() -> Self {
+        Self {
+            len: 0,
+            offsets: [0; MAX_LEN],
+        }
+    }
+
+    fn offsets(&self) -> &[i8] {
+        &self.offsets[..self.len]
+    }
+
+    fn push(&mut self, offset: i8) {
+        self.offsets[self.len] = offset;
+        self.len += 1;
+    }
+
+    fn sort(&mut self) {
+        self.offsets[..self.len].sort_unstable();
+    }
+
+    /// Lowers the `nth` highest note by an octave
+    fn lower(&mut self, nth: usize) -> Option<()> {
+        self.sort();
+        let index = self.len.checked_sub(nth)?;
+        if index == 0 {
+            // lowering the lowest note only inverts the chord
+            return None;
+        }
+
+        self.offsets[index] -= 12;
+        self.sort();
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ChordSymbol;
+
+    const WIDE: RangeInclusive<Note> = Note::C3..=Note::C8;
+
+    fn voice(voicing: Voicing, s: &str, range: RangeInclusive<Note>) -> Option<Notes> {
+        let symbol: ChordSymbol = s.parse().unwrap();
+        voicing.voice(symbol.tonic, &symbol.id, range)
+    }
+
+    #[test]
+    fn close() {
+        assert_eq!(
+            Some(notes![C4, E4, G4, Bb4, D5]),
+            voice(Voicing::Close, "C9", Note::C4..=Note::C8)
+        );
+
+        // the bass goes below the chord
+        assert_eq!(
+            Some(notes![E3, C4, E4, G4]),
+            voice(Voicing::Close, "C/E", WIDE)
+        );
+    }
+
+    #[test]
+    fn drop() {
+        // C E G B -> G C E B
+        assert_eq!(
+            Some(notes![G3, C4, E4, B4]),
+            voice(Voicing::Drop2, "Cmaj7", WIDE)
+        );
+
+        // C E G B -> E C G B
+        assert_eq!(
+            Some(notes![E3, C4, G4, B4]),
+            voice(Voicing::Drop3, "Cmaj7", WIDE)
+        );
+
+        assert_eq!(None, voice(Voicing::Drop3, "C", WIDE));
+    }
+
+    #[test]
+    fn jazz() {
+        assert_eq!(
+            Some(notes![C4, Bb4, E5]),
+            voice(Voicing::Shell, "C7", Note::C4..=Note::C8)
+        );
+        assert_eq!(None, voice(Voicing::Shell, "C", WIDE));
+
+        assert_eq!(
+            Some(notes![E4, A4, Bb4, D5]),
+            voice(Voicing::RootlessA, "C13", Note::C4..=Note::C8)
+        );
+        assert_eq!(
+            Some(notes![Bb4, D5, E5, G5]),
+            voice(Voicing::RootlessB, "C9", Note::Bb4..=Note::C8)
+        );
+    }
+
+    #[test]
+    fn spread() {
+        assert_eq!(
+            Some(notes![C3, G3, D4, E4, Bb4]),
+            voice(Voicing::Spread, "C9", WIDE)
+        );
+    }
+
+    #[test]
+    fn register() {
+        // a C major triad in close position is muddy below C3
+        let low = voice(Voicing::Close, "C", Note::A0..=Note::C8).unwrap();
+        assert_eq!(Some(Note::C3), low.lowest());
+
+        // too narrow
+        assert_eq!(None, voice(Voicing::Spread, "C9", Note::C4..=Note::C5));
+
+        for voicing in Voicing::ALL {
+            if let Some(notes) = voice(voicing, "Bb13#11", WIDE) {
+                assert!(notes.iter().all(|note| WIDE.contains(&note)), "{voicing:?}");
+            }
+        }
+    }
+}
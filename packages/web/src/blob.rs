@@ -0,0 +1,74 @@
+use js::{Downcast as _, Function, Upcast as _};
+
+use crate::Window;
+
+js::inheritance!(Blob: js::Object);
+
+impl Blob {
+    /// `parts` is an array of `Uint8Array`s, strings or other blobs
+    pub fn new(parts: &js::Array) -> Self {
+        let args = js::Array::new();
+        args.push(parts);
+        constructor(&"Blob".into())
+            .construct(&args)
+            .upcast()
+            .downcast()
+    }
+
+    pub fn array_buffer(&self) -> js::Promise<js::ArrayBuffer> {
+        unsafe { js::call!(self, arrayBuffer).unwrap_unchecked().downcast() }
+    }
+
+    pub fn size(&self) -> u32 {
+        unsafe { self.get(&"size".into()).unwrap_unchecked().to_u32() }
+    }
+}
+
+js::inheritance!(File: Blob);
+
+impl File {
+    pub fn name(&self) -> js::String {
+        unsafe { self.get(&"name".into()).unwrap_unchecked().downcast() }
+    }
+}
+
+js::inheritance!(FileList: js::Object);
+
+impl FileList {
+    pub fn item(&self, index: u32) -> Option<File> {
+        if index < self.length() {
+            js::call!(self, item, &js::Integer::from(index)).map(|value| value.downcast())
+        } else {
+            None
+        }
+    }
+
+    pub fn length(&self) -> u32 {
+        unsafe { self.get(&"length".into()).unwrap_unchecked().to_u32() }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Url;
+
+impl Url {
+    pub fn create_object_url(&self, blob: &Blob) -> js::String {
+        unsafe {
+            js::call!(self.object(), createObjectURL, blob)
+                .unwrap_unchecked()
+                .downcast()
+        }
+    }
+
+    pub fn revoke_object_url(&self, url: &js::String) {
+        js::call!(self.object(), revokeObjectURL, url);
+    }
+
+    fn object(&self) -> js::Object {
+        unsafe { Window.get(&"URL".into()).unwrap_unchecked().downcast() }
+    }
+}
+
+fn constructor(name: &js::String) -> Function {
+    unsafe { Window.get(name).unwrap_unchecked().downcast() }
+}
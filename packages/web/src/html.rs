@@ -8,9 +8,37 @@ mod form;
 js::inheritance!(HtmlElement: Element);
 
 impl HtmlElement {
+    pub fn click(&self) {
+        js::call!(self, click);
+    }
+
+    pub fn set_hidden(&self, hidden: bool) {
+        self.set(&"hidden".into(), hidden.as_ref())
+    }
+
     pub fn set_onchange(&self, listener: fn(Event)) {
         self.set(&"onchange".into(), &Function::from(listener))
     }
+
+    pub fn set_onclick(&self, listener: fn(Event)) {
+        self.set(&"onclick".into(), &Function::from(listener))
+    }
+}
+
+js::inheritance!(HtmlAnchorElement: HtmlElement);
+
+impl IsElement for HtmlAnchorElement {
+    const TAG_NAME: &'static str = "a";
+}
+
+impl HtmlAnchorElement {
+    pub fn set_download(&self, filename: &js::String) {
+        self.set(&"download".into(), filename)
+    }
+
+    pub fn set_href(&self, href: &js::String) {
+        self.set(&"href".into(), href)
+    }
 }
 
 js::inheritance!(HtmlDivElement: HtmlElement);
@@ -1,6 +1,31 @@
 use js::{Downcast, Integer};
 
-use crate::{HtmlElement, IsElement};
+use crate::{FileList, HtmlElement, IsElement};
+
+js::inheritance!(HtmlButtonElement: HtmlElement);
+
+impl IsElement for HtmlButtonElement {
+    const TAG_NAME: &'static str = "button";
+}
+
+impl HtmlButtonElement {
+    pub fn set_disabled(&self, disabled: bool) {
+        self.set(&"disabled".into(), disabled.as_ref())
+    }
+
+    /// NOTE buttons inside a form default to the "submit" type
+    pub fn set_type(&self, ty: &js::String) {
+        self.set(&"type".into(), ty)
+    }
+
+    pub fn set_value(&self, value: &js::String) {
+        self.set(&"value".into(), value)
+    }
+
+    pub fn value(&self) -> js::String {
+        unsafe { self.get(&"value".into()).unwrap_unchecked().downcast() }
+    }
+}
 
 js::inheritance!(HtmlFieldsetElement: HtmlElement);
 
@@ -14,6 +39,70 @@ impl IsElement for HtmlFormElement {
     const TAG_NAME: &'static str = "form";
 }
 
+js::inheritance!(HtmlInputElement: HtmlElement);
+
+impl IsElement for HtmlInputElement {
+    const TAG_NAME: &'static str = "input";
+}
+
+impl HtmlInputElement {
+    /// NOTE only valid for inputs of the "checkbox" and "radio" types
+    pub fn checked(&self) -> bool {
+        unsafe { self.get(&"checked".into()).unwrap_unchecked().to_u32() != 0 }
+    }
+
+    /// NOTE only valid for inputs of the "file" type
+    pub fn files(&self) -> FileList {
+        unsafe { self.get(&"files".into()).unwrap_unchecked().downcast() }
+    }
+
+    pub fn set_accept(&self, accept: &js::String) {
+        self.set(&"accept".into(), accept)
+    }
+
+    pub fn set_disabled(&self, disabled: bool) {
+        self.set(&"disabled".into(), disabled.as_ref())
+    }
+
+    pub fn set_max(&self, max: f64) {
+        self.set(&"max".into(), &js::Float::from(max))
+    }
+
+    pub fn set_min(&self, min: f64) {
+        self.set(&"min".into(), &js::Float::from(min))
+    }
+
+    pub fn set_step(&self, step: f64) {
+        self.set(&"step".into(), &js::Float::from(step))
+    }
+
+    pub fn set_type(&self, ty: &js::String) {
+        self.set(&"type".into(), ty)
+    }
+
+    pub fn set_value(&self, value: &js::String) {
+        self.set(&"value".into(), value)
+    }
+
+    pub fn set_value_as_number(&self, value: f64) {
+        self.set(&"valueAsNumber".into(), &js::Float::from(value))
+    }
+
+    pub fn value(&self) -> js::String {
+        unsafe { self.get(&"value".into()).unwrap_unchecked().downcast() }
+    }
+
+    pub fn value_as_number(&self) -> f64 {
+        unsafe {
+            let x: js::Float = self
+                .get(&"valueAsNumber".into())
+                .unwrap_unchecked()
+                .downcast();
+            x.into()
+        }
+    }
+}
+
 js::inheritance!(HtmlLegendElement: HtmlElement);
 
 impl IsElement for HtmlLegendElement {
@@ -27,6 +116,14 @@ impl IsElement for HtmlOptionElement {
 }
 
 impl HtmlOptionElement {
+    pub fn selected(&self) -> bool {
+        unsafe { self.get(&"selected".into()).unwrap_unchecked().to_u32() != 0 }
+    }
+
+    pub fn set_selected(&self, selected: bool) {
+        self.set(&"selected".into(), selected.as_ref())
+    }
+
     pub fn set_value(&self, value: &js::String) {
         self.set(&"value".into(), value)
     }
@@ -43,6 +140,14 @@ impl HtmlSelectElement {
         js::call!(self, add, option);
     }
 
+    pub fn set_disabled(&self, disabled: bool) {
+        self.set(&"disabled".into(), disabled.as_ref())
+    }
+
+    pub fn set_multiple(&self, multiple: bool) {
+        self.set(&"multiple".into(), multiple.as_ref())
+    }
+
     pub fn set_required(&self, required: bool) {
         self.set_attribute(&"required".into(), required.as_ref())
     }
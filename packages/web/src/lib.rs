@@ -1,5 +1,6 @@
 #![no_std]
 
+pub use blob::{Blob, File, FileList, Url};
 pub use body::Body;
 pub use console::Console;
 pub use document::Document;
@@ -12,7 +13,9 @@ pub use navigator::Navigator;
 pub use node::Node;
 pub use performance::Performance;
 pub use svg::*;
+pub use window::Window;
 
+mod blob;
 mod body;
 mod console;
 mod document;
@@ -27,6 +30,7 @@ mod navigator;
 mod node;
 mod performance;
 mod svg;
+mod window;
 
 pub trait IsElement: js::Upcast<Supertype = HtmlElement> {
     const TAG_NAME: &'static str;
@@ -56,3 +60,22 @@ pub fn queue_microtask(function: fn()) {
 
     unsafe { ff(function as usize as u32) }
 }
+
+/// Returns an ID that can be passed to `clear_timeout`
+pub fn set_timeout(function: fn(), delay_ms: f64) -> u32 {
+    unsafe extern "C" {
+        #[link_name = "$setTimeout"]
+        fn ff(f: u32, delay: f64) -> u32;
+    }
+
+    unsafe { ff(function as usize as u32, delay_ms) }
+}
+
+pub fn clear_timeout(id: u32) {
+    unsafe extern "C" {
+        #[link_name = "$clearTimeout"]
+        fn ff(id: u32);
+    }
+
+    unsafe { ff(id) }
+}
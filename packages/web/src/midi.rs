@@ -8,6 +8,24 @@ impl MIDIAccess {
     pub fn inputs(&self) -> MIDIInputMap {
         unsafe { self.get(&"inputs".into()).unwrap_unchecked().downcast() }
     }
+
+    /// Called when a port is connected, disconnected, opened or closed
+    pub fn set_onstatechange(&self, callback: Option<fn(MIDIConnectionEvent)>) {
+        let property = "onstatechange".into();
+        if let Some(f) = callback {
+            self.set(&property, &Function::from(f));
+        } else {
+            self.set(&property, &js::Null);
+        }
+    }
+}
+
+js::inheritance!(MIDIConnectionEvent: Event);
+
+impl MIDIConnectionEvent {
+    pub fn port(&self) -> MIDIPort {
+        unsafe { self.get(&"port".into()).unwrap_unchecked().downcast() }
+    }
 }
 
 js::inheritance!(MIDIInputMap: js::Map);
@@ -42,9 +60,53 @@ impl MIDIInput {
 js::inheritance!(MIDIPort: EventTarget);
 
 impl MIDIPort {
+    pub fn connection(&self) -> MIDIPortConnectionState {
+        let connection = self.string(&"connection".into());
+        if connection == "open".into() {
+            MIDIPortConnectionState::Open
+        } else if connection == "pending".into() {
+            MIDIPortConnectionState::Pending
+        } else {
+            MIDIPortConnectionState::Closed
+        }
+    }
+
+    pub fn id(&self) -> js::String {
+        self.string(&"id".into())
+    }
+
+    pub fn manufacturer(&self) -> js::String {
+        self.string(&"manufacturer".into())
+    }
+
     pub fn name(&self) -> js::String {
         unsafe { self.get(&"name".into()).unwrap_unchecked().downcast() }
     }
+
+    pub fn state(&self) -> MIDIPortDeviceState {
+        if self.string(&"state".into()) == "connected".into() {
+            MIDIPortDeviceState::Connected
+        } else {
+            MIDIPortDeviceState::Disconnected
+        }
+    }
+
+    fn string(&self, property: &js::String) -> js::String {
+        unsafe { self.get(property).unwrap_unchecked().downcast() }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MIDIPortConnectionState {
+    Open,
+    Closed,
+    Pending,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MIDIPortDeviceState {
+    Connected,
+    Disconnected,
 }
 
 js::inheritance!(MIDIMessageEvent: Event);
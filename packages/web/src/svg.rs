@@ -89,6 +89,10 @@ impl SVGRectElement {
         self.set_attribute(&"width".into(), width);
     }
 
+    pub fn set_fill_opacity(&self, x: &js::Value) {
+        self.set_attribute(&"fill-opacity".into(), x);
+    }
+
     pub fn set_opacity(&self, x: &js::Value) {
         self.set_attribute(&"opacity".into(), x);
     }
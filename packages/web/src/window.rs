@@ -0,0 +1,15 @@
+use js::Object;
+
+/// The global object, i.e. `globalThis`
+#[derive(Clone, Copy)]
+pub struct Window;
+
+impl core::ops::Deref for Window {
+    type Target = Object;
+
+    fn deref(&self) -> &Self::Target {
+        static INDEX: u32 = 8; // see app.js.j2
+
+        unsafe { &*core::ptr::addr_of!(INDEX).cast() }
+    }
+}
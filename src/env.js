@@ -6,6 +6,10 @@ function $Array$push(array, value) {
     HEAP.get(array).push(HEAP.get(value));
 }
 
+function $Function$construct(func, args) {
+    return HEAP.add(new (HEAP.get(func))(...HEAP.get(args)));
+}
+
 function $Function$from(func) {
     return HEAP.add(function (...args) {
         WASM.function(func)(...args.map((arg) => HEAP.add(arg)));
@@ -54,6 +58,14 @@ function $Performance$now() {
     return performance.now();
 }
 
+function $String$encode_into(string, ptr, len) {
+    return new TextEncoder().encodeInto(HEAP.get(string), WASM.memory(ptr, len)).written;
+}
+
+function $String$eq(left, right) {
+    return HEAP.get(left) === HEAP.get(right);
+}
+
 function $String$from_str(ptr, len) {
     return HEAP.add(WASM.text(ptr, len));
 }
@@ -62,6 +74,14 @@ function $Uint8Array$copy_to_slice(array, ptr, len) {
     WASM.memory(ptr, len).set(HEAP.get(array));
 }
 
+function $Uint8Array$from_buffer(buffer) {
+    return HEAP.add(new Uint8Array(HEAP.get(buffer)));
+}
+
+function $Uint8Array$from_slice(ptr, len) {
+    return HEAP.add(WASM.memory(ptr, len).slice());
+}
+
 function $Value$clone(value) {
     return HEAP.add(HEAP.get(value));
 }
@@ -74,6 +94,10 @@ function $Value$to_u32(value) {
     return HEAP.get(value);
 }
 
+function $clearTimeout(id) {
+    clearTimeout(id);
+}
+
 function $panic() {
     throw new Error("panicked");
 }
@@ -81,3 +105,7 @@ function $panic() {
 function $queueMicrotask(func) {
     queueMicrotask(WASM.function(func));
 }
+
+function $setTimeout(func, delay) {
+    return setTimeout(WASM.function(func), delay);
+}